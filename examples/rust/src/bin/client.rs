@@ -19,18 +19,24 @@ use {
     tonic::transport::channel::ClientTlsConfig,
     yellowstone_grpc_client::{GeyserGrpcClient, GeyserGrpcClientError, Interceptor},
     yellowstone_grpc_proto::{
+        account_diff::AccountDiffState,
         convert_from,
         plugin::filter::message::FilteredUpdate,
         prelude::{
             subscribe_request_filter_accounts_filter::Filter as AccountsFilterOneof,
             subscribe_request_filter_accounts_filter_lamports::Cmp as AccountsFilterLamports,
             subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
-            subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
-            SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
-            SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterLamports,
+            subscribe_request_filter_transactions_cmp::Cmp as TransactionsFilterCmp,
+            subscribe_update::UpdateOneof, AccountEncoding, CommitmentLevel, SlotRollbackReason,
+            SubscribeRequest, SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
+            SubscribeRequestFilterAccountsDataSlice, SubscribeRequestFilterAccountsFilter,
+            SubscribeRequestFilterAccountsFilterLamports,
             SubscribeRequestFilterAccountsFilterMemcmp, SubscribeRequestFilterBlocks,
             SubscribeRequestFilterBlocksMeta, SubscribeRequestFilterEntry,
-            SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions, SubscribeRequestPing,
+            SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions,
+            SubscribeRequestFilterTransactionsCmp,
+            SubscribeRequestFilterTransactionsInstructionData,
+            SubscribeRequestFilterTransactionsTokenBalance, SubscribeRequestPing,
             SubscribeUpdateAccountInfo, SubscribeUpdateEntry, SubscribeUpdateTransactionInfo,
         },
         prost::Message,
@@ -178,6 +184,23 @@ impl From<ArgsCommitment> for CommitmentLevel {
     }
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ArgsAccountEncoding {
+    Raw,
+    JsonParsed,
+    RawAndJsonParsed,
+}
+
+impl From<ArgsAccountEncoding> for AccountEncoding {
+    fn from(encoding: ArgsAccountEncoding) -> Self {
+        match encoding {
+            ArgsAccountEncoding::Raw => AccountEncoding::Raw,
+            ArgsAccountEncoding::JsonParsed => AccountEncoding::JsonParsed,
+            ArgsAccountEncoding::RawAndJsonParsed => AccountEncoding::RawAndJsonParsed,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Subcommand)]
 enum Action {
     HealthCheck,
@@ -206,6 +229,10 @@ struct ActionSubscribe {
     /// Filter by presence of field txn_signature
     accounts_nonempty_txn_signature: Option<bool>,
 
+    /// Encoding of account data
+    #[clap(long)]
+    accounts_encoding: Option<ArgsAccountEncoding>,
+
     /// Filter by Account Pubkey
     #[clap(long)]
     accounts_account: Vec<String>,
@@ -226,10 +253,22 @@ struct ActionSubscribe {
     #[clap(long)]
     accounts_datasize: Option<u64>,
 
+    /// Filter by minimum Data size
+    #[clap(long)]
+    accounts_datasize_min: Option<u64>,
+
+    /// Filter by maximum Data size
+    #[clap(long)]
+    accounts_datasize_max: Option<u64>,
+
     /// Filter valid token accounts
     #[clap(long)]
     accounts_token_account_state: bool,
 
+    /// Filter by lamports change since previous update, requires account or owner filter
+    #[clap(long)]
+    accounts_lamports_delta: Option<u64>,
+
     /// Filter by lamports, format: `eq:42` / `ne:42` / `lt:42` / `gt:42`
     #[clap(long)]
     accounts_lamports: Vec<String>,
@@ -238,6 +277,10 @@ struct ActionSubscribe {
     #[clap(long)]
     accounts_data_slice: Vec<String>,
 
+    /// Receive only part of updated data account for accounts filter, format: `offset,size`
+    #[clap(long)]
+    accounts_filter_data_slice: Vec<String>,
+
     /// Subscribe on slots updates
     #[clap(long)]
     slots: bool,
@@ -274,6 +317,66 @@ struct ActionSubscribe {
     #[clap(long)]
     transactions_account_required: Vec<String>,
 
+    /// Filter transactions signed by any of accounts
+    #[clap(long)]
+    transactions_signer_include: Vec<String>,
+
+    /// Filter transactions by fee payer
+    #[clap(long)]
+    transactions_fee_payer: Vec<String>,
+
+    /// Filter by compute units consumed, format: `eq:42` / `ne:42` / `lt:42` / `gt:42`
+    #[clap(long)]
+    transactions_compute_units_consumed: Vec<String>,
+
+    /// Filter by fee, format: `eq:42` / `ne:42` / `lt:42` / `gt:42`
+    #[clap(long)]
+    transactions_fee: Vec<String>,
+
+    /// Include log messages into transactions
+    #[clap(long)]
+    transactions_include_logs: Option<bool>,
+
+    /// Include inner instructions into transactions
+    #[clap(long)]
+    transactions_include_inner_instructions: Option<bool>,
+
+    /// Include token balances into transactions
+    #[clap(long)]
+    transactions_include_token_balances: Option<bool>,
+
+    /// Include rewards into transactions
+    #[clap(long)]
+    transactions_include_rewards: Option<bool>,
+
+    /// Filter invoked program in transactions
+    #[clap(long)]
+    transactions_program_include: Vec<String>,
+
+    /// Filter not invoked program in transactions
+    #[clap(long)]
+    transactions_program_exclude: Vec<String>,
+
+    /// Match programs in inner instructions too
+    #[clap(long)]
+    transactions_inner_instructions: Option<bool>,
+
+    /// Filter by instruction data, format: `program_id,offset,data in base58`
+    #[clap(long)]
+    transactions_instruction_data: Vec<String>,
+
+    /// Filter by substring in log messages
+    #[clap(long)]
+    transactions_log_contains: Vec<String>,
+
+    /// Filter by regex in log messages
+    #[clap(long)]
+    transactions_log_regex: Vec<String>,
+
+    /// Filter by token balance change, format: `mint[,owner[,min_delta]]`
+    #[clap(long)]
+    transactions_token_balance: Vec<String>,
+
     /// Subscribe on transactions_status updates
     #[clap(long)]
     transactions_status: bool,
@@ -302,6 +405,50 @@ struct ActionSubscribe {
     #[clap(long)]
     transactions_status_account_required: Vec<String>,
 
+    /// Filter transactions signed by any of accounts
+    #[clap(long)]
+    transactions_status_signer_include: Vec<String>,
+
+    /// Filter transactions by fee payer
+    #[clap(long)]
+    transactions_status_fee_payer: Vec<String>,
+
+    /// Filter by compute units consumed, format: `eq:42` / `ne:42` / `lt:42` / `gt:42`
+    #[clap(long)]
+    transactions_status_compute_units_consumed: Vec<String>,
+
+    /// Filter by fee, format: `eq:42` / `ne:42` / `lt:42` / `gt:42`
+    #[clap(long)]
+    transactions_status_fee: Vec<String>,
+
+    /// Filter invoked program in transactions for transactions_status
+    #[clap(long)]
+    transactions_status_program_include: Vec<String>,
+
+    /// Filter not invoked program in transactions for transactions_status
+    #[clap(long)]
+    transactions_status_program_exclude: Vec<String>,
+
+    /// Match programs in inner instructions too for transactions_status
+    #[clap(long)]
+    transactions_status_inner_instructions: Option<bool>,
+
+    /// Filter by instruction data for transactions_status, format: `program_id,offset,data in base58`
+    #[clap(long)]
+    transactions_status_instruction_data: Vec<String>,
+
+    /// Filter by substring in log messages for transactions_status
+    #[clap(long)]
+    transactions_status_log_contains: Vec<String>,
+
+    /// Filter by regex in log messages for transactions_status
+    #[clap(long)]
+    transactions_status_log_regex: Vec<String>,
+
+    /// Filter by token balance change, format: `mint[,owner[,min_delta]]`
+    #[clap(long)]
+    transactions_status_token_balance: Vec<String>,
+
     #[clap(long)]
     entries: bool,
 
@@ -333,6 +480,18 @@ struct ActionSubscribe {
     #[clap(long)]
     ping: Option<i32>,
 
+    /// Replay stored messages starting from slot
+    #[clap(long)]
+    from_slot: Option<u64>,
+
+    /// Receive patches of account data instead of full data
+    #[clap(long)]
+    accounts_diff: Option<bool>,
+
+    /// Receive confirmed / finalized / dead status of processed transactions
+    #[clap(long)]
+    transactions_commitment_updates: Option<bool>,
+
     /// Resubscribe (only to slots) after
     #[clap(long)]
     resub: Option<usize>,
@@ -389,6 +548,21 @@ impl Action {
                             filter: Some(AccountsFilterOneof::Datasize(datasize)),
                         });
                     }
+                    if let Some(datasize) = args.accounts_datasize_min {
+                        filters.push(SubscribeRequestFilterAccountsFilter {
+                            filter: Some(AccountsFilterOneof::DatasizeMin(datasize)),
+                        });
+                    }
+                    if let Some(datasize) = args.accounts_datasize_max {
+                        filters.push(SubscribeRequestFilterAccountsFilter {
+                            filter: Some(AccountsFilterOneof::DatasizeMax(datasize)),
+                        });
+                    }
+                    if let Some(lamports_delta) = args.accounts_lamports_delta {
+                        filters.push(SubscribeRequestFilterAccountsFilter {
+                            filter: Some(AccountsFilterOneof::LamportsDelta(lamports_delta)),
+                        });
+                    }
                     if args.accounts_token_account_state {
                         filters.push(SubscribeRequestFilterAccountsFilter {
                             filter: Some(AccountsFilterOneof::TokenAccountState(true)),
@@ -427,6 +601,19 @@ impl Action {
                             account: accounts_account,
                             owner: args.accounts_owner.clone(),
                             filters,
+                            account_encoding: args
+                                .accounts_encoding
+                                .map(|encoding| AccountEncoding::from(encoding) as i32),
+                            data_slice: if args.accounts_filter_data_slice.is_empty() {
+                                None
+                            } else {
+                                Some(SubscribeRequestFilterAccountsDataSlice {
+                                    slices: Self::parse_data_slice(
+                                        &args.accounts_filter_data_slice,
+                                    )?,
+                                })
+                            },
+                            commitment: None,
                         },
                     );
                 }
@@ -437,6 +624,7 @@ impl Action {
                         "client".to_owned(),
                         SubscribeRequestFilterSlots {
                             filter_by_commitment: Some(args.slots_filter_by_commitment),
+                            commitment: None,
                         },
                     );
                 }
@@ -452,6 +640,29 @@ impl Action {
                             account_include: args.transactions_account_include.clone(),
                             account_exclude: args.transactions_account_exclude.clone(),
                             account_required: args.transactions_account_required.clone(),
+                            signer_include: args.transactions_signer_include.clone(),
+                            fee_payer: args.transactions_fee_payer.clone(),
+                            program_include: args.transactions_program_include.clone(),
+                            program_exclude: args.transactions_program_exclude.clone(),
+                            inner_instructions: args.transactions_inner_instructions,
+                            instruction_data: Self::parse_instruction_data(
+                                &args.transactions_instruction_data,
+                            )?,
+                            log_contains: args.transactions_log_contains.clone(),
+                            log_regex: args.transactions_log_regex.clone(),
+                            token_balance: Self::parse_token_balance(
+                                &args.transactions_token_balance,
+                            )?,
+                            compute_units_consumed: Self::parse_cmp(
+                                &args.transactions_compute_units_consumed,
+                            )?,
+                            fee: Self::parse_cmp(&args.transactions_fee)?,
+                            include_logs: args.transactions_include_logs,
+                            include_inner_instructions: args
+                                .transactions_include_inner_instructions,
+                            include_token_balances: args.transactions_include_token_balances,
+                            include_rewards: args.transactions_include_rewards,
+                            commitment: None,
                         },
                     );
                 }
@@ -467,13 +678,38 @@ impl Action {
                             account_include: args.transactions_status_account_include.clone(),
                             account_exclude: args.transactions_status_account_exclude.clone(),
                             account_required: args.transactions_status_account_required.clone(),
+                            signer_include: args.transactions_status_signer_include.clone(),
+                            fee_payer: args.transactions_status_fee_payer.clone(),
+                            program_include: args.transactions_status_program_include.clone(),
+                            program_exclude: args.transactions_status_program_exclude.clone(),
+                            inner_instructions: args.transactions_status_inner_instructions,
+                            instruction_data: Self::parse_instruction_data(
+                                &args.transactions_status_instruction_data,
+                            )?,
+                            log_contains: args.transactions_status_log_contains.clone(),
+                            log_regex: args.transactions_status_log_regex.clone(),
+                            token_balance: Self::parse_token_balance(
+                                &args.transactions_status_token_balance,
+                            )?,
+                            compute_units_consumed: Self::parse_cmp(
+                                &args.transactions_status_compute_units_consumed,
+                            )?,
+                            fee: Self::parse_cmp(&args.transactions_status_fee)?,
+                            include_logs: None,
+                            include_inner_instructions: None,
+                            include_token_balances: None,
+                            include_rewards: None,
+                            commitment: None,
                         },
                     );
                 }
 
                 let mut entries: EntryFilterMap = HashMap::new();
                 if args.entries {
-                    entries.insert("client".to_owned(), SubscribeRequestFilterEntry {});
+                    entries.insert(
+                        "client".to_owned(),
+                        SubscribeRequestFilterEntry { commitment: None },
+                    );
                 }
 
                 let mut blocks: BlocksFilterMap = HashMap::new();
@@ -485,28 +721,20 @@ impl Action {
                             include_transactions: args.blocks_include_transactions,
                             include_accounts: args.blocks_include_accounts,
                             include_entries: args.blocks_include_entries,
+                            commitment: None,
                         },
                     );
                 }
 
                 let mut blocks_meta: BlocksMetaFilterMap = HashMap::new();
                 if args.blocks_meta {
-                    blocks_meta.insert("client".to_owned(), SubscribeRequestFilterBlocksMeta {});
+                    blocks_meta.insert(
+                        "client".to_owned(),
+                        SubscribeRequestFilterBlocksMeta { commitment: None },
+                    );
                 }
 
-                let mut accounts_data_slice = Vec::new();
-                for data_slice in args.accounts_data_slice.iter() {
-                    match data_slice.split_once(',') {
-                        Some((offset, length)) => match (offset.parse(), length.parse()) {
-                            (Ok(offset), Ok(length)) => {
-                                accounts_data_slice
-                                    .push(SubscribeRequestAccountsDataSlice { offset, length });
-                            }
-                            _ => anyhow::bail!("invalid data_slice"),
-                        },
-                        _ => anyhow::bail!("invalid data_slice"),
-                    }
-                }
+                let accounts_data_slice = Self::parse_data_slice(&args.accounts_data_slice)?;
 
                 let ping = args.ping.map(|id| SubscribeRequestPing { id });
 
@@ -522,6 +750,11 @@ impl Action {
                         commitment: commitment.map(|x| x as i32),
                         accounts_data_slice,
                         ping,
+                        from_slot: args.from_slot,
+                        accounts_diff: args.accounts_diff,
+                        filter_delta: None,
+                        credits: None,
+                        transactions_commitment_updates: args.transactions_commitment_updates,
                     },
                     args.resub.unwrap_or(0),
                     args.stats,
@@ -531,6 +764,98 @@ impl Action {
             _ => None,
         })
     }
+
+    fn parse_instruction_data(
+        filters: &[String],
+    ) -> anyhow::Result<Vec<SubscribeRequestFilterTransactionsInstructionData>> {
+        filters
+            .iter()
+            .map(
+                |filter| match filter.splitn(3, ',').collect::<Vec<_>>()[..] {
+                    [program_id, offset, data] => {
+                        Ok(SubscribeRequestFilterTransactionsInstructionData {
+                            program_id: program_id.trim().to_string(),
+                            memcmp: Some(SubscribeRequestFilterAccountsFilterMemcmp {
+                                offset: offset
+                                    .parse()
+                                    .map_err(|_| anyhow::anyhow!("invalid offset"))?,
+                                data: Some(AccountsFilterMemcmpOneof::Base58(
+                                    data.trim().to_string(),
+                                )),
+                            }),
+                        })
+                    }
+                    _ => anyhow::bail!("invalid instruction data"),
+                },
+            )
+            .collect()
+    }
+
+    fn parse_data_slice(
+        slices: &[String],
+    ) -> anyhow::Result<Vec<SubscribeRequestAccountsDataSlice>> {
+        slices
+            .iter()
+            .map(|data_slice| match data_slice.split_once(',') {
+                Some((offset, length)) => match (offset.parse(), length.parse()) {
+                    (Ok(offset), Ok(length)) => {
+                        Ok(SubscribeRequestAccountsDataSlice { offset, length })
+                    }
+                    _ => anyhow::bail!("invalid data_slice"),
+                },
+                _ => anyhow::bail!("invalid data_slice"),
+            })
+            .collect()
+    }
+
+    fn parse_cmp(filters: &[String]) -> anyhow::Result<Vec<SubscribeRequestFilterTransactionsCmp>> {
+        filters
+            .iter()
+            .map(|filter| match filter.split_once(':') {
+                Some((cmp, value)) => {
+                    let Ok(value) = value.parse() else {
+                        anyhow::bail!("invalid cmp value: {value}");
+                    };
+                    Ok(SubscribeRequestFilterTransactionsCmp {
+                        cmp: Some(match cmp {
+                            "eq" => TransactionsFilterCmp::Eq(value),
+                            "ne" => TransactionsFilterCmp::Ne(value),
+                            "lt" => TransactionsFilterCmp::Lt(value),
+                            "gt" => TransactionsFilterCmp::Gt(value),
+                            _ => anyhow::bail!("invalid cmp filter: {cmp}"),
+                        }),
+                    })
+                }
+                _ => anyhow::bail!("invalid cmp"),
+            })
+            .collect()
+    }
+
+    fn parse_token_balance(
+        filters: &[String],
+    ) -> anyhow::Result<Vec<SubscribeRequestFilterTransactionsTokenBalance>> {
+        filters
+            .iter()
+            .map(|filter| {
+                let mut parts = filter.splitn(3, ',').map(|part| part.trim());
+                Ok(SubscribeRequestFilterTransactionsTokenBalance {
+                    mint: parts.next().unwrap_or_default().to_string(),
+                    owner: parts
+                        .next()
+                        .filter(|owner| !owner.is_empty())
+                        .map(|owner| owner.to_string()),
+                    min_delta: parts
+                        .next()
+                        .map(|min_delta| {
+                            min_delta
+                                .parse()
+                                .map_err(|_| anyhow::anyhow!("invalid min_delta"))
+                        })
+                        .transpose()?,
+                })
+            })
+            .collect()
+    }
 }
 
 #[tokio::main]
@@ -667,6 +992,7 @@ async fn geyser_subscribe(
 
     info!("stream opened");
     let mut counter = 0;
+    let mut accounts_diff = AccountDiffState::new();
     while let Some(message) = stream.next().await {
         match message {
             Ok(msg) => {
@@ -674,7 +1000,9 @@ async fn geyser_subscribe(
                     let encoded_len = msg.encoded_len() as u64;
                     let (pb_c, pb) = match msg.update_oneof {
                         Some(UpdateOneof::Account(_)) => (&mut pb_accounts_c, &pb_accounts),
+                        Some(UpdateOneof::AccountDiff(_)) => (&mut pb_accounts_c, &pb_accounts),
                         Some(UpdateOneof::Slot(_)) => (&mut pb_slots_c, &pb_slots),
+                        Some(UpdateOneof::SlotRollback(_)) => (&mut pb_slots_c, &pb_slots),
                         Some(UpdateOneof::Transaction(_)) => (&mut pb_txs_c, &pb_txs),
                         Some(UpdateOneof::TransactionStatus(_)) => (&mut pb_txs_st_c, &pb_txs_st),
                         Some(UpdateOneof::Entry(_)) => (&mut pb_entries_c, &pb_entries),
@@ -682,6 +1010,11 @@ async fn geyser_subscribe(
                         Some(UpdateOneof::Block(_)) => (&mut pb_blocks_c, &pb_blocks),
                         Some(UpdateOneof::Ping(_)) => (&mut pb_pp_c, &pb_pp),
                         Some(UpdateOneof::Pong(_)) => (&mut pb_pp_c, &pb_pp),
+                        Some(UpdateOneof::RateLimited(_)) => (&mut pb_pp_c, &pb_pp),
+                        Some(UpdateOneof::Lagged(_)) => (&mut pb_pp_c, &pb_pp),
+                        Some(UpdateOneof::TransactionCommitment(_)) => {
+                            (&mut pb_txs_st_c, &pb_txs_st)
+                        }
                         None => {
                             pb_multi.println("update not found in the message")?;
                             break;
@@ -743,6 +1076,7 @@ async fn geyser_subscribe(
                 let filters = msg.filters;
                 match msg.update_oneof {
                     Some(UpdateOneof::Account(msg)) => {
+                        accounts_diff.update(&msg);
                         let account = msg
                             .account
                             .ok_or(anyhow::anyhow!("no account in the message"))?;
@@ -751,6 +1085,19 @@ async fn geyser_subscribe(
                         value["slot"] = json!(msg.slot);
                         print_update("account", &filters, value);
                     }
+                    Some(UpdateOneof::AccountDiff(msg)) => {
+                        let msg = accounts_diff
+                            .apply(msg)
+                            .map_err(|error| anyhow::anyhow!(error))
+                            .context("failed to apply account diff")?;
+                        let account = msg
+                            .account
+                            .ok_or(anyhow::anyhow!("no account in the message"))?;
+                        let mut value = create_pretty_account(account)?;
+                        value["isStartup"] = json!(msg.is_startup);
+                        value["slot"] = json!(msg.slot);
+                        print_update("accountDiff", &filters, value);
+                    }
                     Some(UpdateOneof::Slot(msg)) => {
                         let status = CommitmentLevel::try_from(msg.status)
                             .context("failed to decode commitment")?;
@@ -848,6 +1195,53 @@ async fn geyser_subscribe(
                             .await?;
                     }
                     Some(UpdateOneof::Pong(_)) => {}
+                    Some(UpdateOneof::RateLimited(msg)) => {
+                        print_update(
+                            "rate_limited",
+                            &filters,
+                            json!({
+                                "droppedMessages": msg.dropped_messages,
+                                "droppedBytes": msg.dropped_bytes,
+                            }),
+                        );
+                    }
+                    Some(UpdateOneof::Lagged(msg)) => {
+                        print_update(
+                            "lagged",
+                            &filters,
+                            json!({
+                                "skippedMessages": msg.skipped_messages,
+                                "skippedBatches": msg.skipped_batches,
+                                "fromSlot": msg.from_slot,
+                                "toSlot": msg.to_slot,
+                            }),
+                        );
+                    }
+                    Some(UpdateOneof::SlotRollback(msg)) => {
+                        let reason = SlotRollbackReason::try_from(msg.reason)
+                            .context("failed to decode rollback reason")?;
+                        print_update(
+                            "slotRollback",
+                            &filters,
+                            json!({
+                                "slot": msg.slot,
+                                "reason": reason.as_str_name(),
+                            }),
+                        );
+                    }
+                    Some(UpdateOneof::TransactionCommitment(msg)) => {
+                        let status = CommitmentLevel::try_from(msg.status)
+                            .context("failed to decode commitment")?;
+                        print_update(
+                            "transactionCommitment",
+                            &filters,
+                            json!({
+                                "signature": Signature::try_from(msg.signature.as_slice()).context("invalid signature")?.to_string(),
+                                "slot": msg.slot,
+                                "status": status.as_str_name(),
+                            }),
+                        );
+                    }
                     None => {
                         error!("update not found in the message");
                         break;
@@ -878,6 +1272,11 @@ async fn geyser_subscribe(
                     commitment: None,
                     accounts_data_slice: Vec::default(),
                     ping: None,
+                    from_slot: None,
+                    accounts_diff: None,
+                    filter_delta: None,
+                    credits: None,
+                    transactions_commitment_updates: None,
                 })
                 .await
                 .map_err(GeyserGrpcClientError::SubscribeSendError)?;
@@ -936,6 +1335,11 @@ fn create_pretty_account(account: SubscribeUpdateAccountInfo) -> anyhow::Result<
         "data": hex::encode(account.data),
         "writeVersion": account.write_version,
         "txnSignature": account.txn_signature.map(|sig| bs58::encode(sig).into_string()),
+        "parsed": account.parsed.map(|parsed| json!({
+            "program": parsed.program,
+            "parsed": serde_json::from_str::<Value>(&parsed.json).ok(),
+            "space": parsed.space,
+        })),
     }))
 }
 
@@ -45,7 +45,7 @@ async fn main() -> anyhow::Result<()> {
         async move {
             subscribe_tx
             .send(SubscribeRequest {
-                slots: maplit::hashmap! { "".to_owned() => SubscribeRequestFilterSlots { filter_by_commitment: Some(true) } },
+                slots: maplit::hashmap! { "".to_owned() => SubscribeRequestFilterSlots { filter_by_commitment: Some(true), commitment: None } },
                 commitment: Some(CommitmentLevel::Processed as i32),
                 ..Default::default()
             })
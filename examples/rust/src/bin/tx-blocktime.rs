@@ -104,13 +104,34 @@ async fn main() -> anyhow::Result<()> {
                 account_include: args.account_include,
                 account_exclude: args.account_exclude,
                 account_required: args.account_required,
+                signer_include: vec![],
+                fee_payer: vec![],
+                program_include: vec![],
+                program_exclude: vec![],
+                inner_instructions: None,
+                instruction_data: vec![],
+                log_contains: vec![],
+                log_regex: vec![],
+                token_balance: vec![],
+                compute_units_consumed: vec![],
+                fee: vec![],
+                include_logs: None,
+                include_inner_instructions: None,
+                include_token_balances: None,
+                include_rewards: None,
+                commitment: None,
             } },
             entry: HashMap::new(),
             blocks: HashMap::new(),
-            blocks_meta: hashmap! { "".to_owned() => SubscribeRequestFilterBlocksMeta {} },
+            blocks_meta: hashmap! { "".to_owned() => SubscribeRequestFilterBlocksMeta { commitment: None } },
             commitment: Some(commitment as i32),
             accounts_data_slice: vec![],
             ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
         })
         .await?;
 
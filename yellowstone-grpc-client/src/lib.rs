@@ -4,7 +4,7 @@ use {
     futures::{
         channel::mpsc,
         sink::{Sink, SinkExt},
-        stream::Stream,
+        stream::{Stream, StreamExt},
     },
     std::time::Duration,
     tonic::{
@@ -16,11 +16,11 @@ use {
     },
     tonic_health::pb::{health_client::HealthClient, HealthCheckRequest, HealthCheckResponse},
     yellowstone_grpc_proto::prelude::{
-        geyser_client::GeyserClient, CommitmentLevel, GetBlockHeightRequest,
-        GetBlockHeightResponse, GetLatestBlockhashRequest, GetLatestBlockhashResponse,
-        GetSlotRequest, GetSlotResponse, GetVersionRequest, GetVersionResponse,
-        IsBlockhashValidRequest, IsBlockhashValidResponse, PingRequest, PongResponse,
-        SubscribeRequest, SubscribeUpdate,
+        geyser_client::GeyserClient, subscribe_update::UpdateOneof, CommitmentLevel,
+        GetBlockHeightRequest, GetBlockHeightResponse, GetLatestBlockhashRequest,
+        GetLatestBlockhashResponse, GetSlotRequest, GetSlotResponse, GetVersionRequest,
+        GetVersionResponse, IsBlockhashValidRequest, IsBlockhashValidResponse, PingRequest,
+        PongResponse, SubscribeRequest, SubscribeRequestCredits, SubscribeUpdate,
     },
 };
 
@@ -127,6 +127,55 @@ impl<F: Interceptor> GeyserGrpcClient<F> {
         Ok((subscribe_tx, response.into_inner()))
     }
 
+    /// Subscribe in credit mode, server sends only granted number of updates and
+    /// buffers the rest. Initial `credits` are granted back once half of them
+    /// consumed from the stream, `ping`, `pong` and `lagged` updates are not counted.
+    pub async fn subscribe_with_credits(
+        &mut self,
+        request: Option<SubscribeRequest>,
+        credits: u64,
+    ) -> GeyserGrpcClientResult<(
+        impl Sink<SubscribeRequest, Error = mpsc::SendError>,
+        impl Stream<Item = Result<SubscribeUpdate, Status>>,
+    )> {
+        let (mut subscribe_tx, subscribe_rx) = mpsc::unbounded();
+        if let Some(request) = request {
+            subscribe_tx
+                .send(request)
+                .await
+                .map_err(GeyserGrpcClientError::SubscribeSendError)?;
+        }
+        let mut request = Request::new(subscribe_rx);
+        request
+            .metadata_mut()
+            .insert("x-credits", MetadataValue::from(credits));
+        let response: Response<Streaming<SubscribeUpdate>> = self.geyser.subscribe(request).await?;
+
+        let credits_tx = subscribe_tx.clone();
+        let threshold = (credits / 2).max(1);
+        let mut consumed = 0;
+        let stream = response.into_inner().inspect(move |message| {
+            if let Ok(SubscribeUpdate {
+                update_oneof:
+                    Some(UpdateOneof::Ping(_) | UpdateOneof::Pong(_) | UpdateOneof::Lagged(_)),
+                ..
+            }) = message
+            {
+                return;
+            }
+
+            consumed += 1;
+            if consumed >= threshold {
+                let _ = credits_tx.unbounded_send(SubscribeRequest {
+                    credits: Some(SubscribeRequestCredits { grant: consumed }),
+                    ..Default::default()
+                });
+                consumed = 0;
+            }
+        });
+        Ok((subscribe_tx, stream))
+    }
+
     pub async fn subscribe_once(
         &mut self,
         request: SubscribeRequest,
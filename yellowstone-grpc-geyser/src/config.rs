@@ -3,7 +3,14 @@ use {
         GeyserPluginError, Result as PluginResult,
     },
     serde::{de, Deserialize, Deserializer},
-    std::{fs::read_to_string, net::SocketAddr, path::Path, time::Duration},
+    std::{
+        collections::{HashMap, HashSet},
+        fs::read_to_string,
+        net::SocketAddr,
+        path::{Path, PathBuf},
+        str::FromStr,
+        time::Duration,
+    },
     tokio::sync::Semaphore,
     tonic::codec::CompressionEncoding,
     yellowstone_grpc_proto::plugin::filter::limits::FilterLimits,
@@ -32,7 +39,11 @@ impl Config {
 
     pub fn load_from_file<P: AsRef<Path>>(file: P) -> PluginResult<Self> {
         let config = read_to_string(file).map_err(GeyserPluginError::ConfigFileOpenError)?;
-        Self::load_from_str(&config)
+        let mut config = Self::load_from_str(&config)?;
+        if let Some(path) = &config.grpc.x_tokens_path {
+            config.grpc.x_tokens = ConfigGrpcToken::load_from_file(path)?;
+        }
+        Ok(config)
     }
 }
 
@@ -93,6 +104,17 @@ pub struct ConfigGrpc {
         deserialize_with = "deserialize_usize_str"
     )]
     pub channel_capacity: usize,
+    /// Action once client can not keep up with updates, can be overridden
+    /// per connection with `x-slow-consumer-policy` header
+    #[serde(default)]
+    pub slow_consumer_policy: ConfigGrpcSlowConsumerPolicy,
+    /// Max number of updates buffered per connection in addition to the channel,
+    /// used by `drop_oldest` policy and credit mode
+    #[serde(
+        default = "ConfigGrpc::slow_consumer_buffer_capacity_default",
+        deserialize_with = "deserialize_usize_str"
+    )]
+    pub slow_consumer_buffer_capacity: usize,
     /// Concurrency limit for unary requests
     #[serde(
         default = "ConfigGrpc::unary_concurrency_limit_default",
@@ -102,11 +124,35 @@ pub struct ConfigGrpc {
     /// Enable/disable unary methods
     #[serde(default)]
     pub unary_disabled: bool,
+    /// Max number of concurrent subscriptions, unlimited if not set
+    #[serde(default, deserialize_with = "deserialize_usize_str_maybe")]
+    pub connections_max: Option<usize>,
+    /// Max number of concurrent subscriptions with the same `x-endpoint` header,
+    /// subscriptions without header are not limited
+    #[serde(default, deserialize_with = "deserialize_usize_str_maybe")]
+    pub connections_max_per_endpoint: Option<usize>,
+    /// Max number of concurrent subscriptions per token from `x_tokens_path`,
+    /// overridden by `connections_max` of the token
+    #[serde(default, deserialize_with = "deserialize_usize_str_maybe")]
+    pub connections_max_per_token: Option<usize>,
     /// Limits for possible filters
     #[serde(default, alias = "filters")]
     pub filter_limits: FilterLimits,
     /// x_token to enforce on connections
     pub x_token: Option<String>,
+    /// Path to the file with tokens, every token has own filter limits,
+    /// connections limit and allowed methods
+    #[serde(default)]
+    pub x_tokens_path: Option<PathBuf>,
+    /// Tokens loaded from `x_tokens_path`
+    #[serde(skip)]
+    pub x_tokens: HashMap<String, ConfigGrpcToken>,
+    /// Validate `x-token` as signed JWT if it does not match static tokens
+    #[serde(default)]
+    pub jwt: Option<ConfigGrpcJwt>,
+    /// Rate limits for updates sent to subscriptions
+    #[serde(default)]
+    pub rate_limit: Option<ConfigGrpcRateLimit>,
     /// Filter name size limit
     #[serde(default = "ConfigGrpc::default_filter_name_size_limit")]
     pub filter_name_size_limit: usize,
@@ -119,6 +165,21 @@ pub struct ConfigGrpc {
         with = "humantime_serde"
     )]
     pub filter_names_cleanup_interval: Duration,
+    /// Number of slots of broadcasted messages stored for replay with `from_slot`,
+    /// replay is disabled if zero
+    #[serde(default, deserialize_with = "deserialize_u64_str")]
+    pub replay_stored_slots: u64,
+    /// Approximate memory limit in bytes for messages stored for replay,
+    /// only raw size of messages is counted without encoded ones
+    #[serde(
+        default = "ConfigGrpc::default_replay_stored_size_limit",
+        deserialize_with = "deserialize_usize_str"
+    )]
+    pub replay_stored_size_limit: usize,
+    /// Disk log of broadcasted messages for replay with `from_slot`
+    /// beyond messages stored in memory
+    #[serde(default)]
+    pub replay_log: Option<ConfigGrpcReplayLog>,
 }
 
 impl ConfigGrpc {
@@ -138,6 +199,10 @@ impl ConfigGrpc {
         250_000
     }
 
+    const fn slow_consumer_buffer_capacity_default() -> usize {
+        10_000
+    }
+
     const fn unary_concurrency_limit_default() -> usize {
         Semaphore::MAX_PERMITS
     }
@@ -153,6 +218,224 @@ impl ConfigGrpc {
     const fn default_filter_names_cleanup_interval() -> Duration {
         Duration::from_secs(1)
     }
+
+    const fn default_replay_stored_size_limit() -> usize {
+        1024 * 1024 * 1024
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcToken {
+    /// Name of the token owner, used in logs
+    pub name: String,
+    /// Limits for filters, `filter_limits` of the grpc config used if not set
+    #[serde(default)]
+    pub filter_limits: Option<FilterLimits>,
+    /// Max number of concurrent subscriptions, unlimited if not set
+    #[serde(default, deserialize_with = "deserialize_usize_str_maybe")]
+    pub connections_max: Option<usize>,
+    /// Allowed methods, all methods are allowed if not set
+    #[serde(default)]
+    pub methods: Option<HashSet<ConfigGrpcMethod>>,
+    /// Rate limit shared by all connections of the token, `rate_limit.token`
+    /// of the grpc config used if not set
+    #[serde(default)]
+    pub rate_limit: Option<ConfigGrpcRateLimitBucket>,
+}
+
+impl ConfigGrpcToken {
+    /// Loads map of token to token config
+    pub fn load_from_file<P: AsRef<Path>>(file: P) -> PluginResult<HashMap<String, Self>> {
+        let config = read_to_string(file).map_err(GeyserPluginError::ConfigFileOpenError)?;
+        serde_json::from_str(&config).map_err(|error| GeyserPluginError::ConfigFileReadError {
+            msg: format!("failed to parse x_tokens: {error}"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcJwt {
+    /// Keys for signature verification
+    pub keys: Vec<ConfigGrpcJwtKey>,
+    /// Allowed clock skew for `exp` and `nbf` claims
+    #[serde(default = "ConfigGrpcJwt::default_leeway", with = "humantime_serde")]
+    pub leeway: Duration,
+    /// Filter limits selected with `profile` claim, `filter_limits` of the grpc config
+    /// used if claim is not set
+    #[serde(default)]
+    pub profiles: HashMap<String, FilterLimits>,
+}
+
+impl ConfigGrpcJwt {
+    const fn default_leeway() -> Duration {
+        Duration::from_secs(60)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "alg")]
+pub enum ConfigGrpcJwtKey {
+    /// HMAC with SHA-256
+    #[serde(rename = "HS256")]
+    Hs256 {
+        #[serde(default)]
+        kid: Option<String>,
+        secret: String,
+    },
+    /// Ed25519 signature, public key in base64
+    #[serde(rename = "EdDSA")]
+    EdDsa {
+        #[serde(default)]
+        kid: Option<String>,
+        public_key: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigGrpcMethod {
+    Subscribe,
+    Ping,
+    GetLatestBlockhash,
+    GetBlockHeight,
+    GetSlot,
+    IsBlockhashValid,
+    GetVersion,
+}
+
+impl ConfigGrpcMethod {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Subscribe => "subscribe",
+            Self::Ping => "ping",
+            Self::GetLatestBlockhash => "get_latest_blockhash",
+            Self::GetBlockHeight => "get_block_height",
+            Self::GetSlot => "get_slot",
+            Self::IsBlockhashValid => "is_blockhash_valid",
+            Self::GetVersion => "get_version",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigGrpcSlowConsumerPolicy {
+    /// Close the stream with `lagged` error
+    #[default]
+    Disconnect,
+    /// Keep up to `slow_consumer_buffer_capacity` updates in additional buffer and drop the oldest,
+    /// `SubscribeUpdateLagged` is sent before the next delivered update
+    DropOldest,
+    /// Send only slot updates until client queue is drained, then `SubscribeUpdateLagged`
+    SlotsOnly,
+}
+
+impl FromStr for ConfigGrpcSlowConsumerPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disconnect" => Ok(Self::Disconnect),
+            "drop_oldest" => Ok(Self::DropOldest),
+            "slots_only" => Ok(Self::SlotsOnly),
+            value => Err(format!("unknown slow consumer policy: {value}")),
+        }
+    }
+}
+
+impl ConfigGrpcSlowConsumerPolicy {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Disconnect => "disconnect",
+            Self::DropOldest => "drop_oldest",
+            Self::SlotsOnly => "slots_only",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcRateLimit {
+    /// Limit for every connection
+    #[serde(default)]
+    pub connection: Option<ConfigGrpcRateLimitBucket>,
+    /// Limit shared by all connections of the same token from `x_tokens_path`
+    #[serde(default)]
+    pub token: Option<ConfigGrpcRateLimitBucket>,
+    /// Action once any limit is exceeded
+    #[serde(default)]
+    pub policy: ConfigGrpcRateLimitPolicy,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcRateLimitBucket {
+    /// Number of updates per second, also used as burst size
+    #[serde(default, deserialize_with = "deserialize_usize_str_maybe")]
+    pub messages_per_second: Option<usize>,
+    /// Number of encoded bytes per second, also used as burst size
+    #[serde(default, deserialize_with = "deserialize_usize_str_maybe")]
+    pub bytes_per_second: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigGrpcRateLimitPolicy {
+    /// Drop updates, `SubscribeUpdateRateLimited` is sent before the next delivered update
+    #[default]
+    Drop,
+    /// Close the stream with `RESOURCE_EXHAUSTED`
+    Disconnect,
+}
+
+impl ConfigGrpcRateLimitPolicy {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Drop => "drop",
+            Self::Disconnect => "disconnect",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcReplayLog {
+    /// Directory for segment files
+    pub path: PathBuf,
+    /// Segment is sealed and new one is created once size limit is reached, default is 128MiB
+    #[serde(
+        default = "ConfigGrpcReplayLog::default_segment_size_limit",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub segment_size_limit: u64,
+    /// Number of slots kept on disk, outdated segments are removed
+    #[serde(
+        default = "ConfigGrpcReplayLog::default_stored_slots",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub stored_slots: u64,
+    /// Total size limit of segments, default is 64GiB
+    #[serde(
+        default = "ConfigGrpcReplayLog::default_stored_size_limit",
+        deserialize_with = "deserialize_u64_str"
+    )]
+    pub stored_size_limit: u64,
+}
+
+impl ConfigGrpcReplayLog {
+    const fn default_segment_size_limit() -> u64 {
+        128 * 1024 * 1024
+    }
+
+    const fn default_stored_slots() -> u64 {
+        9_000
+    }
+
+    const fn default_stored_size_limit() -> u64 {
+        64 * 1024 * 1024 * 1024
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -237,6 +520,13 @@ where
     }
 }
 
+fn deserialize_u64_str<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_usize_str(deserializer).map(|value| value as u64)
+}
+
 fn deserialize_usize_str_maybe<'de, D>(deserializer: D) -> Result<Option<usize>, D::Error>
 where
     D: Deserializer<'de>,
@@ -251,3 +541,37 @@ where
         None => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{ConfigGrpcMethod, ConfigGrpcToken},
+        std::path::Path,
+    };
+
+    #[test]
+    fn test_x_tokens_example() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config-x-tokens.json");
+        let tokens = ConfigGrpcToken::load_from_file(path).unwrap();
+        assert_eq!(tokens.len(), 2);
+
+        let token = &tokens["team-a-secret-token"];
+        assert_eq!(token.name, "team-a");
+        assert_eq!(token.connections_max, Some(4));
+        let methods = token.methods.as_ref().unwrap();
+        assert!(methods.contains(&ConfigGrpcMethod::Subscribe));
+        assert!(!methods.contains(&ConfigGrpcMethod::GetLatestBlockhash));
+        let limits = token.filter_limits.as_ref().unwrap();
+        assert_eq!(limits.accounts.account_max, 10_000);
+        assert_eq!(limits.slots.max, usize::MAX);
+        let rate_limit = token.rate_limit.unwrap();
+        assert_eq!(rate_limit.messages_per_second, Some(10_000));
+        assert_eq!(rate_limit.bytes_per_second, Some(10 * 1024 * 1024));
+
+        let token = &tokens["team-b-secret-token"];
+        assert!(token.filter_limits.is_none());
+        assert!(token.connections_max.is_none());
+        assert!(token.methods.is_none());
+        assert!(token.rate_limit.is_none());
+    }
+}
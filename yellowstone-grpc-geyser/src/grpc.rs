@@ -1,7 +1,13 @@
 use {
     crate::{
-        config::ConfigGrpc,
+        config::{
+            ConfigGrpc, ConfigGrpcMethod, ConfigGrpcRateLimit, ConfigGrpcRateLimitBucket,
+            ConfigGrpcRateLimitPolicy, ConfigGrpcSlowConsumerPolicy, ConfigGrpcToken,
+        },
+        jwt::{JwtResult, JwtValidator},
+        message_log::{MessageLog, MessageLogPosition, MessageLogReader},
         metrics::{self, DebugClientMessage},
+        rate_limit::RateLimiter,
         version::GrpcVersionInfo,
     },
     anyhow::Context,
@@ -9,18 +15,20 @@ use {
     solana_sdk::{
         clock::{Slot, MAX_RECENT_BLOCKHASHES},
         pubkey::Pubkey,
+        signature::Signature,
     },
     std::{
-        collections::{BTreeMap, HashMap},
+        collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+        io, mem,
         sync::{
             atomic::{AtomicUsize, Ordering},
-            Arc,
+            Arc, Mutex as StdMutex,
         },
     },
     tokio::{
         fs,
         runtime::Builder,
-        sync::{broadcast, mpsc, Mutex, Notify, RwLock, Semaphore},
+        sync::{broadcast, mpsc, oneshot, Mutex, Notify, RwLock, Semaphore},
         task::spawn_blocking,
         time::{sleep, Duration, Instant},
     },
@@ -38,13 +46,16 @@ use {
         plugin::{
             filter::{
                 limits::FilterLimits,
-                message::{FilteredUpdate, FilteredUpdateOneof},
+                message::{
+                    FilteredUpdate, FilteredUpdateFilters, FilteredUpdateOneof, FilteredUpdates,
+                },
                 name::FilterNames,
-                Filter,
+                Filter, FilterMintDecimals,
             },
             message::{
-                CommitmentLevel, Message, MessageBlock, MessageBlockMeta, MessageEntry,
-                MessageSlot, MessageTransactionInfo,
+                CommitmentLevel, Message, MessageAccount, MessageAccountInfo, MessageBlock,
+                MessageBlockMeta, MessageEntry, MessageSlot, MessageSlotRollback,
+                MessageTransaction, MessageTransactionInfo,
             },
             proto::geyser_server::{Geyser, GeyserServer},
         },
@@ -52,11 +63,858 @@ use {
             CommitmentLevel as CommitmentLevelProto, GetBlockHeightRequest, GetBlockHeightResponse,
             GetLatestBlockhashRequest, GetLatestBlockhashResponse, GetSlotRequest, GetSlotResponse,
             GetVersionRequest, GetVersionResponse, IsBlockhashValidRequest,
-            IsBlockhashValidResponse, PingRequest, PongResponse, SubscribeRequest,
+            IsBlockhashValidResponse, PingRequest, PongResponse, SlotRollbackReason,
+            SubscribeRequest, SubscribeRequestFilterDelta,
         },
+        prost::Message as _,
     },
 };
 
+type BroadcastedMessage = (CommitmentLevel, Arc<Vec<Message>>);
+
+const MESSAGE_LOG_CHANNEL_CAPACITY: usize = 1_024;
+
+// Replay log is continued after position if it's set
+type ReplayStoredSlotsRequest = (
+    Vec<CommitmentLevel>,
+    Slot,
+    Option<MessageLogPosition>,
+    oneshot::Sender<ReplayStoredSlotsResponse>,
+);
+
+type ReplayStoredSlotsResponse = Result<
+    (
+        ReplayStoredMessages,
+        broadcast::Receiver<BroadcastedMessage>,
+    ),
+    ReplayStoredSlotsError,
+>;
+
+#[derive(Debug)]
+enum ReplayStoredSlotsError {
+    // first available slot (if any)
+    NotAvailable(Option<Slot>),
+    // queue of replay log writer is full
+    Busy,
+}
+
+// Requests to replay log thread, handled in order so replay gets all messages broadcasted before
+#[derive(Debug)]
+enum MessageLogRequest {
+    Append {
+        messages: Arc<Vec<Message>>,
+        // previous messages were not queued
+        gap: bool,
+    },
+    Replay {
+        from_slot: Slot,
+        position: Option<MessageLogPosition>,
+        first_available_memory: Option<Slot>,
+        messages_rx: broadcast::Receiver<BroadcastedMessage>,
+        tx: oneshot::Sender<ReplayStoredSlotsResponse>,
+    },
+}
+
+#[derive(Debug)]
+enum ReplayStoredMessages {
+    Memory(Vec<BroadcastedMessage>),
+    Log(MessageLogReader),
+}
+
+// Token from `x_tokens` or JWT, added to request extensions by interceptor
+#[derive(Debug)]
+struct ClientToken {
+    name: String,
+    filter_limits: Arc<FilterLimits>,
+    connections_max: Option<usize>,
+    connections: Arc<AtomicUsize>,
+    methods: Option<HashSet<ConfigGrpcMethod>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    // unix timestamp, only for JWT
+    expires_at: Option<u64>,
+}
+
+// JWT is validated on every request, connections counter and token limiter
+// are shared by tokens with the same `sub` (or `jti`) while any of them is used
+type ClientJwtSubjects = StdMutex<HashMap<String, (Arc<AtomicUsize>, Option<Arc<RateLimiter>>)>>;
+
+impl ClientToken {
+    fn new(
+        config: ConfigGrpcToken,
+        filter_limits: &Arc<FilterLimits>,
+        connections_max: Option<usize>,
+        rate_limit: Option<ConfigGrpcRateLimitBucket>,
+    ) -> Self {
+        Self {
+            name: config.name,
+            filter_limits: config
+                .filter_limits
+                .map(Arc::new)
+                .unwrap_or_else(|| Arc::clone(filter_limits)),
+            connections_max: config.connections_max.or(connections_max),
+            connections: Arc::new(AtomicUsize::new(0)),
+            methods: config.methods,
+            rate_limiter: config
+                .rate_limit
+                .or(rate_limit)
+                .and_then(RateLimiter::new)
+                .map(Arc::new),
+            expires_at: None,
+        }
+    }
+
+    fn from_jwt(
+        jwt: &JwtValidator,
+        subjects: &ClientJwtSubjects,
+        token: &str,
+        filter_limits: &FilterLimits,
+        connections_max: Option<usize>,
+        rate_limit: Option<ConfigGrpcRateLimitBucket>,
+    ) -> JwtResult<Self> {
+        let claims = jwt.validate(token, JwtValidator::now())?;
+        let filter_limits = Arc::new(jwt.get_filter_limits(&claims, filter_limits)?);
+        let expires_at = jwt.expires_at(&claims);
+
+        let subject = claims
+            .sub
+            .as_deref()
+            .or(claims.jti.as_deref())
+            .unwrap_or(token);
+        let mut subjects = subjects.lock().unwrap();
+        if !subjects.contains_key(subject) {
+            subjects.retain(|_subject, (connections, _)| Arc::strong_count(connections) > 1);
+        }
+        let (connections, rate_limiter) = subjects
+            .entry(subject.to_owned())
+            .or_insert_with(|| {
+                (
+                    Arc::new(AtomicUsize::new(0)),
+                    rate_limit.and_then(RateLimiter::new).map(Arc::new),
+                )
+            })
+            .clone();
+
+        Ok(Self {
+            filter_limits,
+            name: claims.sub.unwrap_or_else(|| "jwt".to_owned()),
+            connections_max,
+            connections,
+            methods: None,
+            rate_limiter,
+            expires_at: Some(expires_at),
+        })
+    }
+
+    fn check_method<T>(request: &Request<T>, method: ConfigGrpcMethod) -> TonicResult<()> {
+        match request.extensions().get::<Arc<Self>>() {
+            Some(token)
+                if !token
+                    .methods
+                    .as_ref()
+                    .map_or(true, |methods| methods.contains(&method)) =>
+            {
+                Err(Status::permission_denied(format!(
+                    "method `{}` is not allowed",
+                    method.as_str()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn connect(self: &Arc<Self>) -> TonicResult<ClientTokenConnection> {
+        let connections = self.connections.fetch_add(1, Ordering::Relaxed) + 1;
+        let connection = ClientTokenConnection(Arc::clone(self));
+        match self.connections_max {
+            Some(max) if connections > max => {
+                metrics::connections_rejected_inc("token");
+                Err(Status::resource_exhausted(format!(
+                    "max connections reached for `{}`, only {max} allowed",
+                    self.name
+                )))
+            }
+            _ => Ok(connection),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClientTokenConnection(Arc<ClientToken>);
+
+impl Drop for ClientTokenConnection {
+    fn drop(&mut self) {
+        self.0.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+struct ClientConnections {
+    max: Option<usize>,
+    max_per_endpoint: Option<usize>,
+    total: AtomicUsize,
+    endpoints: StdMutex<HashMap<String, usize>>,
+}
+
+impl ClientConnections {
+    fn new(max: Option<usize>, max_per_endpoint: Option<usize>) -> Self {
+        Self {
+            max,
+            max_per_endpoint,
+            total: AtomicUsize::new(0),
+            endpoints: StdMutex::default(),
+        }
+    }
+
+    fn connect(self: &Arc<Self>, endpoint: Option<&str>) -> TonicResult<ClientConnection> {
+        let total = self.total.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut connection = ClientConnection {
+            connections: Arc::clone(self),
+            endpoint: None,
+        };
+        if let Some(max) = self.max {
+            if total > max {
+                metrics::connections_rejected_inc("total");
+                return Err(Status::resource_exhausted(format!(
+                    "max connections reached, only {max} allowed"
+                )));
+            }
+        }
+
+        if let (Some(max), Some(endpoint)) = (self.max_per_endpoint, endpoint) {
+            let mut endpoints = self.endpoints.lock().unwrap();
+            let connections = endpoints.entry(endpoint.to_owned()).or_default();
+            *connections += 1;
+            connection.endpoint = Some(endpoint.to_owned());
+            if *connections > max {
+                metrics::connections_rejected_inc("endpoint");
+                return Err(Status::resource_exhausted(format!(
+                    "max connections reached for endpoint `{endpoint}`, only {max} allowed"
+                )));
+            }
+        }
+
+        Ok(connection)
+    }
+}
+
+#[derive(Debug)]
+struct ClientConnection {
+    connections: Arc<ClientConnections>,
+    endpoint: Option<String>,
+}
+
+impl Drop for ClientConnection {
+    fn drop(&mut self) {
+        self.connections.total.fetch_sub(1, Ordering::Relaxed);
+        if let Some(endpoint) = self.endpoint.take() {
+            let mut endpoints = self.connections.endpoints.lock().unwrap();
+            if let Some(connections) = endpoints.get_mut(&endpoint) {
+                *connections -= 1;
+                if *connections == 0 {
+                    endpoints.remove(&endpoint);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClientRateLimits {
+    limiters: Vec<(&'static str, Arc<RateLimiter>)>,
+    policy: ConfigGrpcRateLimitPolicy,
+    dropped_messages: u64,
+    dropped_bytes: u64,
+}
+
+impl ClientRateLimits {
+    fn new(config: Option<&ConfigGrpcRateLimit>, token: Option<&ClientToken>) -> Option<Self> {
+        let mut limiters = vec![];
+        if let Some(limiter) = config
+            .and_then(|config| config.connection)
+            .and_then(RateLimiter::new)
+        {
+            limiters.push(("connection", Arc::new(limiter)));
+        }
+        if let Some(limiter) = token.and_then(|token| token.rate_limiter.as_ref()) {
+            limiters.push(("token", Arc::clone(limiter)));
+        }
+
+        (!limiters.is_empty()).then(|| Self {
+            limiters,
+            policy: config.map(|config| config.policy).unwrap_or_default(),
+            dropped_messages: 0,
+            dropped_bytes: 0,
+        })
+    }
+
+    // Returns notice about dropped updates if update is allowed or name of exceeded limit
+    fn try_acquire(
+        &mut self,
+        message: &FilteredUpdate,
+    ) -> Result<Option<FilteredUpdate>, &'static str> {
+        let bytes = if self
+            .limiters
+            .iter()
+            .any(|(_, limiter)| limiter.is_bytes_limited())
+        {
+            message.encoded_len()
+        } else {
+            0
+        };
+
+        let limiters = self.limiters.iter().map(|(_, limiter)| limiter.as_ref());
+        if let Err(index) = RateLimiter::try_acquire_all(limiters, bytes) {
+            let limit = self.limiters[index].0;
+            let bytes = if bytes == 0 {
+                message.encoded_len()
+            } else {
+                bytes
+            };
+            metrics::rate_limited_inc(limit, self.policy, bytes);
+            self.dropped_messages += 1;
+            self.dropped_bytes += bytes as u64;
+            return Err(limit);
+        }
+
+        if self.dropped_messages == 0 {
+            return Ok(None);
+        }
+        let notice = FilteredUpdate::new_empty(FilteredUpdateOneof::rate_limited(
+            mem::take(&mut self.dropped_messages),
+            mem::take(&mut self.dropped_bytes),
+        ));
+        Ok(Some(notice))
+    }
+}
+
+#[derive(Debug)]
+enum ClientSendError {
+    Lagged,
+    RateLimited(&'static str),
+    Closed,
+}
+
+#[derive(Debug)]
+struct ClientSlowConsumer {
+    endpoint: String,
+    policy: ConfigGrpcSlowConsumerPolicy,
+    capacity: usize,
+    pending: VecDeque<(Slot, TonicResult<FilteredUpdate>)>,
+    // number of skipped updates and batches of messages, first and last slot of them
+    skipped: Option<(u64, u64, Slot, Slot)>,
+    // last slot of skipped batches is known once the next batch is received
+    lagged: bool,
+    degraded: bool,
+    last_slot: Slot,
+    // number of updates granted by client in credit mode
+    credits: Option<u64>,
+    // accounts of dropped updates, diff cache of the filter is reset for them
+    dropped_accounts: Vec<Pubkey>,
+}
+
+impl ClientSlowConsumer {
+    const fn new(
+        endpoint: String,
+        policy: ConfigGrpcSlowConsumerPolicy,
+        capacity: usize,
+        credits: Option<u64>,
+    ) -> Self {
+        Self {
+            endpoint,
+            policy,
+            capacity,
+            pending: VecDeque::new(),
+            skipped: None,
+            lagged: false,
+            degraded: false,
+            last_slot: 0,
+            credits,
+            dropped_accounts: vec![],
+        }
+    }
+
+    fn grant(&mut self, grant: u64) {
+        if let Some(credits) = self.credits.as_mut() {
+            *credits = credits.saturating_add(grant);
+        }
+    }
+
+    fn consume_credit(&mut self) {
+        if let Some(credits) = self.credits.as_mut() {
+            *credits = credits.saturating_sub(1);
+        }
+    }
+
+    fn skip(&mut self, updates: u64, batches: u64, slot: Slot) {
+        let (skipped_updates, skipped_batches, from_slot, to_slot) =
+            self.skipped.get_or_insert_with(|| {
+                metrics::slow_consumer_inc(&self.endpoint, self.policy);
+                (0, 0, slot, slot)
+            });
+        *skipped_updates += updates;
+        *skipped_batches += batches;
+        *from_slot = (*from_slot).min(slot);
+        *to_slot = (*to_slot).max(slot);
+        metrics::slow_consumer_skipped_inc(&self.endpoint, self.policy, updates + batches);
+    }
+
+    fn dropped(&mut self, message: &FilteredUpdate) {
+        match &message.message {
+            FilteredUpdateOneof::Account(msg) => self.dropped_accounts.push(msg.account.pubkey),
+            FilteredUpdateOneof::AccountDiff(msg) => self.dropped_accounts.push(msg.account.pubkey),
+            _ => {}
+        }
+    }
+
+    // broadcast receiver got batch of messages
+    fn received(&mut self, slot: Slot) {
+        if mem::take(&mut self.lagged) {
+            if let Some((_, _, _, to_slot)) = self.skipped.as_mut() {
+                *to_slot = (*to_slot).max(slot);
+            }
+        }
+    }
+
+    fn take_notice(&mut self) -> Option<FilteredUpdate> {
+        if self.lagged {
+            return None;
+        }
+        self.skipped
+            .take()
+            .map(|(skipped_updates, skipped_batches, from_slot, to_slot)| {
+                FilteredUpdate::new_empty(FilteredUpdateOneof::lagged(
+                    skipped_updates,
+                    skipped_batches,
+                    from_slot,
+                    to_slot,
+                ))
+            })
+    }
+
+    const fn is_buffered(&self) -> bool {
+        self.credits.is_some() || matches!(self.policy, ConfigGrpcSlowConsumerPolicy::DropOldest)
+    }
+
+    fn has_pending(&self) -> bool {
+        (self.is_buffered() && self.skipped.is_some() && !self.lagged)
+            || (!self.pending.is_empty() && self.credits != Some(0))
+    }
+
+    // replayed and snapshot messages are read only when updates are not buffered
+    fn is_ready(&self) -> bool {
+        !self.has_pending() && self.pending.is_empty() && self.credits != Some(0)
+    }
+
+    // notice does not consume credits
+    fn pop_pending(&mut self) -> Option<TonicResult<FilteredUpdate>> {
+        if let Some(notice) = self.take_notice() {
+            return Some(Ok(notice));
+        }
+        if self.credits == Some(0) {
+            return None;
+        }
+        let (_slot, message) = self.pending.pop_front()?;
+        self.consume_credit();
+        Some(message)
+    }
+
+    fn send_buffered(
+        &mut self,
+        stream_tx: &mpsc::Sender<TonicResult<FilteredUpdate>>,
+        slot: Slot,
+        message: FilteredUpdate,
+    ) -> Result<(), ClientSendError> {
+        let message = if self.has_pending() || self.credits == Some(0) {
+            Ok(message)
+        } else {
+            match stream_tx.try_send(Ok(message)) {
+                Ok(()) => {
+                    self.consume_credit();
+                    return Ok(());
+                }
+                Err(mpsc::error::TrySendError::Full(message)) => message,
+                Err(mpsc::error::TrySendError::Closed(_)) => return Err(ClientSendError::Closed),
+            }
+        };
+        self.pending.push_back((slot, message));
+        if self.pending.len() > self.capacity {
+            if self.policy == ConfigGrpcSlowConsumerPolicy::Disconnect {
+                metrics::slow_consumer_inc(&self.endpoint, self.policy);
+                return Err(ClientSendError::Lagged);
+            }
+            if let Some((slot, message)) = self.pending.pop_front() {
+                self.skip(1, 0, slot);
+                if let Ok(message) = &message {
+                    self.dropped(message);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn send(
+        &mut self,
+        stream_tx: &mpsc::Sender<TonicResult<FilteredUpdate>>,
+        slot: Slot,
+        message: FilteredUpdate,
+    ) -> Result<(), ClientSendError> {
+        match self.policy {
+            _ if self.credits.is_some() => self.send_buffered(stream_tx, slot, message),
+            ConfigGrpcSlowConsumerPolicy::Disconnect => match stream_tx.try_send(Ok(message)) {
+                Ok(()) => Ok(()),
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    metrics::slow_consumer_inc(&self.endpoint, self.policy);
+                    Err(ClientSendError::Lagged)
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => Err(ClientSendError::Closed),
+            },
+            ConfigGrpcSlowConsumerPolicy::DropOldest => {
+                self.send_buffered(stream_tx, slot, message)
+            }
+            ConfigGrpcSlowConsumerPolicy::SlotsOnly => {
+                // caught up once queue is drained
+                if self.degraded && stream_tx.capacity() == stream_tx.max_capacity() {
+                    self.degraded = false;
+                    if let Some(notice) = self.take_notice() {
+                        if let Err(mpsc::error::TrySendError::Closed(_)) =
+                            stream_tx.try_send(Ok(notice))
+                        {
+                            return Err(ClientSendError::Closed);
+                        }
+                    }
+                }
+                if self.degraded && !matches!(message.message, FilteredUpdateOneof::Slot(_)) {
+                    self.skip(1, 0, slot);
+                    self.dropped(&message);
+                    return Ok(());
+                }
+                match stream_tx.try_send(Ok(message)) {
+                    Ok(()) => Ok(()),
+                    Err(mpsc::error::TrySendError::Full(message)) => {
+                        self.degraded = true;
+                        self.skip(1, 0, slot);
+                        if let Ok(message) = &message {
+                            self.dropped(message);
+                        }
+                        Ok(())
+                    }
+                    Err(mpsc::error::TrySendError::Closed(_)) => Err(ClientSendError::Closed),
+                }
+            }
+        }
+    }
+
+    // broadcast receiver skipped `count` batches of messages
+    fn lagged(&mut self, count: u64) -> Result<(), ClientSendError> {
+        match self.policy {
+            ConfigGrpcSlowConsumerPolicy::Disconnect => {
+                metrics::slow_consumer_inc(&self.endpoint, self.policy);
+                Err(ClientSendError::Lagged)
+            }
+            ConfigGrpcSlowConsumerPolicy::DropOldest => {
+                self.lagged = true;
+                self.skip(0, count, self.last_slot);
+                Ok(())
+            }
+            ConfigGrpcSlowConsumerPolicy::SlotsOnly => {
+                self.lagged = true;
+                self.degraded = true;
+                self.skip(0, count, self.last_slot);
+                Ok(())
+            }
+        }
+    }
+}
+
+// processed transactions delivered to the client by slot, with flag of sent confirmed status
+#[derive(Debug, Default)]
+struct ClientTransactionsCommitment {
+    slots: BTreeMap<Slot, (bool, HashMap<Signature, FilteredUpdateFilters>)>,
+}
+
+impl ClientTransactionsCommitment {
+    fn clear(&mut self) {
+        self.slots.clear();
+    }
+
+    fn track(&mut self, update: &FilteredUpdate) {
+        if update.commitment != Some(CommitmentLevel::Processed) {
+            return;
+        }
+        let (signature, slot) = match &update.message {
+            FilteredUpdateOneof::Transaction(msg) => (msg.transaction.signature, msg.slot),
+            FilteredUpdateOneof::TransactionStatus(msg) => (msg.transaction.signature, msg.slot),
+            _ => return,
+        };
+        let (_confirmed, transactions) = self.slots.entry(slot).or_default();
+        let filters = transactions.entry(signature).or_default();
+        for name in update.filters.iter() {
+            if !filters.contains(name) {
+                filters.push(name.clone());
+            }
+        }
+    }
+
+    // slots before finalized one are dropped without update, they are not on the main fork
+    // and the client receives `slot_rollback` for them
+    fn get_updates(&mut self, message: &MessageSlot) -> Vec<FilteredUpdate> {
+        let transactions = match message.status {
+            CommitmentLevel::Confirmed => match self.slots.get_mut(&message.slot) {
+                Some((confirmed, transactions)) if !*confirmed => {
+                    *confirmed = true;
+                    transactions.clone()
+                }
+                _ => return vec![],
+            },
+            CommitmentLevel::Finalized => {
+                let slots = self.slots.split_off(&(message.slot + 1));
+                match mem::replace(&mut self.slots, slots).remove(&message.slot) {
+                    Some((_confirmed, transactions)) => transactions,
+                    None => return vec![],
+                }
+            }
+            CommitmentLevel::Dead => match self.slots.remove(&message.slot) {
+                Some((_confirmed, transactions)) => transactions,
+                None => return vec![],
+            },
+            _ => return vec![],
+        };
+
+        transactions
+            .into_iter()
+            .map(|(signature, filters)| {
+                FilteredUpdate::new(
+                    filters,
+                    FilteredUpdateOneof::transaction_commitment(
+                        &signature,
+                        message.slot,
+                        message.status,
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+// slots with processed data delivered to the client, kept until slot is finalized
+#[derive(Debug, Default)]
+struct ClientSlotsRollback {
+    slots: BTreeSet<Slot>,
+}
+
+impl ClientSlotsRollback {
+    fn track(&mut self, slot: Slot, update: &FilteredUpdate) {
+        if update.commitment == Some(CommitmentLevel::Processed)
+            && matches!(
+                update.message,
+                FilteredUpdateOneof::Account(_)
+                    | FilteredUpdateOneof::AccountDiff(_)
+                    | FilteredUpdateOneof::Transaction(_)
+                    | FilteredUpdateOneof::TransactionStatus(_)
+                    | FilteredUpdateOneof::Block(_)
+                    | FilteredUpdateOneof::BlockMeta(_)
+                    | FilteredUpdateOneof::Entry(_)
+            )
+        {
+            self.slots.insert(slot);
+        }
+    }
+
+    // sent only to clients received processed data of the slot, on replay too
+    fn get_update(&mut self, message: &Message) -> Option<FilteredUpdate> {
+        match message {
+            Message::Slot(msg) if msg.status == CommitmentLevel::Finalized => {
+                self.slots = self.slots.split_off(&(msg.slot + 1));
+                None
+            }
+            Message::SlotRollback(msg) if self.slots.remove(&msg.slot) => Some(
+                FilteredUpdate::new_empty(FilteredUpdateOneof::slot_rollback(msg)),
+            ),
+            _ => None,
+        }
+    }
+}
+
+// Snapshot on startup or stored slots, sent before live messages
+#[derive(Debug)]
+struct ClientReplay {
+    source: ClientReplaySource,
+    messages: VecDeque<(Option<CommitmentLevel>, Message)>,
+    // live messages are received with it once replay is finished
+    messages_rx: Option<broadcast::Receiver<BroadcastedMessage>>,
+}
+
+#[derive(Debug)]
+enum ClientReplaySource {
+    Snapshot(mpsc::Receiver<Box<Message>>),
+    Memory,
+    Log {
+        replay_stored_slots_tx: mpsc::Sender<ReplayStoredSlotsRequest>,
+        commitments: Vec<CommitmentLevel>,
+        from_slot: Slot,
+        log_rx: mpsc::Receiver<io::Result<Message>>,
+        log_commitments: ReplayLogCommitments,
+        position: MessageLogPosition,
+        caught_up: bool,
+    },
+}
+
+impl ClientReplay {
+    const SNAPSHOT_CHANNEL_CAPACITY: usize = 1_024;
+    const LOG_CHANNEL_CAPACITY: usize = 1_024;
+    // live messages are received once replay is close to them,
+    // otherwise receiver would lag during long replay
+    const LOG_CATCH_UP_SIZE: u64 = 16 * 1024 * 1024;
+
+    fn snapshot(
+        snapshot_rx: crossbeam_channel::Receiver<Box<Message>>,
+        mint_decimals: FilterMintDecimals,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(Self::SNAPSHOT_CHANNEL_CAPACITY);
+        spawn_blocking(move || {
+            while let Ok(message) = snapshot_rx.recv() {
+                metrics::message_queue_size_dec();
+                if let Message::Account(msg) = message.as_ref() {
+                    mint_decimals.update(&msg.account);
+                }
+                if tx.blocking_send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            source: ClientReplaySource::Snapshot(rx),
+            messages: VecDeque::new(),
+            messages_rx: None,
+        }
+    }
+
+    fn new(
+        replay_stored_slots_tx: &mpsc::Sender<ReplayStoredSlotsRequest>,
+        commitments: Vec<CommitmentLevel>,
+        from_slot: Slot,
+        messages: ReplayStoredMessages,
+        messages_rx: broadcast::Receiver<BroadcastedMessage>,
+    ) -> Self {
+        match messages {
+            ReplayStoredMessages::Memory(messages) => Self {
+                source: ClientReplaySource::Memory,
+                messages: messages
+                    .iter()
+                    .flat_map(|(commitment, messages)| {
+                        messages.iter().map(|message| (Some(*commitment), message))
+                    })
+                    .filter(|(_commitment, message)| message.get_slot() >= from_slot)
+                    .map(|(commitment, message)| (commitment, message.clone()))
+                    .collect(),
+                messages_rx: Some(messages_rx),
+            },
+            ReplayStoredMessages::Log(reader) => {
+                let position = reader.end();
+                let caught_up = reader.size() <= Self::LOG_CATCH_UP_SIZE;
+                Self {
+                    source: ClientReplaySource::Log {
+                        replay_stored_slots_tx: replay_stored_slots_tx.clone(),
+                        commitments,
+                        from_slot,
+                        log_rx: reader.spawn(Self::LOG_CHANNEL_CAPACITY),
+                        log_commitments: ReplayLogCommitments::default(),
+                        position,
+                        caught_up,
+                    },
+                    messages: VecDeque::new(),
+                    messages_rx: caught_up.then_some(messages_rx),
+                }
+            }
+        }
+    }
+
+    // Wait for free slot in the stream first, so message is not lost if future is dropped
+    async fn next_ready(
+        stream_tx: &mpsc::Sender<TonicResult<FilteredUpdate>>,
+        replay: &mut Option<Self>,
+    ) -> Result<Option<(Option<CommitmentLevel>, Message)>, Status> {
+        if stream_tx.reserve().await.is_err() {
+            return Err(Status::internal("stream closed"));
+        }
+        match replay.as_mut() {
+            Some(replay) => replay.next().await,
+            None => Ok(None),
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<(Option<CommitmentLevel>, Message)>, Status> {
+        loop {
+            if let Some(message) = self.messages.pop_front() {
+                return Ok(Some(message));
+            }
+
+            match &mut self.source {
+                ClientReplaySource::Snapshot(snapshot_rx) => {
+                    return Ok(snapshot_rx.recv().await.map(|message| (None, *message)));
+                }
+                ClientReplaySource::Memory => return Ok(None),
+                ClientReplaySource::Log {
+                    replay_stored_slots_tx,
+                    commitments,
+                    from_slot,
+                    log_rx,
+                    log_commitments,
+                    position,
+                    caught_up,
+                } => match log_rx.recv().await {
+                    Some(Ok(message)) => {
+                        for (commitment, messages) in log_commitments.push(message) {
+                            if commitments.contains(&commitment) {
+                                self.messages.extend(
+                                    messages
+                                        .into_iter()
+                                        .map(|message| (Some(commitment), message)),
+                                );
+                            }
+                        }
+                    }
+                    Some(Err(error)) => {
+                        return Err(Status::internal(format!(
+                            "failed to read replay log: {error}"
+                        )));
+                    }
+                    None if *caught_up => return Ok(None),
+                    None => {
+                        let (messages, messages_rx) = GrpcService::replay_stored_slots(
+                            replay_stored_slots_tx,
+                            commitments.clone(),
+                            *from_slot,
+                            Some(*position),
+                        )
+                        .await?;
+                        let ReplayStoredMessages::Log(reader) = messages else {
+                            unreachable!("replay log is continued");
+                        };
+                        *position = reader.end();
+                        *caught_up = reader.size() <= Self::LOG_CATCH_UP_SIZE;
+                        if *caught_up {
+                            self.messages_rx = Some(messages_rx);
+                        }
+                        *log_rx = reader.spawn(Self::LOG_CHANNEL_CAPACITY);
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ClientFilterUpdate {
+    Filter {
+        from_slot: Option<Slot>,
+        filter: Filter,
+    },
+    Delta(Box<SubscribeRequestFilterDelta>),
+    Credits(u64),
+}
+
 #[derive(Debug)]
 struct BlockhashStatus {
     slot: u64,
@@ -263,9 +1121,62 @@ struct SlotMessages {
     parent_slot: Option<Slot>,
     confirmed: bool,
     finalized: bool,
+    rolled_back: bool,
 }
 
 impl SlotMessages {
+    // Update block reconstruction info, returns Block message once all messages received
+    fn push(&mut self, message: &Message) -> Option<Message> {
+        if let Message::Slot(msg) = message {
+            match msg.status {
+                CommitmentLevel::Processed => {
+                    self.parent_slot = msg.parent;
+                }
+                CommitmentLevel::Confirmed => {
+                    self.confirmed = true;
+                }
+                CommitmentLevel::Finalized => {
+                    self.finalized = true;
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        self.messages.push(Some(message.clone()));
+        match message {
+            Message::BlockMeta(msg) => {
+                self.block_meta = Some(Arc::clone(msg));
+                self.try_seal()
+            }
+            Message::Transaction(msg) => {
+                self.transactions.push(Arc::clone(&msg.transaction));
+                self.try_seal()
+            }
+            // Dedup accounts by max write_version
+            Message::Account(msg) => {
+                let write_version = msg.account.write_version;
+                let msg_index = self.messages.len() - 1;
+                if let Some(entry) = self.accounts_dedup.get_mut(&msg.account.pubkey) {
+                    if entry.0 < write_version {
+                        // We can replace the message, but in this case we will lose the order
+                        self.messages[entry.1] = None;
+                        *entry = (write_version, msg_index);
+                    }
+                } else {
+                    self.accounts_dedup
+                        .insert(msg.account.pubkey, (write_version, msg_index));
+                }
+                None
+            }
+            Message::Entry(msg) => {
+                self.entries.push(Arc::clone(msg));
+                self.try_seal()
+            }
+            _ => None,
+        }
+    }
+
     pub fn try_seal(&mut self) -> Option<Message> {
         if !self.sealed {
             if let Some(block_meta) = &self.block_meta {
@@ -309,17 +1220,304 @@ impl SlotMessages {
     }
 }
 
+// Confirmed and finalized messages restored from processed messages of replay log,
+// batches are created in the same order as in `geyser_loop`
+#[derive(Debug, Default)]
+struct ReplayLogCommitments {
+    messages: BTreeMap<Slot, SlotMessages>,
+}
+
+impl ReplayLogCommitments {
+    fn push(&mut self, message: Message) -> Vec<(CommitmentLevel, Vec<Message>)> {
+        let mut batches = Vec::with_capacity(3);
+        match &message {
+            Message::Slot(msg) => {
+                let slot_messages = self.messages.entry(msg.slot).or_default();
+                slot_messages.push(&message);
+                let (mut confirmed_messages, mut finalized_messages) = match msg.status {
+                    CommitmentLevel::Confirmed => {
+                        if !slot_messages.sealed {
+                            slot_messages.confirmed_at = Some(slot_messages.messages.len());
+                        }
+                        let vec = slot_messages.messages.iter().flatten().cloned().collect();
+                        (vec, Vec::with_capacity(1))
+                    }
+                    CommitmentLevel::Finalized => {
+                        if !slot_messages.sealed {
+                            slot_messages.finalized_at = Some(slot_messages.messages.len());
+                        }
+                        let vec = slot_messages.messages.iter().flatten().cloned().collect();
+                        (Vec::with_capacity(1), vec)
+                    }
+                    _ => (Vec::with_capacity(1), Vec::with_capacity(1)),
+                };
+
+                // keep extra 10 slots
+                if msg.status == CommitmentLevel::Finalized {
+                    self.messages = self.messages.split_off(&msg.slot.saturating_sub(10));
+                }
+
+                batches.push((CommitmentLevel::Processed, vec![message.clone()]));
+                confirmed_messages.push(message.clone());
+                batches.push((CommitmentLevel::Confirmed, confirmed_messages));
+                finalized_messages.push(message);
+                batches.push((CommitmentLevel::Finalized, finalized_messages));
+            }
+            Message::SlotRollback(_) => {
+                batches.push((CommitmentLevel::Processed, vec![message]));
+            }
+            _ => {
+                let slot_messages = self.messages.entry(message.get_slot()).or_default();
+                let sealed_block_msg = slot_messages.push(&message);
+                batches.push((CommitmentLevel::Processed, vec![message]));
+                if let Some(sealed_block_msg) = sealed_block_msg {
+                    batches.push((CommitmentLevel::Processed, vec![sealed_block_msg]));
+                    for (commitment, at) in [
+                        (CommitmentLevel::Confirmed, slot_messages.confirmed_at),
+                        (CommitmentLevel::Finalized, slot_messages.finalized_at),
+                    ] {
+                        if let Some(at) = at {
+                            let vec = slot_messages.messages.as_slice()[at..]
+                                .iter()
+                                .flatten()
+                                .cloned()
+                                .collect();
+                            batches.push((commitment, vec));
+                        }
+                    }
+                }
+            }
+        }
+        batches
+    }
+}
+
+#[derive(Debug)]
+struct ReplayStoredSlotsBatch {
+    commitment: CommitmentLevel,
+    slot_min: Slot,
+    slot_max: Slot,
+    size: usize,
+    messages: Arc<Vec<Message>>,
+}
+
+#[derive(Debug)]
+struct ReplayStoredSlots {
+    slots: u64,
+    size_limit: usize,
+    size: usize,
+    batches: VecDeque<ReplayStoredSlotsBatch>,
+    first_available: Option<Slot>,
+}
+
+impl ReplayStoredSlots {
+    const fn new(slots: u64, size_limit: usize) -> Self {
+        Self {
+            slots,
+            size_limit,
+            size: 0,
+            batches: VecDeque::new(),
+            first_available: None,
+        }
+    }
+
+    fn push(&mut self, commitment: CommitmentLevel, messages: &Arc<Vec<Message>>) {
+        let Some(slot_max) = messages.iter().map(Message::get_slot).max() else {
+            return;
+        };
+        let slot_min = messages
+            .iter()
+            .map(Message::get_slot)
+            .min()
+            .unwrap_or(slot_max);
+
+        // confirmed and finalized batches share the same messages with processed
+        let size = if commitment == CommitmentLevel::Processed {
+            messages.iter().map(Self::get_message_size).sum()
+        } else {
+            0
+        };
+
+        self.batches.push_back(ReplayStoredSlotsBatch {
+            commitment,
+            slot_min,
+            slot_max,
+            size,
+            messages: Arc::clone(messages),
+        });
+        self.size += size;
+
+        // every batch with a message for slot `>= first_available` is kept,
+        // so replay from such slot is never partial
+        let first_available = *self.first_available.get_or_insert(slot_min);
+        let mut first_available_new =
+            first_available.max((slot_max + 1).saturating_sub(self.slots));
+        while self.size > self.size_limit {
+            let Some(batch) = self.batches.front() else {
+                break;
+            };
+            first_available_new = first_available_new.max(batch.slot_max + 1);
+            self.remove_outdated(first_available_new);
+        }
+        if first_available_new != first_available {
+            self.remove_outdated(first_available_new);
+        }
+    }
+
+    fn remove_outdated(&mut self, first_available: Slot) {
+        self.batches
+            .retain(|batch| batch.slot_max >= first_available);
+        self.size = self.batches.iter().map(|batch| batch.size).sum();
+        self.first_available = Some(first_available);
+    }
+
+    // messages of the slot are kept only for replay, encoded ones are released by replacing
+    // messages with copies without cache, the same copy is used by batches of all commitments
+    fn release_encoded(&mut self, slot: Slot) {
+        let mut accounts = HashMap::new();
+        let mut transactions = HashMap::new();
+        let mut add_account = |account: &Arc<MessageAccountInfo>| {
+            if account.encoded.get().is_some() {
+                accounts
+                    .entry(Arc::as_ptr(account))
+                    .or_insert_with(|| Arc::new(account.as_ref().clone()));
+            }
+        };
+        let mut add_transaction = |transaction: &Arc<MessageTransactionInfo>| {
+            if transaction.encoded.get().is_some() {
+                transactions
+                    .entry(Arc::as_ptr(transaction))
+                    .or_insert_with(|| Arc::new(transaction.as_ref().clone()));
+            }
+        };
+        let batches = self
+            .batches
+            .iter()
+            .filter(|batch| batch.slot_min <= slot && slot <= batch.slot_max);
+        for message in batches.flat_map(|batch| batch.messages.iter()) {
+            match message {
+                Message::Account(msg) if msg.slot == slot => add_account(&msg.account),
+                Message::Transaction(msg) if msg.slot == slot => add_transaction(&msg.transaction),
+                Message::Block(msg) if msg.meta.slot == slot => {
+                    msg.accounts.iter().for_each(&mut add_account);
+                    msg.transactions.iter().for_each(&mut add_transaction);
+                }
+                _ => {}
+            }
+        }
+        if accounts.is_empty() && transactions.is_empty() {
+            return;
+        }
+
+        let get_account = |account: &Arc<MessageAccountInfo>| {
+            Arc::clone(accounts.get(&Arc::as_ptr(account)).unwrap_or(account))
+        };
+        let get_transaction = |transaction: &Arc<MessageTransactionInfo>| {
+            Arc::clone(
+                transactions
+                    .get(&Arc::as_ptr(transaction))
+                    .unwrap_or(transaction),
+            )
+        };
+        let batches = self
+            .batches
+            .iter_mut()
+            .filter(|batch| batch.slot_min <= slot && slot <= batch.slot_max);
+        for batch in batches {
+            let messages = batch
+                .messages
+                .iter()
+                .map(|message| match message {
+                    Message::Account(msg) if msg.slot == slot => Message::Account(MessageAccount {
+                        account: get_account(&msg.account),
+                        slot: msg.slot,
+                        is_startup: msg.is_startup,
+                    }),
+                    Message::Transaction(msg) if msg.slot == slot => {
+                        Message::Transaction(MessageTransaction {
+                            transaction: get_transaction(&msg.transaction),
+                            slot: msg.slot,
+                        })
+                    }
+                    Message::Block(msg) if msg.meta.slot == slot => {
+                        Message::Block(Arc::new(MessageBlock {
+                            meta: Arc::clone(&msg.meta),
+                            transactions: msg.transactions.iter().map(get_transaction).collect(),
+                            updated_account_count: msg.updated_account_count,
+                            accounts: msg.accounts.iter().map(get_account).collect(),
+                            entries: msg.entries.clone(),
+                        }))
+                    }
+                    message => message.clone(),
+                })
+                .collect();
+            batch.messages = Arc::new(messages);
+        }
+    }
+
+    // batches of all requested commitments in broadcast order
+    fn replay(
+        &self,
+        commitments: &[CommitmentLevel],
+        from_slot: Slot,
+    ) -> Result<Vec<BroadcastedMessage>, Option<Slot>> {
+        match self.first_available {
+            Some(first_available) if from_slot >= first_available => Ok(self
+                .batches
+                .iter()
+                .filter(|batch| {
+                    commitments.contains(&batch.commitment) && batch.slot_max >= from_slot
+                })
+                .map(|batch| (batch.commitment, Arc::clone(&batch.messages)))
+                .collect()),
+            first_available => Err(first_available),
+        }
+    }
+
+    fn get_message_size(message: &Message) -> usize {
+        mem::size_of::<Message>()
+            + match message {
+                Message::Slot(_) | Message::SlotRollback(_) => 0,
+                // raw size only, encoded messages shared with clients are not counted
+                Message::Account(msg) => {
+                    mem::size_of::<MessageAccountInfo>() + msg.account.data.len()
+                }
+                Message::Transaction(msg) => {
+                    mem::size_of::<MessageTransactionInfo>()
+                        + msg.transaction.transaction.encoded_len()
+                        + msg.transaction.meta.encoded_len()
+                        + (msg.transaction.account_keys.len() + msg.transaction.signers.len())
+                            * mem::size_of::<Pubkey>()
+                }
+                Message::Entry(_) => mem::size_of::<MessageEntry>(),
+                Message::BlockMeta(msg) => mem::size_of::<MessageBlockMeta>() + msg.encoded_len(),
+                // transactions, accounts and entries are shared with other messages
+                Message::Block(msg) => {
+                    mem::size_of::<MessageBlock>()
+                        + (msg.transactions.len() + msg.accounts.len() + msg.entries.len())
+                            * mem::size_of::<usize>()
+                }
+            }
+    }
+}
+
 #[derive(Debug)]
 pub struct GrpcService {
     config_snapshot_client_channel_capacity: usize,
     config_channel_capacity: usize,
+    config_slow_consumer_buffer_capacity: usize,
+    config_slow_consumer_policy: ConfigGrpcSlowConsumerPolicy,
     config_filter_limits: Arc<FilterLimits>,
+    config_rate_limit: Option<ConfigGrpcRateLimit>,
+    connections: Arc<ClientConnections>,
     blocks_meta: Option<BlockMetaStorage>,
     subscribe_id: AtomicUsize,
     snapshot_rx: Mutex<Option<crossbeam_channel::Receiver<Box<Message>>>>,
-    broadcast_tx: broadcast::Sender<(CommitmentLevel, Arc<Vec<Message>>)>,
+    broadcast_tx: broadcast::Sender<BroadcastedMessage>,
+    replay_stored_slots_tx: Option<mpsc::Sender<ReplayStoredSlotsRequest>>,
     debug_clients_tx: Option<mpsc::UnboundedSender<DebugClientMessage>>,
     filter_names: Arc<Mutex<FilterNames>>,
+    mint_decimals: FilterMintDecimals,
 }
 
 impl GrpcService {
@@ -362,6 +1560,31 @@ impl GrpcService {
         // Messages to clients combined by commitment
         let (broadcast_tx, _) = broadcast::channel(config.channel_capacity);
 
+        // Stored messages for replay with `from_slot`
+        let replay_stored_slots = (config.replay_stored_slots > 0).then(|| {
+            ReplayStoredSlots::new(config.replay_stored_slots, config.replay_stored_size_limit)
+        });
+        let message_log_tx = match config.replay_log.clone() {
+            Some(config) => {
+                let path = config.path.clone();
+                let message_log = spawn_blocking(move || MessageLog::open(config))
+                    .await?
+                    .with_context(|| format!("failed to open replay log at {path:?}"))?;
+
+                // disk writes should not block broadcast of messages
+                let (tx, rx) = mpsc::channel(MESSAGE_LOG_CHANNEL_CAPACITY);
+                std::thread::Builder::new()
+                    .name("solGeyserGrpcLog".to_owned())
+                    .spawn(move || Self::message_log_loop(message_log, rx))
+                    .context("failed to spawn replay log thread")?;
+                Some(tx)
+            }
+            None => None,
+        };
+        let (replay_stored_slots_tx, replay_stored_slots_rx) = mpsc::channel(1);
+        let replay_stored_slots_tx = (replay_stored_slots.is_some() || message_log_tx.is_some())
+            .then_some(replay_stored_slots_tx);
+
         // gRPC server builder with optional TLS
         let mut server_builder = Server::builder();
         if let Some(tls_config) = &config.tls_config {
@@ -381,18 +1604,58 @@ impl GrpcService {
             config.filter_names_cleanup_interval,
         )));
 
+        // Decimals of mints for `jsonParsed` encoding, shared by all clients
+        let mint_decimals =
+            FilterMintDecimals::new(config.filter_limits.accounts.json_parsed_mint_cache_max);
+
+        // Tokens with own limits
+        let config_filter_limits = Arc::new(config.filter_limits);
+        let token_rate_limit = config.rate_limit.as_ref().and_then(|config| config.token);
+        let connections_max_per_token = config.connections_max_per_token;
+        let x_tokens = config
+            .x_tokens
+            .into_iter()
+            .map(|(token, config)| {
+                let token_config = ClientToken::new(
+                    config,
+                    &config_filter_limits,
+                    connections_max_per_token,
+                    token_rate_limit,
+                );
+                (token, Arc::new(token_config))
+            })
+            .collect::<HashMap<_, _>>();
+        let jwt = config
+            .jwt
+            .as_ref()
+            .map(JwtValidator::new)
+            .transpose()
+            .context("failed to create jwt validator")?
+            .map(Arc::new);
+        let jwt_filter_limits = Arc::clone(&config_filter_limits);
+        let jwt_subjects = Arc::new(ClientJwtSubjects::default());
+
         // Create Server
         let max_decoding_message_size = config.max_decoding_message_size;
         let mut service = GeyserServer::new(Self {
             config_snapshot_client_channel_capacity: config.snapshot_client_channel_capacity,
             config_channel_capacity: config.channel_capacity,
-            config_filter_limits: Arc::new(config.filter_limits),
+            config_slow_consumer_buffer_capacity: config.slow_consumer_buffer_capacity,
+            config_slow_consumer_policy: config.slow_consumer_policy,
+            config_filter_limits,
+            config_rate_limit: config.rate_limit,
+            connections: Arc::new(ClientConnections::new(
+                config.connections_max,
+                config.connections_max_per_endpoint,
+            )),
             blocks_meta,
             subscribe_id: AtomicUsize::new(0),
             snapshot_rx: Mutex::new(snapshot_rx),
             broadcast_tx: broadcast_tx.clone(),
+            replay_stored_slots_tx,
             debug_clients_tx,
             filter_names,
+            mint_decimals: mint_decimals.clone(),
         })
         .max_decoding_message_size(max_decoding_message_size);
         for encoding in config.compression.accept {
@@ -411,7 +1674,15 @@ impl GrpcService {
                 .enable_all()
                 .build()
                 .expect("Failed to create a new runtime for geyser loop")
-                .block_on(Self::geyser_loop(messages_rx, blocks_meta_tx, broadcast_tx));
+                .block_on(Self::geyser_loop(
+                    messages_rx,
+                    blocks_meta_tx,
+                    broadcast_tx,
+                    replay_stored_slots_rx,
+                    replay_stored_slots,
+                    message_log_tx,
+                    mint_decimals,
+                ));
         });
 
         // Run Server
@@ -424,15 +1695,43 @@ impl GrpcService {
 
             server_builder
                 .http2_keepalive_interval(Some(Duration::from_secs(5)))
-                .layer(interceptor(move |request: Request<()>| {
-                    if let Some(x_token) = &config.x_token {
-                        match request.metadata().get("x-token") {
-                            Some(token) if x_token == token => Ok(request),
-                            _ => Err(Status::unauthenticated("No valid auth token")),
-                        }
-                    } else {
-                        Ok(request)
+                .layer(interceptor(move |mut request: Request<()>| {
+                    if config.x_token.is_none() && x_tokens.is_empty() && jwt.is_none() {
+                        return Ok(request);
                     }
+
+                    let token = request
+                        .metadata()
+                        .get("x-token")
+                        .and_then(|token| token.to_str().ok());
+                    if token.is_some() && token == config.x_token.as_deref() {
+                        return Ok(request);
+                    }
+                    let client_token = match (token, &jwt) {
+                        (Some(token), _) if x_tokens.contains_key(token) => {
+                            Arc::clone(&x_tokens[token])
+                        }
+                        (Some(token), Some(jwt)) => {
+                            match ClientToken::from_jwt(
+                                jwt,
+                                &jwt_subjects,
+                                token,
+                                &jwt_filter_limits,
+                                connections_max_per_token,
+                                token_rate_limit,
+                            ) {
+                                Ok(client_token) => Arc::new(client_token),
+                                Err(error) => {
+                                    return Err(Status::unauthenticated(format!(
+                                        "No valid auth token: {error}"
+                                    )))
+                                }
+                            }
+                        }
+                        _ => return Err(Status::unauthenticated("No valid auth token")),
+                    };
+                    request.extensions_mut().insert(client_token);
+                    Ok(request)
                 }))
                 .add_service(health_service)
                 .add_service(service)
@@ -446,12 +1745,17 @@ impl GrpcService {
     async fn geyser_loop(
         mut messages_rx: mpsc::UnboundedReceiver<Message>,
         blocks_meta_tx: Option<mpsc::UnboundedSender<Message>>,
-        broadcast_tx: broadcast::Sender<(CommitmentLevel, Arc<Vec<Message>>)>,
+        broadcast_tx: broadcast::Sender<BroadcastedMessage>,
+        mut replay_stored_slots_rx: mpsc::Receiver<ReplayStoredSlotsRequest>,
+        mut replay_stored_slots: Option<ReplayStoredSlots>,
+        message_log_tx: Option<mpsc::Sender<MessageLogRequest>>,
+        mint_decimals: FilterMintDecimals,
     ) {
         const PROCESSED_MESSAGES_MAX: usize = 31;
         const PROCESSED_MESSAGES_SLEEP: Duration = Duration::from_millis(10);
 
         let mut messages: BTreeMap<u64, SlotMessages> = Default::default();
+        let mut message_log = message_log_tx.map(|tx| (tx, false)); // (sender, gap)
         let mut processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
         let mut processed_first_slot = None;
         let processed_sleep = sleep(PROCESSED_MESSAGES_SLEEP);
@@ -467,6 +1771,11 @@ impl GrpcService {
                         metrics::update_slot_plugin_status(slot_message.status, slot_message.slot);
                     }
 
+                    // Update decimals of mints before messages are filtered
+                    if let Message::Account(msg) = &message {
+                        mint_decimals.update(&msg.account);
+                    }
+
                     // Update blocks info
                     if let Some(blocks_meta_tx) = &blocks_meta_tx {
                         if matches!(&message, Message::Slot(_) | Message::BlockMeta(_)) {
@@ -488,6 +1797,10 @@ impl GrpcService {
                                     match messages.keys().next().cloned() {
                                         Some(slot) if slot < msg_slot => {
                                             if let Some(slot_messages) = messages.remove(&slot) {
+                                                if let Some(replay_stored_slots) = replay_stored_slots.as_mut() {
+                                                    replay_stored_slots.release_encoded(slot);
+                                                }
+
                                                 match processed_first_slot {
                                                     Some(processed_first) if slot <= processed_first => continue,
                                                     None => continue,
@@ -528,27 +1841,12 @@ impl GrpcService {
 
                     // Update block reconstruction info
                     let slot_messages = messages.entry(message.get_slot()).or_default();
-                    if let Message::Slot(msg) = &message {
-                        match msg.status {
-                            CommitmentLevel::Processed => {
-                                slot_messages.parent_slot = msg.parent;
-                            },
-                            CommitmentLevel::Confirmed => {
-                                slot_messages.confirmed = true;
-                            },
-                            CommitmentLevel::Finalized => {
-                                slot_messages.finalized = true;
-                            },
-                            _ => {}
-                        }
-                    }
                     if !matches!(&message, Message::Slot(_)) {
-                        slot_messages.messages.push(Some(message.clone()));
-
                         // If we already build Block message, new message will be a problem
                         if slot_messages.sealed && !(matches!(&message, Message::Entry(_)) && slot_messages.entries_count == 0) {
                             let kind = match &message {
                                 Message::Slot(_) => "Slot",
+                                Message::SlotRollback(_) => "SlotRollback",
                                 Message::Account(_) => "Account",
                                 Message::Transaction(_) => "Transaction",
                                 Message::Entry(_) => "Entry",
@@ -558,38 +1856,49 @@ impl GrpcService {
                             metrics::update_invalid_blocks(format!("unexpected message {kind}"));
                         }
                     }
-                    let mut sealed_block_msg = None;
-                    match &message {
-                        Message::BlockMeta(msg) => {
-                            if slot_messages.block_meta.is_some() {
-                                metrics::update_invalid_blocks("unexpected message: BlockMeta (duplicate)");
+                    if matches!(&message, Message::BlockMeta(_)) && slot_messages.block_meta.is_some() {
+                        metrics::update_invalid_blocks("unexpected message: BlockMeta (duplicate)");
+                    }
+                    let sealed_block_msg = slot_messages.push(&message);
+
+                    // Processed data of abandoned forks and dead slots should be discarded by clients,
+                    // rollbacks are sent before slot status which flushes processed messages
+                    if let Message::Slot(msg) = &message {
+                        match msg.status {
+                            CommitmentLevel::Finalized => {
+                                // every known slot between rooted one and its oldest known ancestor
+                                // is on abandoned fork if it's not an ancestor
+                                let mut ancestors = HashSet::new();
+                                let mut slot = msg.slot;
+                                while let Some(parent) = messages.get(&slot).and_then(|entry| entry.parent_slot) {
+                                    ancestors.insert(parent);
+                                    slot = parent;
+                                }
+                                for (slot, slot_messages) in messages.range_mut(slot..msg.slot) {
+                                    if !ancestors.contains(slot) && !slot_messages.rolled_back {
+                                        slot_messages.rolled_back = true;
+                                        processed_messages.push(Message::SlotRollback(MessageSlotRollback {
+                                            slot: *slot,
+                                            reason: SlotRollbackReason::AbandonedFork,
+                                        }));
+                                        metrics::slot_rollback_inc(SlotRollbackReason::AbandonedFork);
+                                    }
+                                }
                             }
-                            slot_messages.block_meta = Some(Arc::clone(msg));
-                            sealed_block_msg = slot_messages.try_seal();
-                        }
-                        Message::Transaction(msg) => {
-                            slot_messages.transactions.push(Arc::clone(&msg.transaction));
-                            sealed_block_msg = slot_messages.try_seal();
-                        }
-                        // Dedup accounts by max write_version
-                        Message::Account(msg) => {
-                            let write_version = msg.account.write_version;
-                            let msg_index = slot_messages.messages.len() - 1;
-                            if let Some(entry) = slot_messages.accounts_dedup.get_mut(&msg.account.pubkey) {
-                                if entry.0 < write_version {
-                                    // We can replace the message, but in this case we will lose the order
-                                    slot_messages.messages[entry.1] = None;
-                                    *entry = (write_version, msg_index);
+                            CommitmentLevel::Dead => {
+                                if let Some(slot_messages) = messages.get_mut(&msg.slot) {
+                                    if !slot_messages.rolled_back {
+                                        slot_messages.rolled_back = true;
+                                        processed_messages.push(Message::SlotRollback(MessageSlotRollback {
+                                            slot: msg.slot,
+                                            reason: SlotRollbackReason::DeadSlot,
+                                        }));
+                                        metrics::slot_rollback_inc(SlotRollbackReason::DeadSlot);
+                                    }
                                 }
-                            } else {
-                                slot_messages.accounts_dedup.insert(msg.account.pubkey, (write_version, msg_index));
                             }
+                            _ => {}
                         }
-                        Message::Entry(msg) => {
-                            slot_messages.entries.push(Arc::clone(msg));
-                            sealed_block_msg = slot_messages.try_seal();
-                        }
-                        _ => {}
                     }
 
                     // Send messages to filter (and to clients)
@@ -670,8 +1979,7 @@ impl GrpcService {
 
                             // processed
                             processed_messages.push(message.clone());
-                            let _ =
-                                broadcast_tx.send((CommitmentLevel::Processed, processed_messages.into()));
+                            Self::broadcast(&broadcast_tx, &mut replay_stored_slots, &mut message_log, CommitmentLevel::Processed, processed_messages);
                             processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
                             processed_sleep
                                 .as_mut()
@@ -679,13 +1987,11 @@ impl GrpcService {
 
                             // confirmed
                             confirmed_messages.push(message.clone());
-                            let _ =
-                                broadcast_tx.send((CommitmentLevel::Confirmed, confirmed_messages.into()));
+                            Self::broadcast(&broadcast_tx, &mut replay_stored_slots, &mut message_log, CommitmentLevel::Confirmed, confirmed_messages);
 
                             // finalized
                             finalized_messages.push(message);
-                            let _ =
-                                broadcast_tx.send((CommitmentLevel::Finalized, finalized_messages.into()));
+                            Self::broadcast(&broadcast_tx, &mut replay_stored_slots, &mut message_log, CommitmentLevel::Finalized, finalized_messages);
                         } else {
                             let mut confirmed_messages = vec![];
                             let mut finalized_messages = vec![];
@@ -709,8 +2015,7 @@ impl GrpcService {
                                 || !confirmed_messages.is_empty()
                                 || !finalized_messages.is_empty()
                             {
-                                let _ = broadcast_tx
-                                    .send((CommitmentLevel::Processed, processed_messages.into()));
+                                Self::broadcast(&broadcast_tx, &mut replay_stored_slots, &mut message_log, CommitmentLevel::Processed, processed_messages);
                                 processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
                                 processed_sleep
                                     .as_mut()
@@ -718,25 +2023,151 @@ impl GrpcService {
                             }
 
                             if !confirmed_messages.is_empty() {
-                                let _ =
-                                    broadcast_tx.send((CommitmentLevel::Confirmed, confirmed_messages.into()));
+                                Self::broadcast(&broadcast_tx, &mut replay_stored_slots, &mut message_log, CommitmentLevel::Confirmed, confirmed_messages);
                             }
 
-                            if !finalized_messages.is_empty() {
+                            if !finalized_messages.is_empty() {
+                                Self::broadcast(&broadcast_tx, &mut replay_stored_slots, &mut message_log, CommitmentLevel::Finalized, finalized_messages);
+                            }
+                        }
+                    }
+                }
+                Some((commitments, from_slot, position, tx)) = replay_stored_slots_rx.recv() => {
+                    // new receiver gets only messages which are not stored yet
+                    let first_available_memory = match replay_stored_slots
+                        .as_ref()
+                        .filter(|_| position.is_none())
+                        .map(|stored| stored.replay(&commitments, from_slot))
+                    {
+                        Some(Ok(messages)) => {
+                            let _ = tx.send(Ok((ReplayStoredMessages::Memory(messages), broadcast_tx.subscribe())));
+                            continue;
+                        }
+                        Some(Err(first_available)) => first_available,
+                        None => None,
+                    };
+
+                    let Some((message_log_tx, _gap)) = &message_log else {
+                        let _ = tx.send(Err(ReplayStoredSlotsError::NotAvailable(first_available_memory)));
+                        continue;
+                    };
+                    let request = MessageLogRequest::Replay {
+                        from_slot,
+                        position,
+                        first_available_memory,
+                        messages_rx: broadcast_tx.subscribe(),
+                        tx,
+                    };
+                    match message_log_tx.try_send(request) {
+                        Ok(()) => {}
+                        Err(mpsc::error::TrySendError::Full(MessageLogRequest::Replay { tx, .. })) => {
+                            let _ = tx.send(Err(ReplayStoredSlotsError::Busy));
+                        }
+                        Err(mpsc::error::TrySendError::Closed(MessageLogRequest::Replay { tx, .. })) => {
+                            let _ = tx.send(Err(ReplayStoredSlotsError::NotAvailable(first_available_memory)));
+                        }
+                        Err(_) => unreachable!("replay request is sent"),
+                    }
+                }
+                () = &mut processed_sleep => {
+                    if !processed_messages.is_empty() {
+                        Self::broadcast(&broadcast_tx, &mut replay_stored_slots, &mut message_log, CommitmentLevel::Processed, processed_messages);
+                        processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
+                    }
+                    processed_sleep.as_mut().reset(Instant::now() + PROCESSED_MESSAGES_SLEEP);
+                }
+                else => break,
+            }
+        }
+    }
+
+    fn broadcast(
+        broadcast_tx: &broadcast::Sender<BroadcastedMessage>,
+        replay_stored_slots: &mut Option<ReplayStoredSlots>,
+        message_log: &mut Option<(mpsc::Sender<MessageLogRequest>, bool)>,
+        commitment: CommitmentLevel,
+        messages: Vec<Message>,
+    ) {
+        let messages = Arc::new(messages);
+        if let Some(replay_stored_slots) = replay_stored_slots {
+            replay_stored_slots.push(commitment, &messages);
+        }
+        // confirmed and finalized messages are restored from processed on replay
+        if let (Some((message_log_tx, gap)), CommitmentLevel::Processed) = (message_log, commitment)
+        {
+            let request = MessageLogRequest::Append {
+                messages: Arc::clone(&messages),
+                gap: *gap,
+            };
+            match message_log_tx.try_send(request) {
+                Ok(()) => *gap = false,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    if !*gap {
+                        error!(
+                            "replay log: writer queue is full, stored messages would be removed"
+                        );
+                    }
+                    *gap = true;
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {}
+            }
+        }
+        let _ = broadcast_tx.send((commitment, messages));
+    }
+
+    fn message_log_loop(
+        message_log: MessageLog,
+        mut message_log_rx: mpsc::Receiver<MessageLogRequest>,
+    ) {
+        let mut message_log = Some(message_log);
+        while let Some(request) = message_log_rx.blocking_recv() {
+            match request {
+                MessageLogRequest::Append { messages, gap } => {
+                    let Some(log) = &mut message_log else {
+                        continue;
+                    };
+                    let mut result = Ok(());
+                    if gap {
+                        // replay over missed messages would be silently incomplete
+                        result = log.clear();
+                    }
+                    if let Err(error) = result.and_then(|()| log.append(&messages)) {
+                        // log with missed messages can not be used for replay anymore
+                        error!("failed to write replay log, replay from disk disabled: {error}");
+                        message_log = None;
+                    }
+                }
+                MessageLogRequest::Replay {
+                    from_slot,
+                    position,
+                    first_available_memory,
+                    messages_rx,
+                    tx,
+                } => {
+                    let mut first_available_log = None;
+                    if let Some(log) = &mut message_log {
+                        let result = match position {
+                            Some(position) => log.replay_after(position),
+                            None => log.replay(from_slot),
+                        };
+                        match result {
+                            Ok(Some(reader)) => {
                                 let _ =
-                                    broadcast_tx.send((CommitmentLevel::Finalized, finalized_messages.into()));
+                                    tx.send(Ok((ReplayStoredMessages::Log(reader), messages_rx)));
+                                continue;
                             }
+                            Ok(None) => {}
+                            Err(error) => error!("failed to replay from disk log: {error}"),
                         }
+                        first_available_log = log.first_available();
                     }
+
+                    let first_available = match (first_available_memory, first_available_log) {
+                        (Some(memory), Some(log)) => Some(memory.min(log)),
+                        (memory, log) => memory.or(log),
+                    };
+                    let _ = tx.send(Err(ReplayStoredSlotsError::NotAvailable(first_available)));
                 }
-                () = &mut processed_sleep => {
-                    if !processed_messages.is_empty() {
-                        let _ = broadcast_tx.send((CommitmentLevel::Processed, processed_messages.into()));
-                        processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
-                    }
-                    processed_sleep.as_mut().reset(Instant::now() + PROCESSED_MESSAGES_SLEEP);
-                }
-                else => break,
             }
         }
     }
@@ -746,10 +2177,15 @@ impl GrpcService {
         id: usize,
         endpoint: String,
         stream_tx: mpsc::Sender<TonicResult<FilteredUpdate>>,
-        mut client_rx: mpsc::UnboundedReceiver<Option<Filter>>,
+        mut client_rx: mpsc::UnboundedReceiver<Option<ClientFilterUpdate>>,
         mut snapshot_rx: Option<crossbeam_channel::Receiver<Box<Message>>>,
-        mut messages_rx: broadcast::Receiver<(CommitmentLevel, Arc<Vec<Message>>)>,
+        mut messages_rx: broadcast::Receiver<BroadcastedMessage>,
+        replay_stored_slots_tx: Option<mpsc::Sender<ReplayStoredSlotsRequest>>,
         debug_client_tx: Option<mpsc::UnboundedSender<DebugClientMessage>>,
+        config_filter_limits: Arc<FilterLimits>,
+        mint_decimals: FilterMintDecimals,
+        mut rate_limits: Option<ClientRateLimits>,
+        mut slow_consumer: ClientSlowConsumer,
         drop_client: impl FnOnce(),
     ) {
         let mut filter = Filter::default();
@@ -762,6 +2198,9 @@ impl GrpcService {
         });
         info!("client #{id}: new");
 
+        let mut transactions_commitment = ClientTransactionsCommitment::default();
+        let mut slots_rollback = ClientSlotsRollback::default();
+        let mut replay = None;
         let mut is_alive = true;
         if let Some(snapshot_rx) = snapshot_rx.take() {
             Self::client_loop_snapshot(
@@ -769,62 +2208,146 @@ impl GrpcService {
                 &endpoint,
                 &stream_tx,
                 &mut client_rx,
-                snapshot_rx,
                 &mut is_alive,
                 &mut filter,
+                &config_filter_limits,
+                &mut slow_consumer,
             )
             .await;
+            replay = Some(ClientReplay::snapshot(snapshot_rx, mint_decimals));
         }
 
         if is_alive {
+            // handlers move `stream_tx` to send error, `reserve` borrows own sender
+            let pending_stream_tx = stream_tx.clone();
             'outer: loop {
                 tokio::select! {
-                    mut message = client_rx.recv() => {
-                        // forward to latest filter
-                        loop {
-                            match client_rx.try_recv() {
-                                Ok(message_new) => {
-                                    message = Some(message_new);
+                    message = client_rx.recv() => {
+                        let Some(updates) = Self::client_coalesce_updates(message, &mut client_rx) else {
+                            break 'outer;
+                        };
+
+                        for update in updates {
+                            match update {
+                                ClientFilterUpdate::Filter { from_slot, filter: filter_new } => {
+                                    if let Some(msg) = filter_new.get_pong_msg() {
+                                        if stream_tx.send(Ok(msg)).await.is_err() {
+                                            error!("client #{id}: stream closed");
+                                            break 'outer;
+                                        }
+                                        continue;
+                                    }
+
+                                    metrics::update_subscriptions(&endpoint, Some(&filter), Some(&filter_new));
+                                    filter = filter_new;
+                                    if !filter.has_transactions_commitment_updates() {
+                                        transactions_commitment.clear();
+                                    }
+                                    DebugClientMessage::maybe_send(&debug_client_tx, || DebugClientMessage::UpdateFilter { id, filter: Box::new(filter.clone()) });
+                                    info!("client #{id}: filter updated");
+
+                                    // `from_slot` without enabled replay rejected on filter creation
+                                    if let (Some(from_slot), Some(replay_stored_slots_tx)) = (from_slot, &replay_stored_slots_tx) {
+                                        // every commitment used by filters is replayed, as it is broadcasted
+                                        let commitments = [CommitmentLevel::Processed, CommitmentLevel::Confirmed, CommitmentLevel::Finalized]
+                                            .into_iter()
+                                            .filter(|commitment| filter.has_commitment_level(*commitment))
+                                            .collect::<Vec<_>>();
+                                        let (messages, messages_rx_new) = match Self::replay_stored_slots(replay_stored_slots_tx, commitments.clone(), from_slot, None).await {
+                                            Ok(value) => value,
+                                            Err(status) => {
+                                                info!("client #{id}: failed to replay: {}", status.message());
+                                                tokio::spawn(async move {
+                                                    let _ = stream_tx.send(Err(status)).await;
+                                                });
+                                                break 'outer;
+                                            }
+                                        };
+
+                                        info!("client #{id}: replay from slot {from_slot}");
+                                        replay = Some(ClientReplay::new(replay_stored_slots_tx, commitments, from_slot, messages, messages_rx_new));
+                                    }
                                 }
-                                Err(mpsc::error::TryRecvError::Empty) => break,
-                                Err(mpsc::error::TryRecvError::Disconnected) => {
-                                    message = None;
-                                    break;
+                                ClientFilterUpdate::Delta(delta) => {
+                                    metrics::update_subscriptions(&endpoint, Some(&filter), None);
+                                    let result = filter.apply_delta(&delta, &config_filter_limits);
+                                    metrics::update_subscriptions(&endpoint, None, Some(&filter));
+                                    if let Err(error) = result {
+                                        info!("client #{id}: failed to apply filter delta: {error}");
+                                        tokio::spawn(async move {
+                                            let _ = stream_tx.send(Err(Status::invalid_argument(format!("failed to update filter: {error}")))).await;
+                                        });
+                                        break 'outer;
+                                    }
+                                    DebugClientMessage::maybe_send(&debug_client_tx, || DebugClientMessage::UpdateFilter { id, filter: Box::new(filter.clone()) });
+                                    info!("client #{id}: filter delta applied");
+                                }
+                                ClientFilterUpdate::Credits(grant) => {
+                                    slow_consumer.grant(grant);
                                 }
                             }
                         }
-
-                        match message {
-                            Some(Some(filter_new)) => {
-                                if let Some(msg) = filter_new.get_pong_msg() {
-                                    if stream_tx.send(Ok(msg)).await.is_err() {
-                                        error!("client #{id}: stream closed");
-                                        break 'outer;
+                    }
+                    permit = pending_stream_tx.reserve(), if slow_consumer.has_pending() => {
+                        match (permit, slow_consumer.pop_pending()) {
+                            (Ok(permit), Some(message)) => permit.send(message),
+                            (Ok(_permit), None) => {}
+                            (Err(_error), _) => {
+                                error!("client #{id}: stream closed");
+                                break 'outer;
+                            }
+                        }
+                    }
+                    // replayed messages are read once client is able to receive them
+                    message = ClientReplay::next_ready(&pending_stream_tx, &mut replay), if replay.is_some() && slow_consumer.is_ready() => {
+                        let (commitment, message) = match message {
+                            Ok(Some(message)) => message,
+                            Ok(None) => {
+                                // only replay of stored slots has own receiver of live messages
+                                match replay.take().and_then(|replay| replay.messages_rx) {
+                                    Some(messages_rx_new) => {
+                                        messages_rx = messages_rx_new;
+                                        info!("client #{id}: replay finished");
                                     }
-                                    continue;
+                                    None => info!("client #{id}: end of startup"),
                                 }
-
-                                metrics::update_subscriptions(&endpoint, Some(&filter), Some(&filter_new));
-                                filter = filter_new;
-                                DebugClientMessage::maybe_send(&debug_client_tx, || DebugClientMessage::UpdateFilter { id, filter: Box::new(filter.clone()) });
-                                info!("client #{id}: filter updated");
+                                continue 'outer;
                             }
-                            Some(None) => {
-                                break 'outer;
-                            },
-                            None => {
+                            Err(status) => {
+                                info!("client #{id}: failed to replay: {}", status.message());
+                                tokio::spawn(async move {
+                                    let _ = stream_tx.send(Err(status)).await;
+                                });
                                 break 'outer;
                             }
+                        };
+
+                        let slot = message.get_slot();
+                        slow_consumer.last_slot = slot;
+                        let updates = Self::client_get_updates(&filter, &mut transactions_commitment, &mut slots_rollback, &message, commitment);
+                        let transactions_commitment = filter.has_transactions_commitment_updates().then_some(&mut transactions_commitment);
+                        if let Err(error) = Self::client_send_updates(&stream_tx, &mut rate_limits, &mut slow_consumer, transactions_commitment, &mut slots_rollback, slot, updates) {
+                            Self::client_send_error(id, stream_tx, error);
+                            break 'outer;
                         }
+                        filter.reset_accounts_diff(slow_consumer.dropped_accounts.drain(..));
                     }
-                    message = messages_rx.recv() => {
+                    message = messages_rx.recv(), if replay.is_none() => {
                         let (commitment, messages) = match message {
-                            Ok((commitment, messages)) => (commitment, messages),
+                            Ok((commitment, messages)) => {
+                                if let Some(message) = messages.first() {
+                                    slow_consumer.received(message.get_slot());
+                                }
+                                (commitment, messages)
+                            }
                             Err(broadcast::error::RecvError::Closed) => {
                                 break 'outer;
                             },
-                            Err(broadcast::error::RecvError::Lagged(_)) => {
+                            Err(broadcast::error::RecvError::Lagged(count)) => {
                                 info!("client #{id}: lagged to receive geyser messages");
+                                if slow_consumer.lagged(count).is_ok() {
+                                    continue 'outer;
+                                }
                                 tokio::spawn(async move {
                                     let _ = stream_tx.send(Err(Status::internal("lagged"))).await;
                                 });
@@ -832,24 +2355,17 @@ impl GrpcService {
                             }
                         };
 
-                        if commitment == filter.get_commitment_level() {
+                        if filter.has_commitment_level(commitment) {
                             for message in messages.iter() {
-                                for message in filter.get_updates(message, Some(commitment)) {
-                                    match stream_tx.try_send(Ok(message)) {
-                                        Ok(()) => {}
-                                        Err(mpsc::error::TrySendError::Full(_)) => {
-                                            error!("client #{id}: lagged to send update");
-                                            tokio::spawn(async move {
-                                                let _ = stream_tx.send(Err(Status::internal("lagged"))).await;
-                                            });
-                                            break 'outer;
-                                        }
-                                        Err(mpsc::error::TrySendError::Closed(_)) => {
-                                            error!("client #{id}: stream closed");
-                                            break 'outer;
-                                        }
-                                    }
+                                let slot = message.get_slot();
+                                slow_consumer.last_slot = slot;
+                                let updates = Self::client_get_updates(&filter, &mut transactions_commitment, &mut slots_rollback, message, Some(commitment));
+                                let transactions_commitment = filter.has_transactions_commitment_updates().then_some(&mut transactions_commitment);
+                                if let Err(error) = Self::client_send_updates(&stream_tx, &mut rate_limits, &mut slow_consumer, transactions_commitment, &mut slots_rollback, slot, updates) {
+                                    Self::client_send_error(id, stream_tx, error);
+                                    break 'outer;
                                 }
+                                filter.reset_accounts_diff(slow_consumer.dropped_accounts.drain(..));
                             }
                         }
 
@@ -872,21 +2388,166 @@ impl GrpcService {
         drop_client();
     }
 
+    // forward to latest filter, deltas received after it applied in order, credits are kept,
+    // returns `None` once close is received, queued updates are not applied after it
+    fn client_coalesce_updates(
+        message: Option<Option<ClientFilterUpdate>>,
+        client_rx: &mut mpsc::UnboundedReceiver<Option<ClientFilterUpdate>>,
+    ) -> Option<Vec<ClientFilterUpdate>> {
+        let mut updates = vec![message??];
+        loop {
+            match client_rx.try_recv() {
+                Ok(Some(update)) => {
+                    if matches!(&update, ClientFilterUpdate::Filter { filter, .. } if filter.get_pong_msg().is_none())
+                    {
+                        updates.retain(|update| matches!(update, ClientFilterUpdate::Credits(_)));
+                    }
+                    updates.push(update);
+                }
+                Ok(None) | Err(mpsc::error::TryRecvError::Disconnected) => return None,
+                Err(mpsc::error::TryRecvError::Empty) => return Some(updates),
+            }
+        }
+    }
+
+    // Live and replayed messages produce the same updates, commitment of delivered
+    // transactions and rollbacks of delivered slots are sent with processed messages
+    fn client_get_updates(
+        filter: &Filter,
+        transactions_commitment: &mut ClientTransactionsCommitment,
+        slots_rollback: &mut ClientSlotsRollback,
+        message: &Message,
+        commitment: Option<CommitmentLevel>,
+    ) -> FilteredUpdates {
+        let mut updates = filter.get_updates(message, commitment);
+        // slot statuses are broadcasted with every commitment
+        if commitment == Some(CommitmentLevel::Processed) {
+            if let Message::Slot(message) = message {
+                updates.extend(transactions_commitment.get_updates(message));
+            }
+            updates.extend(slots_rollback.get_update(message));
+        }
+        updates
+    }
+
+    // Live, replayed and snapshot updates are sent with the same rate limits and slow consumer policy
+    fn client_send_updates(
+        stream_tx: &mpsc::Sender<TonicResult<FilteredUpdate>>,
+        rate_limits: &mut Option<ClientRateLimits>,
+        slow_consumer: &mut ClientSlowConsumer,
+        mut transactions_commitment: Option<&mut ClientTransactionsCommitment>,
+        slots_rollback: &mut ClientSlotsRollback,
+        slot: Slot,
+        updates: impl IntoIterator<Item = FilteredUpdate>,
+    ) -> Result<(), ClientSendError> {
+        for message in updates {
+            let mut notice = None;
+            if let Some(rate_limits) = rate_limits.as_mut() {
+                match rate_limits.try_acquire(&message) {
+                    Ok(value) => notice = value,
+                    Err(_limit) if rate_limits.policy == ConfigGrpcRateLimitPolicy::Drop => {
+                        slow_consumer.dropped(&message);
+                        continue;
+                    }
+                    Err(limit) => return Err(ClientSendError::RateLimited(limit)),
+                }
+            }
+
+            if let Some(transactions_commitment) = transactions_commitment.as_mut() {
+                transactions_commitment.track(&message);
+            }
+            slots_rollback.track(slot, &message);
+            for message in notice.into_iter().chain([message]) {
+                slow_consumer.send(stream_tx, slot, message)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn client_send_error(
+        id: usize,
+        stream_tx: mpsc::Sender<TonicResult<FilteredUpdate>>,
+        error: ClientSendError,
+    ) {
+        let status = match error {
+            ClientSendError::Lagged => {
+                error!("client #{id}: lagged to send update");
+                Status::internal("lagged")
+            }
+            ClientSendError::RateLimited(limit) => {
+                info!("client #{id}: {limit} rate limit exceeded");
+                Status::resource_exhausted(format!("{limit} rate limit exceeded"))
+            }
+            ClientSendError::Closed => {
+                error!("client #{id}: stream closed");
+                return;
+            }
+        };
+        tokio::spawn(async move {
+            let _ = stream_tx.send(Err(status)).await;
+        });
+    }
+
+    async fn replay_stored_slots(
+        replay_stored_slots_tx: &mpsc::Sender<ReplayStoredSlotsRequest>,
+        commitments: Vec<CommitmentLevel>,
+        from_slot: Slot,
+        position: Option<MessageLogPosition>,
+    ) -> Result<
+        (
+            ReplayStoredMessages,
+            broadcast::Receiver<BroadcastedMessage>,
+        ),
+        Status,
+    > {
+        let (tx, rx) = oneshot::channel();
+        if replay_stored_slots_tx
+            .send((commitments, from_slot, position, tx))
+            .await
+            .is_err()
+        {
+            return Err(Status::internal("failed to send from_slot request"));
+        }
+
+        match rx.await {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(ReplayStoredSlotsError::NotAvailable(first_available))) => {
+                Err(Status::out_of_range(match first_available {
+                    Some(slot) => format!(
+                        "broadcast from {from_slot} is not available, oldest available slot: {slot}"
+                    ),
+                    None => {
+                        format!("broadcast from {from_slot} is not available, no stored slots yet")
+                    }
+                }))
+            }
+            Ok(Err(ReplayStoredSlotsError::Busy)) => {
+                Err(Status::unavailable("replay log is busy, try again later"))
+            }
+            Err(_error) => Err(Status::internal("failed to get replay response")),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn client_loop_snapshot(
         id: usize,
         endpoint: &str,
         stream_tx: &mpsc::Sender<TonicResult<FilteredUpdate>>,
-        client_rx: &mut mpsc::UnboundedReceiver<Option<Filter>>,
-        snapshot_rx: crossbeam_channel::Receiver<Box<Message>>,
+        client_rx: &mut mpsc::UnboundedReceiver<Option<ClientFilterUpdate>>,
         is_alive: &mut bool,
         filter: &mut Filter,
+        config_filter_limits: &FilterLimits,
+        slow_consumer: &mut ClientSlowConsumer,
     ) {
         info!("client #{id}: going to receive snapshot data");
 
         // we start with default filter, for snapshot we need wait actual filter first
         while *is_alive {
             match client_rx.recv().await {
-                Some(Some(filter_new)) => {
+                Some(Some(ClientFilterUpdate::Filter {
+                    from_slot: _,
+                    filter: filter_new,
+                })) => {
                     if let Some(msg) = filter_new.get_pong_msg() {
                         if stream_tx.send(Ok(msg)).await.is_err() {
                             error!("client #{id}: stream closed");
@@ -900,6 +2561,26 @@ impl GrpcService {
                     info!("client #{id}: filter updated");
                     break;
                 }
+                Some(Some(ClientFilterUpdate::Delta(delta))) => {
+                    metrics::update_subscriptions(endpoint, Some(filter), None);
+                    let result = filter.apply_delta(&delta, config_filter_limits);
+                    metrics::update_subscriptions(endpoint, None, Some(filter));
+                    if let Err(error) = result {
+                        info!("client #{id}: failed to apply filter delta: {error}");
+                        let _ = stream_tx
+                            .send(Err(Status::invalid_argument(format!(
+                                "failed to update filter: {error}"
+                            ))))
+                            .await;
+                        *is_alive = false;
+                        continue;
+                    }
+                    info!("client #{id}: filter delta applied");
+                    break;
+                }
+                Some(Some(ClientFilterUpdate::Credits(grant))) => {
+                    slow_consumer.grant(grant);
+                }
                 Some(None) => {
                     *is_alive = false;
                 }
@@ -908,31 +2589,6 @@ impl GrpcService {
                 }
             };
         }
-
-        while *is_alive {
-            let message = match snapshot_rx.try_recv() {
-                Ok(message) => {
-                    metrics::message_queue_size_dec();
-                    message
-                }
-                Err(crossbeam_channel::TryRecvError::Empty) => {
-                    sleep(Duration::from_millis(1)).await;
-                    continue;
-                }
-                Err(crossbeam_channel::TryRecvError::Disconnected) => {
-                    info!("client #{id}: end of startup");
-                    break;
-                }
-            };
-
-            for message in filter.get_updates(&message, None) {
-                if stream_tx.send(Ok(message)).await.is_err() {
-                    error!("client #{id}: stream closed");
-                    *is_alive = false;
-                    break;
-                }
-            }
-        }
     }
 }
 
@@ -944,6 +2600,42 @@ impl Geyser for GrpcService {
         &self,
         mut request: Request<Streaming<SubscribeRequest>>,
     ) -> TonicResult<Response<Self::SubscribeStream>> {
+        ClientToken::check_method(&request, ConfigGrpcMethod::Subscribe)?;
+        let endpoint = request
+            .metadata()
+            .get("x-endpoint")
+            .and_then(|h| h.to_str().ok().map(|s| s.to_string()));
+        let connection = self.connections.connect(endpoint.as_deref())?;
+        let slow_consumer_policy = match request.metadata().get("x-slow-consumer-policy") {
+            Some(value) => value
+                .to_str()
+                .map_err(|_error| "invalid header value".to_owned())
+                .and_then(|value| value.parse())
+                .map_err(|error| {
+                    Status::invalid_argument(format!("invalid x-slow-consumer-policy: {error}"))
+                })?,
+            None => self.config_slow_consumer_policy,
+        };
+        let credits = match request.metadata().get("x-credits") {
+            Some(value) => Some(
+                value
+                    .to_str()
+                    .map_err(|_error| "invalid header value".to_owned())
+                    .and_then(|value| value.parse::<u64>().map_err(|error| error.to_string()))
+                    .map_err(|error| {
+                        Status::invalid_argument(format!("invalid x-credits: {error}"))
+                    })?,
+            ),
+            None => None,
+        };
+        let token = request.extensions().get::<Arc<ClientToken>>().cloned();
+        let token_connection = token.as_ref().map(ClientToken::connect).transpose()?;
+        let rate_limits = ClientRateLimits::new(self.config_rate_limit.as_ref(), token.as_deref());
+        let config_filter_limits = match &token {
+            Some(token) => Arc::clone(&token.filter_limits),
+            None => Arc::clone(&self.config_filter_limits),
+        };
+
         let id = self.subscribe_id.fetch_add(1, Ordering::Relaxed);
 
         let x_request_snapshot = request.metadata().contains_key("x-request-snapshot");
@@ -961,18 +2653,32 @@ impl Geyser for GrpcService {
         let notify_exit1 = Arc::new(Notify::new());
         let notify_exit2 = Arc::new(Notify::new());
 
+        // stream is closed once JWT is expired
+        let expires_in = token
+            .as_ref()
+            .and_then(|token| token.expires_at)
+            .map(|expires_at| Duration::from_secs(expires_at.saturating_sub(JwtValidator::now())));
+
         let ping_stream_tx = stream_tx.clone();
         let ping_client_tx = client_tx.clone();
         let ping_exit = Arc::clone(&notify_exit1);
         tokio::spawn(async move {
             let exit = ping_exit.notified();
             tokio::pin!(exit);
+            let expired = sleep(expires_in.unwrap_or_default());
+            tokio::pin!(expired);
 
             loop {
                 tokio::select! {
                     _ = &mut exit => {
                         break;
                     }
+                    _ = &mut expired, if expires_in.is_some() => {
+                        info!("client #{id}: token expired");
+                        let _ = ping_stream_tx.send(Err(Status::unauthenticated("token expired"))).await;
+                        let _ = ping_client_tx.send(None);
+                        break;
+                    }
                     _ = sleep(Duration::from_secs(10)) => {
                         let msg = FilteredUpdate::new_empty(FilteredUpdateOneof::ping());
                         match ping_stream_tx.try_send(Ok(msg)) {
@@ -988,14 +2694,11 @@ impl Geyser for GrpcService {
             }
         });
 
-        let endpoint = request
-            .metadata()
-            .get("x-endpoint")
-            .and_then(|h| h.to_str().ok().map(|s| s.to_string()))
-            .unwrap_or_else(|| "".to_owned());
-
-        let config_filter_limits = Arc::clone(&self.config_filter_limits);
+        let incoming_filter_limits = Arc::clone(&config_filter_limits);
         let filter_names = Arc::clone(&self.filter_names);
+        let mint_decimals = self.mint_decimals.clone();
+        let replay_stored_slots_enabled = self.replay_stored_slots_tx.is_some();
+        let credits_enabled = credits.is_some();
         let incoming_stream_tx = stream_tx.clone();
         let incoming_client_tx = client_tx;
         let incoming_exit = Arc::clone(&notify_exit2);
@@ -1010,11 +2713,37 @@ impl Geyser for GrpcService {
                     }
                     message = request.get_mut().message() => match message {
                         Ok(Some(request)) => {
+                            if let Some(credits) = request.credits {
+                                let message = if credits_enabled {
+                                    Some(ClientFilterUpdate::Credits(credits.grant))
+                                } else {
+                                    let err = Err(Status::invalid_argument("credits are not enabled, `x-credits` header is required"));
+                                    if incoming_stream_tx.send(err).await.is_err() {
+                                        break;
+                                    }
+                                    None
+                                };
+                                if incoming_client_tx.send(message).is_err() {
+                                    break;
+                                }
+                                continue;
+                            }
+
+                            if let Some(delta) = request.filter_delta {
+                                if incoming_client_tx.send(Some(ClientFilterUpdate::Delta(Box::new(delta)))).is_err() {
+                                    break;
+                                }
+                                continue;
+                            }
+
                             let mut filter_names = filter_names.lock().await;
                             filter_names.try_clean();
 
-                            if let Err(error) = match Filter::new(&request, &config_filter_limits, &mut filter_names) {
-                                Ok(filter) => match incoming_client_tx.send(Some(filter)) {
+                            if let Err(error) = match Filter::new(&request, &incoming_filter_limits, &mut filter_names, &mint_decimals) {
+                                Ok(_filter) if request.from_slot.is_some() && !replay_stored_slots_enabled => {
+                                    Err("`from_slot` is not supported".to_owned())
+                                }
+                                Ok(filter) => match incoming_client_tx.send(Some(ClientFilterUpdate::Filter { from_slot: request.from_slot, filter })) {
                                     Ok(()) => Ok(()),
                                     Err(error) => Err(error.to_string()),
                                 },
@@ -1040,6 +2769,13 @@ impl Geyser for GrpcService {
             }
         });
 
+        let endpoint = endpoint.unwrap_or_default();
+        let slow_consumer = ClientSlowConsumer::new(
+            endpoint.clone(),
+            slow_consumer_policy,
+            self.config_slow_consumer_buffer_capacity,
+            credits,
+        );
         tokio::spawn(Self::client_loop(
             id,
             endpoint,
@@ -1047,8 +2783,15 @@ impl Geyser for GrpcService {
             client_rx,
             snapshot_rx,
             self.broadcast_tx.subscribe(),
+            self.replay_stored_slots_tx.clone(),
             self.debug_clients_tx.clone(),
+            config_filter_limits,
+            self.mint_decimals.clone(),
+            rate_limits,
+            slow_consumer,
             move || {
+                drop(token_connection);
+                drop(connection);
                 notify_exit1.notify_one();
                 notify_exit2.notify_one();
             },
@@ -1058,6 +2801,7 @@ impl Geyser for GrpcService {
     }
 
     async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PongResponse>, Status> {
+        ClientToken::check_method(&request, ConfigGrpcMethod::Ping)?;
         let count = request.get_ref().count;
         let response = PongResponse { count };
         Ok(Response::new(response))
@@ -1067,6 +2811,7 @@ impl Geyser for GrpcService {
         &self,
         request: Request<GetLatestBlockhashRequest>,
     ) -> Result<Response<GetLatestBlockhashResponse>, Status> {
+        ClientToken::check_method(&request, ConfigGrpcMethod::GetLatestBlockhash)?;
         if let Some(blocks_meta) = &self.blocks_meta {
             blocks_meta
                 .get_block(
@@ -1090,6 +2835,7 @@ impl Geyser for GrpcService {
         &self,
         request: Request<GetBlockHeightRequest>,
     ) -> Result<Response<GetBlockHeightResponse>, Status> {
+        ClientToken::check_method(&request, ConfigGrpcMethod::GetBlockHeight)?;
         if let Some(blocks_meta) = &self.blocks_meta {
             blocks_meta
                 .get_block(
@@ -1110,6 +2856,7 @@ impl Geyser for GrpcService {
         &self,
         request: Request<GetSlotRequest>,
     ) -> Result<Response<GetSlotResponse>, Status> {
+        ClientToken::check_method(&request, ConfigGrpcMethod::GetSlot)?;
         if let Some(blocks_meta) = &self.blocks_meta {
             blocks_meta
                 .get_block(
@@ -1126,6 +2873,7 @@ impl Geyser for GrpcService {
         &self,
         request: Request<IsBlockhashValidRequest>,
     ) -> Result<Response<IsBlockhashValidResponse>, Status> {
+        ClientToken::check_method(&request, ConfigGrpcMethod::IsBlockhashValid)?;
         if let Some(blocks_meta) = &self.blocks_meta {
             let req = request.get_ref();
             blocks_meta
@@ -1138,10 +2886,720 @@ impl Geyser for GrpcService {
 
     async fn get_version(
         &self,
-        _request: Request<GetVersionRequest>,
+        request: Request<GetVersionRequest>,
     ) -> Result<Response<GetVersionResponse>, Status> {
+        ClientToken::check_method(&request, ConfigGrpcMethod::GetVersion)?;
         Ok(Response::new(GetVersionResponse {
             version: serde_json::to_string(&GrpcVersionInfo::default()).unwrap(),
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            ClientConnections, ClientFilterUpdate, ClientSendError, ClientSlotsRollback,
+            ClientSlowConsumer, ClientToken, ClientTransactionsCommitment, GrpcService,
+            ReplayLogCommitments, ReplayStoredSlots,
+        },
+        crate::config::{ConfigGrpcSlowConsumerPolicy, ConfigGrpcToken},
+        solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+        std::{
+            collections::{HashMap, HashSet},
+            sync::{atomic::Ordering, Arc},
+            time::Duration,
+        },
+        tokio::sync::mpsc,
+        tonic::{Code, Result as TonicResult},
+        yellowstone_grpc_proto::{
+            plugin::{
+                filter::{
+                    limits::FilterLimits,
+                    message::{FilteredUpdate, FilteredUpdateOneof},
+                    name::FilterNames,
+                    Filter, FilterMintDecimals,
+                },
+                message::{
+                    CommitmentLevel, Message, MessageAccount, MessageAccountInfo,
+                    MessageEncodedCache, MessageSlot, MessageSlotRollback, MessageTransaction,
+                    MessageTransactionInfo,
+                },
+            },
+            prelude::{SlotRollbackReason, SubscribeRequest, SubscribeRequestFilterTransactions},
+        },
+    };
+
+    fn create_account(slot: Slot) -> Message {
+        Message::Account(MessageAccount {
+            account: Arc::new(MessageAccountInfo {
+                pubkey: Pubkey::new_unique(),
+                lamports: slot * 1_000,
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+                data: vec![slot as u8; 64],
+                write_version: slot,
+                txn_signature: None,
+                encoded: MessageEncodedCache::default(),
+            }),
+            slot,
+            is_startup: false,
+        })
+    }
+
+    fn create_transaction(slot: Slot) -> Message {
+        Message::Transaction(MessageTransaction {
+            transaction: Arc::new(MessageTransactionInfo {
+                signature: Signature::new_unique(),
+                is_vote: false,
+                transaction: Default::default(),
+                meta: Default::default(),
+                index: 0,
+                account_keys: HashSet::new(),
+                signers: vec![],
+                encoded: MessageEncodedCache::default(),
+            }),
+            slot,
+        })
+    }
+
+    const fn create_slot(slot: Slot, status: CommitmentLevel) -> Message {
+        Message::Slot(MessageSlot {
+            slot,
+            parent: slot.checked_sub(1),
+            status,
+            dead_error: None,
+        })
+    }
+
+    fn create_batch(slot: Slot, status: CommitmentLevel) -> Arc<Vec<Message>> {
+        Arc::new(vec![create_account(slot), create_slot(slot, status)])
+    }
+
+    fn create_update(message: &Message) -> FilteredUpdate {
+        FilteredUpdate::new_empty(match message {
+            Message::Account(msg) => FilteredUpdateOneof::account(msg, Default::default()),
+            Message::Slot(msg) => FilteredUpdateOneof::slot(msg.clone()),
+            _ => unreachable!("unexpected message"),
+        })
+    }
+
+    fn recv_updates(rx: &mut mpsc::Receiver<TonicResult<FilteredUpdate>>) -> Vec<String> {
+        let mut updates = vec![];
+        while let Ok(update) = rx.try_recv() {
+            updates.push(match update.expect("update").message {
+                FilteredUpdateOneof::Account(msg) => format!("account {}", msg.slot),
+                FilteredUpdateOneof::Slot(msg) => format!("slot {}", msg.slot),
+                FilteredUpdateOneof::Lagged(msg) => format!(
+                    "lagged {} {} {} {}",
+                    msg.skipped_messages, msg.skipped_batches, msg.from_slot, msg.to_slot
+                ),
+                message => panic!("unexpected update: {message:?}"),
+            });
+        }
+        updates
+    }
+
+    // same as `pending_stream_tx.reserve()` branch of `client_loop`
+    fn recv_pending(
+        slow_consumer: &mut ClientSlowConsumer,
+        tx: &mpsc::Sender<TonicResult<FilteredUpdate>>,
+        rx: &mut mpsc::Receiver<TonicResult<FilteredUpdate>>,
+    ) -> Vec<String> {
+        let mut updates = recv_updates(rx);
+        while slow_consumer.has_pending() {
+            if let Some(message) = slow_consumer.pop_pending() {
+                tx.try_send(message).unwrap();
+            }
+            updates.extend(recv_updates(rx));
+        }
+        updates
+    }
+
+    fn replay_slots(
+        stored: &ReplayStoredSlots,
+        commitment: CommitmentLevel,
+        from_slot: Slot,
+    ) -> Result<Vec<Slot>, Option<Slot>> {
+        stored.replay(&[commitment], from_slot).map(|batches| {
+            batches
+                .iter()
+                .flat_map(|(_commitment, messages)| messages.iter().map(Message::get_slot))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_replay_stored_slots_push() {
+        let mut stored = ReplayStoredSlots::new(100, usize::MAX);
+        for slot in 10..=12 {
+            stored.push(
+                CommitmentLevel::Processed,
+                &create_batch(slot, CommitmentLevel::Processed),
+            );
+        }
+        stored.push(
+            CommitmentLevel::Confirmed,
+            &create_batch(10, CommitmentLevel::Confirmed),
+        );
+
+        assert_eq!(stored.first_available, Some(10));
+        assert_eq!(
+            replay_slots(&stored, CommitmentLevel::Processed, 11),
+            Ok(vec![11, 11, 12, 12])
+        );
+        assert_eq!(
+            replay_slots(&stored, CommitmentLevel::Confirmed, 10),
+            Ok(vec![10, 10])
+        );
+        assert_eq!(
+            replay_slots(&stored, CommitmentLevel::Finalized, 10),
+            Ok(vec![])
+        );
+
+        // batches of different commitments are replayed in order of broadcast
+        let commitments = stored
+            .replay(
+                &[CommitmentLevel::Processed, CommitmentLevel::Confirmed],
+                10,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(commitment, _messages)| commitment)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            commitments,
+            [
+                CommitmentLevel::Processed,
+                CommitmentLevel::Processed,
+                CommitmentLevel::Processed,
+                CommitmentLevel::Confirmed
+            ]
+        );
+
+        // confirmed batch shares messages with processed and not counted in size
+        let size = stored.size;
+        stored.push(
+            CommitmentLevel::Confirmed,
+            &create_batch(11, CommitmentLevel::Confirmed),
+        );
+        assert_eq!(stored.size, size);
+    }
+
+    #[test]
+    fn test_replay_transactions_commitment() {
+        let mut transactions = HashMap::new();
+        transactions.insert(
+            "transactions".to_owned(),
+            SubscribeRequestFilterTransactions::default(),
+        );
+        let config = SubscribeRequest {
+            transactions,
+            transactions_commitment_updates: Some(true),
+            ..Default::default()
+        };
+        let mut names = FilterNames::new(64, 1024, Duration::from_secs(1));
+        let mint_decimals = FilterMintDecimals::new(0);
+        let filter = Filter::new(
+            &config,
+            &FilterLimits::default(),
+            &mut names,
+            &mint_decimals,
+        )
+        .unwrap();
+
+        // slot statuses are stored with processed messages
+        let mut stored = ReplayStoredSlots::new(100, usize::MAX);
+        for messages in [
+            vec![create_transaction(9)],
+            vec![
+                create_transaction(10),
+                create_slot(10, CommitmentLevel::Processed),
+            ],
+            vec![create_slot(10, CommitmentLevel::Confirmed)],
+            vec![create_slot(10, CommitmentLevel::Finalized)],
+        ] {
+            stored.push(CommitmentLevel::Processed, &Arc::new(messages));
+        }
+
+        let mut transactions_commitment = ClientTransactionsCommitment::default();
+        let mut slots_rollback = ClientSlotsRollback::default();
+        let mut updates = vec![];
+        for (commitment, messages) in stored.replay(&[CommitmentLevel::Processed], 9).unwrap() {
+            for message in messages.iter() {
+                for update in GrpcService::client_get_updates(
+                    &filter,
+                    &mut transactions_commitment,
+                    &mut slots_rollback,
+                    message,
+                    Some(commitment),
+                ) {
+                    transactions_commitment.track(&update);
+                    updates.push(match update.message {
+                        FilteredUpdateOneof::Transaction(msg) => {
+                            format!("transaction {}", msg.slot)
+                        }
+                        FilteredUpdateOneof::TransactionCommitment(msg) => {
+                            format!("commitment {} {:?}", msg.slot, msg.status())
+                        }
+                        message => panic!("unexpected update: {message:?}"),
+                    });
+                }
+            }
+        }
+        assert_eq!(
+            updates,
+            [
+                "transaction 9",
+                "transaction 10",
+                "commitment 10 Confirmed",
+                "commitment 10 Finalized"
+            ]
+        );
+        // not finalized slot 9 is dropped without update, it's not on the main fork
+        assert!(transactions_commitment.slots.is_empty());
+    }
+
+    #[test]
+    fn test_replay_stored_slots_evict_slots() {
+        let mut stored = ReplayStoredSlots::new(3, usize::MAX);
+        for slot in 10..=15 {
+            stored.push(
+                CommitmentLevel::Processed,
+                &create_batch(slot, CommitmentLevel::Processed),
+            );
+        }
+
+        assert_eq!(stored.first_available, Some(13));
+        assert_eq!(stored.batches.len(), 3);
+        assert_eq!(
+            replay_slots(&stored, CommitmentLevel::Processed, 13),
+            Ok(vec![13, 13, 14, 14, 15, 15])
+        );
+    }
+
+    #[test]
+    fn test_replay_stored_slots_evict_size() {
+        let batch_size = create_batch(0, CommitmentLevel::Processed)
+            .iter()
+            .map(ReplayStoredSlots::get_message_size)
+            .sum::<usize>();
+
+        let mut stored = ReplayStoredSlots::new(100, batch_size * 2);
+        for slot in 10..=14 {
+            stored.push(
+                CommitmentLevel::Processed,
+                &create_batch(slot, CommitmentLevel::Processed),
+            );
+            assert!(stored.size <= batch_size * 2);
+        }
+
+        assert_eq!(stored.first_available, Some(13));
+        assert_eq!(stored.size, batch_size * 2);
+        assert_eq!(
+            replay_slots(&stored, CommitmentLevel::Processed, 13),
+            Ok(vec![13, 13, 14, 14])
+        );
+    }
+
+    #[test]
+    fn test_replay_stored_slots_out_of_range() {
+        let mut stored = ReplayStoredSlots::new(2, usize::MAX);
+        assert_eq!(
+            replay_slots(&stored, CommitmentLevel::Processed, 10),
+            Err(None)
+        );
+
+        for slot in 10..=12 {
+            stored.push(
+                CommitmentLevel::Processed,
+                &create_batch(slot, CommitmentLevel::Processed),
+            );
+        }
+        assert_eq!(
+            replay_slots(&stored, CommitmentLevel::Processed, 10),
+            Err(Some(11))
+        );
+        assert_eq!(
+            replay_slots(&stored, CommitmentLevel::Processed, 11),
+            Ok(vec![11, 11, 12, 12])
+        );
+    }
+
+    #[test]
+    fn test_replay_stored_slots_release_encoded() {
+        let accounts = [create_account(10), create_account(10), create_account(11)];
+        for message in accounts.iter() {
+            if let Message::Account(msg) = message {
+                msg.account.encoded.get_or_encode(|| vec![0; 100]);
+            }
+        }
+
+        let mut stored = ReplayStoredSlots::new(100, usize::MAX);
+        stored.push(
+            CommitmentLevel::Processed,
+            &Arc::new(vec![accounts[0].clone()]),
+        );
+        stored.push(
+            CommitmentLevel::Processed,
+            &Arc::new(vec![accounts[1].clone(), accounts[2].clone()]),
+        );
+        stored.push(
+            CommitmentLevel::Confirmed,
+            &Arc::new(vec![accounts[0].clone(), accounts[1].clone()]),
+        );
+        stored.release_encoded(10);
+
+        let batches = stored
+            .replay(
+                &[CommitmentLevel::Processed, CommitmentLevel::Confirmed],
+                10,
+            )
+            .unwrap();
+        let released = batches
+            .iter()
+            .flat_map(|(_commitment, messages)| messages.iter())
+            .filter_map(|message| match message {
+                Message::Account(msg) => Some(msg),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(released.len(), 5);
+        for msg in released.iter() {
+            assert_eq!(msg.account.encoded.get().is_some(), msg.slot == 11);
+        }
+        // copy is shared by batches of all commitments
+        assert!(Arc::ptr_eq(&released[0].account, &released[3].account));
+        assert!(Arc::ptr_eq(&released[1].account, &released[4].account));
+        assert_eq!(Message::Account(released[0].clone()), accounts[0]);
+    }
+
+    #[test]
+    fn test_replay_log_commitments() {
+        let mut commitments = ReplayLogCommitments::default();
+
+        let account = create_account(10);
+        assert_eq!(
+            commitments.push(account.clone()),
+            vec![(CommitmentLevel::Processed, vec![account.clone()])]
+        );
+
+        // statuses are sent with every commitment
+        let processed = create_slot(10, CommitmentLevel::Processed);
+        assert_eq!(
+            commitments.push(processed.clone()),
+            vec![
+                (CommitmentLevel::Processed, vec![processed.clone()]),
+                (CommitmentLevel::Confirmed, vec![processed.clone()]),
+                (CommitmentLevel::Finalized, vec![processed]),
+            ]
+        );
+
+        // messages of the slot are sent with confirmed status
+        let confirmed = create_slot(10, CommitmentLevel::Confirmed);
+        assert_eq!(
+            commitments.push(confirmed.clone()),
+            vec![
+                (CommitmentLevel::Processed, vec![confirmed.clone()]),
+                (CommitmentLevel::Confirmed, vec![account, confirmed.clone()]),
+                (CommitmentLevel::Finalized, vec![confirmed]),
+            ]
+        );
+
+        // rollbacks are sent only with processed
+        let rollback = Message::SlotRollback(MessageSlotRollback {
+            slot: 11,
+            reason: SlotRollbackReason::DeadSlot,
+        });
+        assert_eq!(
+            commitments.push(rollback.clone()),
+            vec![(CommitmentLevel::Processed, vec![rollback])]
+        );
+    }
+
+    #[test]
+    fn test_client_connections() {
+        let connections = Arc::new(ClientConnections::new(Some(2), Some(1)));
+
+        let connection_a = connections.connect(Some("a")).unwrap();
+        let error = connections.connect(Some("a")).unwrap_err();
+        assert_eq!(error.code(), Code::ResourceExhausted);
+        let connection_b = connections.connect(None).unwrap();
+        let error = connections.connect(Some("b")).unwrap_err();
+        assert_eq!(error.code(), Code::ResourceExhausted);
+        // rejected connections are released
+        assert_eq!(connections.total.load(Ordering::Relaxed), 2);
+
+        drop(connection_a);
+        let connection_a = connections.connect(Some("a")).unwrap();
+        drop((connection_a, connection_b));
+        assert_eq!(connections.total.load(Ordering::Relaxed), 0);
+        assert!(connections.endpoints.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_client_token_connections() {
+        let token = Arc::new(ClientToken::new(
+            ConfigGrpcToken {
+                name: "test".to_owned(),
+                filter_limits: None,
+                connections_max: Some(1),
+                methods: None,
+                rate_limit: None,
+            },
+            &Arc::new(FilterLimits::default()),
+            Some(10),
+            None,
+        ));
+
+        let connection = token.connect().unwrap();
+        let error = token.connect().unwrap_err();
+        assert_eq!(error.code(), Code::ResourceExhausted);
+        drop(connection);
+        let _connection = token.connect().unwrap();
+        assert_eq!(token.connections.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_client_coalesce_updates() {
+        let filter = |from_slot| ClientFilterUpdate::Filter {
+            from_slot: Some(from_slot),
+            filter: Filter::default(),
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        // updates before the latest filter are dropped, except credits
+        for update in [
+            filter(1),
+            ClientFilterUpdate::Delta(Box::default()),
+            filter(2),
+            ClientFilterUpdate::Delta(Box::default()),
+            ClientFilterUpdate::Credits(2),
+        ] {
+            tx.send(Some(update)).unwrap();
+        }
+        let updates = GrpcService::client_coalesce_updates(
+            Some(Some(ClientFilterUpdate::Credits(1))),
+            &mut rx,
+        )
+        .expect("updates");
+        let updates = updates
+            .iter()
+            .map(|update| match update {
+                ClientFilterUpdate::Filter { from_slot, .. } => format!("filter {from_slot:?}"),
+                ClientFilterUpdate::Delta(_) => "delta".to_owned(),
+                ClientFilterUpdate::Credits(grant) => format!("credits {grant}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            updates,
+            ["credits 1", "filter Some(2)", "delta", "credits 2"]
+        );
+
+        // close on token expiration is not replaced by filter sent after it
+        tx.send(None).unwrap();
+        tx.send(Some(filter(4))).unwrap();
+        assert!(GrpcService::client_coalesce_updates(Some(Some(filter(3))), &mut rx).is_none());
+        assert!(GrpcService::client_coalesce_updates(Some(None), &mut rx).is_none());
+        drop(tx);
+        assert!(GrpcService::client_coalesce_updates(Some(Some(filter(5))), &mut rx).is_none());
+    }
+
+    #[test]
+    fn test_slow_consumer_disconnect() {
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut slow_consumer = ClientSlowConsumer::new(
+            "test".to_owned(),
+            ConfigGrpcSlowConsumerPolicy::Disconnect,
+            2,
+            None,
+        );
+
+        for slot in 1..=2 {
+            let update = create_update(&create_account(slot));
+            assert!(slow_consumer.send(&tx, slot, update).is_ok());
+        }
+        let update = create_update(&create_account(3));
+        assert!(matches!(
+            slow_consumer.send(&tx, 3, update),
+            Err(ClientSendError::Lagged)
+        ));
+        assert!(matches!(
+            slow_consumer.lagged(1),
+            Err(ClientSendError::Lagged)
+        ));
+        assert_eq!(recv_updates(&mut rx), ["account 1", "account 2"]);
+    }
+
+    #[test]
+    fn test_slow_consumer_drop_oldest() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut slow_consumer = ClientSlowConsumer::new(
+            "test".to_owned(),
+            ConfigGrpcSlowConsumerPolicy::DropOldest,
+            2,
+            None,
+        );
+
+        // update of slot 2 is dropped from buffer
+        let mut pubkeys = vec![];
+        for slot in 1..=4 {
+            let message = create_account(slot);
+            if let Message::Account(msg) = &message {
+                pubkeys.push(msg.account.pubkey);
+            }
+            assert!(slow_consumer
+                .send(&tx, slot, create_update(&message))
+                .is_ok());
+        }
+        assert_eq!(
+            recv_pending(&mut slow_consumer, &tx, &mut rx),
+            ["account 1", "lagged 1 0 2 2", "account 3", "account 4"]
+        );
+        // accounts diff of dropped account is reset by client loop
+        assert_eq!(slow_consumer.dropped_accounts, [pubkeys[1]]);
+
+        // notice is delayed until the next batch of messages
+        slow_consumer.last_slot = 4;
+        assert!(slow_consumer.lagged(5).is_ok());
+        assert!(!slow_consumer.has_pending());
+        slow_consumer.received(7);
+        let update = create_update(&create_account(7));
+        assert!(slow_consumer.send(&tx, 7, update).is_ok());
+        assert_eq!(
+            recv_pending(&mut slow_consumer, &tx, &mut rx),
+            ["lagged 0 5 4 7", "account 7"]
+        );
+    }
+
+    #[test]
+    fn test_slow_consumer_slots_only() {
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut slow_consumer = ClientSlowConsumer::new(
+            "test".to_owned(),
+            ConfigGrpcSlowConsumerPolicy::SlotsOnly,
+            2,
+            None,
+        );
+
+        let mut pubkeys = vec![];
+        for slot in 1..=3 {
+            let message = create_account(slot);
+            if let Message::Account(msg) = &message {
+                pubkeys.push(msg.account.pubkey);
+            }
+            assert!(slow_consumer
+                .send(&tx, slot, create_update(&message))
+                .is_ok());
+        }
+        assert_eq!(recv_updates(&mut rx), ["account 1", "account 2"]);
+        assert_eq!(slow_consumer.dropped_accounts, [pubkeys[2]]);
+        // notice is sent once queue is drained
+        let update = create_update(&create_account(4));
+        assert!(slow_consumer.send(&tx, 4, update).is_ok());
+        assert_eq!(recv_updates(&mut rx), ["lagged 1 0 3 3", "account 4"]);
+
+        // only slots are sent until queue is drained
+        let update = create_update(&create_account(5));
+        assert!(slow_consumer.send(&tx, 5, update).is_ok());
+        slow_consumer.last_slot = 5;
+        assert!(slow_consumer.lagged(3).is_ok());
+        slow_consumer.received(6);
+        for message in [
+            create_account(6),
+            create_slot(6, CommitmentLevel::Processed),
+        ] {
+            assert!(slow_consumer.send(&tx, 6, create_update(&message)).is_ok());
+        }
+        assert_eq!(recv_updates(&mut rx), ["account 5", "slot 6"]);
+        let update = create_update(&create_account(7));
+        assert!(slow_consumer.send(&tx, 7, update).is_ok());
+        assert_eq!(recv_updates(&mut rx), ["lagged 1 3 5 6", "account 7"]);
+    }
+
+    #[test]
+    fn test_slots_rollback() {
+        let mut slots_rollback = ClientSlotsRollback::default();
+        for (message, commitment) in [
+            (create_account(5), CommitmentLevel::Processed),
+            (create_account(6), CommitmentLevel::Confirmed),
+            (
+                create_slot(7, CommitmentLevel::Processed),
+                CommitmentLevel::Processed,
+            ),
+            (create_account(8), CommitmentLevel::Processed),
+            (create_account(9), CommitmentLevel::Processed),
+        ] {
+            let mut update = create_update(&message);
+            update.commitment = Some(commitment);
+            slots_rollback.track(message.get_slot(), &update);
+        }
+        let rollback = |slot| {
+            Message::SlotRollback(MessageSlotRollback {
+                slot,
+                reason: SlotRollbackReason::AbandonedFork,
+            })
+        };
+
+        // only slots with delivered processed data, once
+        for slot in [6, 7] {
+            assert!(slots_rollback.get_update(&rollback(slot)).is_none());
+        }
+        let update = slots_rollback.get_update(&rollback(5)).expect("rollback");
+        assert!(matches!(update.message, FilteredUpdateOneof::SlotRollback(msg) if msg.slot == 5));
+        assert!(slots_rollback.get_update(&rollback(5)).is_none());
+
+        // slots up to finalized are not tracked
+        let finalized = create_slot(8, CommitmentLevel::Finalized);
+        assert!(slots_rollback.get_update(&finalized).is_none());
+        assert!(slots_rollback.get_update(&rollback(8)).is_none());
+        assert!(slots_rollback.get_update(&rollback(9)).is_some());
+    }
+
+    #[test]
+    fn test_slow_consumer_credits() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let mut slow_consumer = ClientSlowConsumer::new(
+            "test".to_owned(),
+            ConfigGrpcSlowConsumerPolicy::Disconnect,
+            2,
+            Some(1),
+        );
+
+        // updates are buffered once credits are exhausted
+        for slot in 1..=2 {
+            let update = create_update(&create_account(slot));
+            assert!(slow_consumer.send(&tx, slot, update).is_ok());
+        }
+        assert!(!slow_consumer.has_pending());
+        assert!(!slow_consumer.is_ready());
+        assert_eq!(
+            recv_pending(&mut slow_consumer, &tx, &mut rx),
+            ["account 1"]
+        );
+
+        // buffered updates are sent after top-up
+        slow_consumer.grant(2);
+        assert_eq!(
+            recv_pending(&mut slow_consumer, &tx, &mut rx),
+            ["account 2"]
+        );
+        assert!(slow_consumer.is_ready());
+        let update = create_update(&create_account(3));
+        assert!(slow_consumer.send(&tx, 3, update).is_ok());
+        assert!(!slow_consumer.is_ready());
+        assert_eq!(recv_updates(&mut rx), ["account 3"]);
+
+        // policy is applied once buffer is full
+        for slot in 4..=5 {
+            let update = create_update(&create_account(slot));
+            assert!(slow_consumer.send(&tx, slot, update).is_ok());
+        }
+        let update = create_update(&create_account(6));
+        assert!(matches!(
+            slow_consumer.send(&tx, 6, update),
+            Err(ClientSendError::Lagged)
+        ));
+    }
+}
@@ -0,0 +1,431 @@
+use {
+    crate::config::{ConfigGrpcJwt, ConfigGrpcJwtKey},
+    base64::{
+        engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64_URL},
+        Engine,
+    },
+    ring::{hmac, signature},
+    serde::{de::DeserializeOwned, Deserialize},
+    std::{
+        collections::{HashMap, HashSet},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    yellowstone_grpc_proto::plugin::filter::limits::FilterLimits,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum JwtError {
+    #[error("invalid key: {0}")]
+    InvalidKey(&'static str),
+    #[error("invalid token format")]
+    InvalidFormat,
+    #[error("unsupported algorithm {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("token expired")]
+    Expired,
+    #[error("token is not valid yet")]
+    NotYetValid,
+    #[error("unknown limits profile {0}")]
+    UnknownProfile(String),
+}
+
+pub type JwtResult<T> = Result<T, JwtError>;
+
+#[derive(Debug, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtClaims {
+    #[serde(default)]
+    pub sub: Option<String>,
+    #[serde(default)]
+    pub jti: Option<String>,
+    pub exp: u64,
+    #[serde(default)]
+    pub nbf: Option<u64>,
+    /// Allowed subscription kinds, all kinds are allowed if not set
+    #[serde(default)]
+    pub kinds: Option<HashSet<JwtSubscriptionKind>>,
+    /// Name of filter limits profile from the config
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JwtSubscriptionKind {
+    Accounts,
+    Slots,
+    Transactions,
+    TransactionsStatus,
+    Blocks,
+    BlocksMeta,
+    Entry,
+}
+
+#[derive(Debug)]
+enum JwtKey {
+    Hs256 {
+        kid: Option<String>,
+        key: hmac::Key,
+    },
+    EdDsa {
+        kid: Option<String>,
+        public_key: Vec<u8>,
+    },
+}
+
+impl JwtKey {
+    fn new(config: &ConfigGrpcJwtKey) -> JwtResult<Self> {
+        Ok(match config {
+            ConfigGrpcJwtKey::Hs256 { kid, secret } => Self::Hs256 {
+                kid: kid.clone(),
+                key: hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()),
+            },
+            ConfigGrpcJwtKey::EdDsa { kid, public_key } => {
+                let public_key = BASE64
+                    .decode(public_key)
+                    .map_err(|_| JwtError::InvalidKey("invalid base64 public key"))?;
+                if public_key.len() != 32 {
+                    return Err(JwtError::InvalidKey(
+                        "Ed25519 public key should be 32 bytes",
+                    ));
+                }
+                Self::EdDsa {
+                    kid: kid.clone(),
+                    public_key,
+                }
+            }
+        })
+    }
+
+    fn is_match(&self, header: &JwtHeader) -> bool {
+        let (alg, kid) = match self {
+            Self::Hs256 { kid, .. } => ("HS256", kid),
+            Self::EdDsa { kid, .. } => ("EdDSA", kid),
+        };
+        alg == header.alg
+            && match (kid, &header.kid) {
+                (Some(kid), Some(header_kid)) => kid == header_kid,
+                _ => true,
+            }
+    }
+
+    fn verify(&self, message: &[u8], tag: &[u8]) -> bool {
+        match self {
+            Self::Hs256 { key, .. } => hmac::verify(key, message, tag).is_ok(),
+            Self::EdDsa { public_key, .. } => {
+                signature::UnparsedPublicKey::new(&signature::ED25519, public_key)
+                    .verify(message, tag)
+                    .is_ok()
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct JwtValidator {
+    keys: Vec<JwtKey>,
+    leeway: u64,
+    profiles: HashMap<String, FilterLimits>,
+}
+
+impl JwtValidator {
+    pub fn new(config: &ConfigGrpcJwt) -> JwtResult<Self> {
+        Ok(Self {
+            keys: config
+                .keys
+                .iter()
+                .map(JwtKey::new)
+                .collect::<Result<_, _>>()?,
+            leeway: config.leeway.as_secs(),
+            profiles: config.profiles.clone(),
+        })
+    }
+
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+
+    /// Verifies signature and time claims, `now` is unix timestamp in seconds
+    pub fn validate(&self, token: &str, now: u64) -> JwtResult<JwtClaims> {
+        let mut parts = token.splitn(3, '.');
+        let (Some(header), Some(payload), Some(tag)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(JwtError::InvalidFormat);
+        };
+        let message = &token[..header.len() + 1 + payload.len()];
+        let header: JwtHeader = Self::decode(header)?;
+        let tag = BASE64_URL
+            .decode(tag)
+            .map_err(|_| JwtError::InvalidFormat)?;
+
+        let mut keys = self
+            .keys
+            .iter()
+            .filter(|key| key.is_match(&header))
+            .peekable();
+        if keys.peek().is_none() {
+            return Err(JwtError::UnsupportedAlgorithm(header.alg));
+        }
+        if !keys.any(|key| key.verify(message.as_bytes(), &tag)) {
+            return Err(JwtError::InvalidSignature);
+        }
+
+        let claims: JwtClaims = Self::decode(payload)?;
+        if claims.exp.saturating_add(self.leeway) < now {
+            return Err(JwtError::Expired);
+        }
+        if matches!(claims.nbf, Some(nbf) if nbf > now.saturating_add(self.leeway)) {
+            return Err(JwtError::NotYetValid);
+        }
+        Ok(claims)
+    }
+
+    /// Unix timestamp in seconds after which token is not valid anymore
+    pub const fn expires_at(&self, claims: &JwtClaims) -> u64 {
+        claims.exp.saturating_add(self.leeway)
+    }
+
+    /// Limits from claimed profile, filters of not allowed kinds are disabled
+    pub fn get_filter_limits(
+        &self,
+        claims: &JwtClaims,
+        default: &FilterLimits,
+    ) -> JwtResult<FilterLimits> {
+        let mut limits = match &claims.profile {
+            Some(profile) => self
+                .profiles
+                .get(profile)
+                .cloned()
+                .ok_or_else(|| JwtError::UnknownProfile(profile.clone()))?,
+            None => default.clone(),
+        };
+        if let Some(kinds) = &claims.kinds {
+            for (kind, max) in [
+                (JwtSubscriptionKind::Accounts, &mut limits.accounts.max),
+                (JwtSubscriptionKind::Slots, &mut limits.slots.max),
+                (
+                    JwtSubscriptionKind::Transactions,
+                    &mut limits.transactions.max,
+                ),
+                (
+                    JwtSubscriptionKind::TransactionsStatus,
+                    &mut limits.transactions_status.max,
+                ),
+                (JwtSubscriptionKind::Blocks, &mut limits.blocks.max),
+                (JwtSubscriptionKind::BlocksMeta, &mut limits.blocks_meta.max),
+                (JwtSubscriptionKind::Entry, &mut limits.entries.max),
+            ] {
+                if !kinds.contains(&kind) {
+                    *max = 0;
+                }
+            }
+        }
+        Ok(limits)
+    }
+
+    fn decode<T: DeserializeOwned>(part: &str) -> JwtResult<T> {
+        let data = BASE64_URL
+            .decode(part)
+            .map_err(|_| JwtError::InvalidFormat)?;
+        serde_json::from_slice(&data).map_err(|_| JwtError::InvalidFormat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{JwtError, JwtValidator, BASE64, BASE64_URL},
+        crate::config::{ConfigGrpcJwt, ConfigGrpcJwtKey},
+        base64::Engine,
+        ring::{
+            hmac,
+            signature::{Ed25519KeyPair, KeyPair},
+        },
+        serde_json::{json, Value},
+        std::{collections::HashMap, time::Duration},
+        yellowstone_grpc_proto::plugin::filter::limits::FilterLimits,
+    };
+
+    const NOW: u64 = 1_700_000_000;
+    const SECRET: &str = "secret";
+
+    fn create_validator(keys: Vec<ConfigGrpcJwtKey>) -> JwtValidator {
+        let mut profiles = HashMap::new();
+        let mut limits = FilterLimits::default();
+        limits.accounts.account_max = 10;
+        profiles.insert("small".to_owned(), limits);
+        JwtValidator::new(&ConfigGrpcJwt {
+            keys,
+            leeway: Duration::from_secs(60),
+            profiles,
+        })
+        .unwrap()
+    }
+
+    fn create_hs256_validator() -> JwtValidator {
+        create_validator(vec![ConfigGrpcJwtKey::Hs256 {
+            kid: None,
+            secret: SECRET.to_owned(),
+        }])
+    }
+
+    fn encode(header: &Value, claims: &Value) -> String {
+        format!(
+            "{}.{}",
+            BASE64_URL.encode(header.to_string()),
+            BASE64_URL.encode(claims.to_string())
+        )
+    }
+
+    fn sign_hs256(claims: &Value, secret: &str) -> String {
+        let message = encode(&json!({"alg": "HS256", "typ": "JWT"}), claims);
+        let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+        let tag = hmac::sign(&key, message.as_bytes());
+        format!("{message}.{}", BASE64_URL.encode(tag.as_ref()))
+    }
+
+    #[test]
+    fn test_jwt_hs256() {
+        let validator = create_hs256_validator();
+
+        let token = sign_hs256(&json!({"sub": "team-a", "exp": NOW + 10}), SECRET);
+        let claims = validator.validate(&token, NOW).unwrap();
+        assert_eq!(claims.sub.as_deref(), Some("team-a"));
+        assert!(claims.kinds.is_none());
+        assert!(claims.profile.is_none());
+
+        let token = sign_hs256(&json!({"exp": NOW + 10}), "other secret");
+        assert!(matches!(
+            validator.validate(&token, NOW),
+            Err(JwtError::InvalidSignature)
+        ));
+
+        assert!(matches!(
+            validator.validate("token", NOW),
+            Err(JwtError::InvalidFormat)
+        ));
+    }
+
+    #[test]
+    fn test_jwt_expired() {
+        let validator = create_hs256_validator();
+
+        // leeway is 60s
+        let token = sign_hs256(&json!({"exp": NOW - 30}), SECRET);
+        assert!(validator.validate(&token, NOW).is_ok());
+        let token = sign_hs256(&json!({"exp": NOW - 120}), SECRET);
+        assert!(matches!(
+            validator.validate(&token, NOW),
+            Err(JwtError::Expired)
+        ));
+
+        let token = sign_hs256(&json!({"exp": NOW + 600, "nbf": NOW + 300}), SECRET);
+        assert!(matches!(
+            validator.validate(&token, NOW),
+            Err(JwtError::NotYetValid)
+        ));
+        assert!(validator.validate(&token, NOW + 300).is_ok());
+
+        // `exp` is required
+        let token = sign_hs256(&json!({"sub": "team-a"}), SECRET);
+        assert!(matches!(
+            validator.validate(&token, NOW),
+            Err(JwtError::InvalidFormat)
+        ));
+    }
+
+    #[test]
+    fn test_jwt_tampered() {
+        let validator = create_hs256_validator();
+
+        let token = sign_hs256(&json!({"exp": NOW + 10, "profile": "small"}), SECRET);
+        let (message, tag) = token.rsplit_once('.').unwrap();
+        let (header, _claims) = message.split_once('.').unwrap();
+
+        // replaced claims with original signature
+        let claims = BASE64_URL.encode(json!({"exp": NOW + 10_000}).to_string());
+        let tampered = format!("{header}.{claims}.{tag}");
+        assert!(matches!(
+            validator.validate(&tampered, NOW),
+            Err(JwtError::InvalidSignature)
+        ));
+
+        // unsigned token
+        let unsigned = format!(
+            "{}.",
+            encode(&json!({"alg": "none"}), &json!({"exp": NOW + 10}))
+        );
+        assert!(matches!(
+            validator.validate(&unsigned, NOW),
+            Err(JwtError::UnsupportedAlgorithm(_))
+        ));
+    }
+
+    #[test]
+    fn test_jwt_eddsa() {
+        let keypair = Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap();
+        let validator = create_validator(vec![
+            ConfigGrpcJwtKey::Hs256 {
+                kid: None,
+                secret: SECRET.to_owned(),
+            },
+            ConfigGrpcJwtKey::EdDsa {
+                kid: Some("ed".to_owned()),
+                public_key: BASE64.encode(keypair.public_key().as_ref()),
+            },
+        ]);
+
+        let sign = |header: Value, claims: Value| {
+            let message = encode(&header, &claims);
+            let tag = keypair.sign(message.as_bytes());
+            format!("{message}.{}", BASE64_URL.encode(tag.as_ref()))
+        };
+
+        let claims = json!({"exp": NOW + 10, "kinds": ["accounts", "slots"]});
+        let token = sign(json!({"alg": "EdDSA", "kid": "ed"}), claims.clone());
+        let claims = validator.validate(&token, NOW).unwrap();
+
+        let limits = validator
+            .get_filter_limits(&claims, &FilterLimits::default())
+            .unwrap();
+        assert_eq!(limits.accounts.max, usize::MAX);
+        assert_eq!(limits.slots.max, usize::MAX);
+        assert_eq!(limits.transactions.max, 0);
+        assert_eq!(limits.blocks_meta.max, 0);
+
+        let token = sign(json!({"alg": "EdDSA", "kid": "other"}), json!({"exp": NOW}));
+        assert!(matches!(
+            validator.validate(&token, NOW),
+            Err(JwtError::UnsupportedAlgorithm(_))
+        ));
+    }
+
+    #[test]
+    fn test_jwt_profile() {
+        let validator = create_hs256_validator();
+        let default = FilterLimits::default();
+
+        let token = sign_hs256(&json!({"exp": NOW, "profile": "small"}), SECRET);
+        let claims = validator.validate(&token, NOW).unwrap();
+        let limits = validator.get_filter_limits(&claims, &default).unwrap();
+        assert_eq!(limits.accounts.account_max, 10);
+
+        let token = sign_hs256(&json!({"exp": NOW, "profile": "large"}), SECRET);
+        let claims = validator.validate(&token, NOW).unwrap();
+        assert!(matches!(
+            validator.get_filter_limits(&claims, &default),
+            Err(JwtError::UnknownProfile(_))
+        ));
+    }
+}
@@ -1,7 +1,10 @@
 pub mod config;
 pub mod grpc;
+pub mod jwt;
+pub mod message_log;
 pub mod metrics;
 pub mod plugin;
+pub mod rate_limit;
 pub mod version;
 
 pub fn get_thread_name() -> String {
@@ -0,0 +1,1013 @@
+use {
+    crate::config::ConfigGrpcReplayLog,
+    log::{error, warn},
+    solana_sdk::clock::Slot,
+    std::{
+        collections::VecDeque,
+        fs::{self, File, OpenOptions},
+        io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Take, Write},
+        mem,
+        path::{Path, PathBuf},
+        sync::Arc,
+    },
+    tokio::sync::mpsc,
+    yellowstone_grpc_proto::{
+        plugin::{
+            filter::{
+                message::{FilteredUpdate, FilteredUpdateBlock, FilteredUpdateOneof},
+                FilterAccountsDataSlice,
+            },
+            message::Message,
+        },
+        prelude::SubscribeUpdate,
+        prost::Message as _,
+    },
+};
+
+const SEGMENT_MAGIC: &[u8; 8] = b"YGRPLOG2";
+const SEGMENT_HEADER_SIZE: u64 = 16; // magic + first available slot
+const RECORD_HEADER_SIZE: u64 = 8; // body length + body crc32
+const RECORD_BODY_HEADER_SIZE: usize = 8; // slot
+const INDEX_ENTRY_SIZE: usize = 16; // slot + offset
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IndexEntry {
+    // max slot of all records in the log up to this record (inclusive)
+    slot: Slot,
+    offset: u64,
+}
+
+#[derive(Debug)]
+struct Segment {
+    id: u64,
+    // replay from this slot is complete if segment is the first one
+    first_available: Slot,
+    size: u64,
+    index: Vec<IndexEntry>,
+}
+
+/// Position in the log after the last record read by replay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageLogPosition {
+    segment_id: u64,
+    offset: u64,
+}
+
+impl Segment {
+    fn slot_max(&self) -> Option<Slot> {
+        self.index.last().map(|entry| entry.slot)
+    }
+}
+
+/// Append-only log of processed messages and slot statuses split into segments,
+/// confirmed and finalized messages are restored from them on replay.
+///
+/// Every segment has an index with offsets where max written slot is increased,
+/// so replay from any slot starts from the first record which can have such slot.
+#[derive(Debug)]
+pub struct MessageLog {
+    path: PathBuf,
+    segment_size_limit: u64,
+    stored_slots: u64,
+    stored_size_limit: u64,
+    segments: VecDeque<Segment>,
+    writer: Option<BufWriter<File>>, // writer of the last segment
+    next_id: u64,
+    slot_max: Option<Slot>,
+    size: u64,
+    // slots between stored and the first appended after open can be missed
+    restarted: bool,
+}
+
+impl MessageLog {
+    pub fn open(config: ConfigGrpcReplayLog) -> io::Result<Self> {
+        fs::create_dir_all(&config.path)?;
+
+        let mut ids = vec![];
+        for entry in fs::read_dir(&config.path)? {
+            let path = entry?.path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("log") => {
+                    if let Some(id) = path
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .and_then(|stem| stem.parse::<u64>().ok())
+                    {
+                        ids.push(id);
+                    }
+                }
+                Some("tmp") => fs::remove_file(&path)?,
+                _ => {}
+            }
+        }
+        ids.sort_unstable();
+
+        let mut log = Self {
+            path: config.path,
+            segment_size_limit: config.segment_size_limit,
+            stored_slots: config.stored_slots,
+            stored_size_limit: config.stored_size_limit,
+            segments: VecDeque::new(),
+            writer: None,
+            next_id: ids.last().map_or(0, |id| id + 1),
+            slot_max: None,
+            size: 0,
+            restarted: false,
+        };
+
+        let last_id = ids.last().copied();
+        for id in ids {
+            // last segment can be partially written, always verify it
+            let segment = match (Some(id) != last_id)
+                .then(|| log.load_segment(id))
+                .transpose()?
+                .flatten()
+            {
+                Some(segment) => segment,
+                None => {
+                    match log.scan_segment(id)? {
+                        Some((segment, truncated)) => {
+                            log.write_index(&segment)?;
+                            if truncated && Some(id) != last_id {
+                                // records after the truncated one are lost, earlier slots are not complete
+                                warn!("replay log: segment #{id} is truncated, remove previous segments");
+                                while let Some(segment) = log.segments.pop_front() {
+                                    log.remove_segment(&segment)?;
+                                }
+                                log.size = 0;
+                                log.remove_segment(&segment)?;
+                                continue;
+                            }
+                            segment
+                        }
+                        None => continue,
+                    }
+                }
+            };
+
+            if segment.index.is_empty() {
+                log.remove_segment(&segment)?;
+                continue;
+            }
+
+            log.slot_max = segment.slot_max().max(log.slot_max);
+            log.size += segment.size;
+            log.segments.push_back(segment);
+        }
+        log.remove_outdated()?;
+        log.restarted = !log.segments.is_empty();
+
+        Ok(log)
+    }
+
+    pub fn first_available(&self) -> Option<Slot> {
+        self.segments.front().map(|segment| segment.first_available)
+    }
+
+    pub fn append(&mut self, messages: &[Message]) -> io::Result<()> {
+        // blocks are reconstructed from stored messages on replay
+        for message in messages
+            .iter()
+            .filter(|msg| !matches!(msg, Message::Block(_)))
+        {
+            let slot = message.get_slot();
+            let body = Self::encode_record_body(slot, message)?;
+
+            if mem::take(&mut self.restarted) {
+                if let Some(slot_max) = self.slot_max.filter(|slot_max| slot > slot_max + 1) {
+                    // replay over missed slots would be silently incomplete
+                    warn!(
+                        "replay log: slots {}..{slot} are missed after restart, remove stored segments",
+                        slot_max + 1
+                    );
+                    self.clear()?;
+                }
+            }
+
+            let rotate = match (self.writer.is_some(), self.segments.back()) {
+                (true, Some(segment)) => segment.size >= self.segment_size_limit,
+                _ => true,
+            };
+            if rotate {
+                self.rotate(slot)?;
+            }
+
+            let (Some(writer), Some(segment)) = (&mut self.writer, self.segments.back_mut()) else {
+                unreachable!("active segment should be created");
+            };
+            writer.write_all(&(body.len() as u32).to_le_bytes())?;
+            writer.write_all(&crc32fast::hash(&body).to_le_bytes())?;
+            writer.write_all(&body)?;
+
+            let offset = segment.size;
+            let size = RECORD_HEADER_SIZE + body.len() as u64;
+            segment.size += size;
+            self.size += size;
+
+            let slot_max = self.slot_max.map_or(slot, |slot_max| slot_max.max(slot));
+            if segment.index.is_empty() || Some(slot_max) != self.slot_max {
+                segment.index.push(IndexEntry {
+                    slot: slot_max,
+                    offset,
+                });
+                self.slot_max = Some(slot_max);
+            }
+        }
+
+        self.remove_outdated()
+    }
+
+    pub fn replay(&mut self, from_slot: Slot) -> io::Result<Option<MessageLogReader>> {
+        match self.first_available() {
+            Some(first_available) if from_slot >= first_available => {}
+            _ => return Ok(None),
+        }
+
+        let mut segments = VecDeque::new();
+        for segment in self.segments.iter() {
+            let start = if segments.is_empty() {
+                let index = segment
+                    .index
+                    .partition_point(|entry| entry.slot < from_slot);
+                match segment.index.get(index) {
+                    Some(entry) => entry.offset,
+                    None => continue,
+                }
+            } else {
+                SEGMENT_HEADER_SIZE
+            };
+            segments.push_back((segment.id, start));
+        }
+
+        self.create_reader(segments, from_slot).map(Some)
+    }
+
+    /// Continue replay with records appended after the position,
+    /// returns `None` if records were removed
+    pub fn replay_after(
+        &mut self,
+        position: MessageLogPosition,
+    ) -> io::Result<Option<MessageLogReader>> {
+        let Some(index) = self
+            .segments
+            .iter()
+            .position(|segment| segment.id == position.segment_id)
+        else {
+            return Ok(None);
+        };
+
+        let segments = self
+            .segments
+            .iter()
+            .skip(index)
+            .map(|segment| {
+                let start = if segment.id == position.segment_id {
+                    position.offset
+                } else {
+                    SEGMENT_HEADER_SIZE
+                };
+                (segment.id, start)
+            })
+            .collect();
+        self.create_reader(segments, 0).map(Some)
+    }
+
+    fn create_reader(
+        &mut self,
+        segments: VecDeque<(u64, u64)>, // (id, start)
+        from_slot: Slot,
+    ) -> io::Result<MessageLogReader> {
+        if let Some(writer) = &mut self.writer {
+            writer.flush()?;
+        }
+
+        let last = self
+            .segments
+            .back()
+            .expect("replay requires stored segments");
+        let end = MessageLogPosition {
+            segment_id: last.id,
+            offset: last.size,
+        };
+
+        let mut size = 0;
+        let segments = segments
+            .into_iter()
+            .map(|(id, start)| {
+                let end = self
+                    .segments
+                    .iter()
+                    .find(|segment| segment.id == id)
+                    .map_or(start, |segment| segment.size);
+                size += end - start;
+                (self.segment_path(id), start, end)
+            })
+            .collect();
+
+        Ok(MessageLogReader {
+            segments,
+            end,
+            size,
+            from_slot,
+            reader: None,
+        })
+    }
+
+    fn rotate(&mut self, slot: Slot) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+            if let Some(segment) = self.segments.back() {
+                self.write_index(segment)?;
+            }
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let first_available = self.slot_max.map_or(slot, |slot_max| slot_max + 1);
+
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(self.segment_path(id))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(SEGMENT_MAGIC)?;
+        writer.write_all(&first_available.to_le_bytes())?;
+
+        self.segments.push_back(Segment {
+            id,
+            first_available,
+            size: SEGMENT_HEADER_SIZE,
+            index: vec![],
+        });
+        self.size += SEGMENT_HEADER_SIZE;
+        self.writer = Some(writer);
+
+        Ok(())
+    }
+
+    fn remove_outdated(&mut self) -> io::Result<()> {
+        // the last segment is never removed, it keeps first available slot
+        while self.segments.len() > 1 {
+            let outdated = match (
+                self.segments.front().and_then(Segment::slot_max),
+                self.slot_max,
+            ) {
+                (Some(segment_slot_max), Some(slot_max)) => {
+                    segment_slot_max + self.stored_slots <= slot_max
+                }
+                _ => true,
+            };
+            if !outdated && self.size <= self.stored_size_limit {
+                break;
+            }
+
+            if let Some(segment) = self.segments.pop_front() {
+                self.size -= segment.size;
+                self.remove_segment(&segment)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove all stored segments, used when some messages were not written
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.writer = None;
+        while let Some(segment) = self.segments.pop_front() {
+            self.remove_segment(&segment)?;
+        }
+        self.slot_max = None;
+        self.size = 0;
+        Ok(())
+    }
+
+    fn segment_path(&self, id: u64) -> PathBuf {
+        self.path.join(format!("{id:020}.log"))
+    }
+
+    fn index_path(&self, id: u64) -> PathBuf {
+        self.path.join(format!("{id:020}.index"))
+    }
+
+    fn remove_segment(&self, segment: &Segment) -> io::Result<()> {
+        for path in [self.segment_path(segment.id), self.index_path(segment.id)] {
+            match fs::remove_file(path) {
+                Ok(()) => {}
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    fn write_index(&self, segment: &Segment) -> io::Result<()> {
+        let mut data = Vec::with_capacity(segment.index.len() * INDEX_ENTRY_SIZE);
+        for entry in segment.index.iter() {
+            data.extend_from_slice(&entry.slot.to_le_bytes());
+            data.extend_from_slice(&entry.offset.to_le_bytes());
+        }
+
+        // index is replaced atomically, partially written index would be removed on open
+        let path = self.index_path(segment.id);
+        let path_tmp = path.with_extension("index.tmp");
+        fs::write(&path_tmp, data)?;
+        fs::rename(path_tmp, path)
+    }
+
+    fn read_header(path: &Path, file: &mut impl Read) -> io::Result<Option<Slot>> {
+        let mut header = [0; SEGMENT_HEADER_SIZE as usize];
+        match file.read_exact(&mut header) {
+            Ok(()) if &header[0..8] == SEGMENT_MAGIC => Ok(Some(Slot::from_le_bytes(
+                header[8..16].try_into().expect("valid slice size"),
+            ))),
+            Ok(()) => {
+                warn!("replay log: invalid header in {path:?}");
+                Ok(None)
+            }
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                warn!("replay log: truncated header in {path:?}");
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // Segment with index file written on rotation
+    fn load_segment(&self, id: u64) -> io::Result<Option<Segment>> {
+        let path = self.segment_path(id);
+        let mut file = File::open(&path)?;
+        let size = file.metadata()?.len();
+        let Some(first_available) = Self::read_header(&path, &mut file)? else {
+            return Ok(None);
+        };
+
+        let data = match fs::read(self.index_path(id)) {
+            Ok(data) => data,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        if data.len() % INDEX_ENTRY_SIZE != 0 {
+            return Ok(None);
+        }
+
+        let index = data
+            .chunks_exact(INDEX_ENTRY_SIZE)
+            .map(|chunk| IndexEntry {
+                slot: Slot::from_le_bytes(chunk[0..8].try_into().expect("valid slice size")),
+                offset: u64::from_le_bytes(chunk[8..16].try_into().expect("valid slice size")),
+            })
+            .collect::<Vec<_>>();
+        if index.iter().any(|entry| entry.offset >= size) {
+            return Ok(None);
+        }
+
+        Ok(Some(Segment {
+            id,
+            first_available,
+            size,
+            index,
+        }))
+    }
+
+    // Read all records, rebuild index and truncate partially written or corrupted tail
+    fn scan_segment(&self, id: u64) -> io::Result<Option<(Segment, bool)>> {
+        let path = self.segment_path(id);
+        let file = OpenOptions::new().read(true).write(true).open(&path)?;
+        let file_size = file.metadata()?.len();
+        let mut reader = BufReader::new(&file);
+
+        let Some(first_available) = Self::read_header(&path, &mut reader)? else {
+            drop(reader);
+            fs::remove_file(&path)?;
+            return Ok(None);
+        };
+
+        let mut size = SEGMENT_HEADER_SIZE;
+        let mut index = vec![];
+        let mut slot_max = self.slot_max;
+        while let Some(body) = read_record(&mut reader, file_size - size)? {
+            let (slot, _payload) = parse_record_body(&body);
+            let slot_max_new = slot_max.map_or(slot, |slot_max| slot_max.max(slot));
+            if index.is_empty() || Some(slot_max_new) != slot_max {
+                index.push(IndexEntry {
+                    slot: slot_max_new,
+                    offset: size,
+                });
+                slot_max = Some(slot_max_new);
+            }
+            size += RECORD_HEADER_SIZE + body.len() as u64;
+        }
+
+        let truncated = size < file_size;
+        if truncated {
+            warn!(
+                "replay log: truncate {path:?} from {file_size} to {size} bytes, {} bytes lost",
+                file_size - size
+            );
+            drop(reader);
+            file.set_len(size)?;
+            file.sync_all()?;
+        }
+
+        Ok(Some((
+            Segment {
+                id,
+                first_available,
+                size,
+                index,
+            },
+            truncated,
+        )))
+    }
+
+    fn encode_record_body(slot: Slot, message: &Message) -> io::Result<Vec<u8>> {
+        let message = match message {
+            Message::Slot(msg) => FilteredUpdateOneof::slot(msg.clone()),
+            Message::SlotRollback(msg) => FilteredUpdateOneof::slot_rollback(msg),
+            Message::Account(msg) => {
+                FilteredUpdateOneof::account(msg, FilterAccountsDataSlice::default())
+            }
+            Message::Transaction(msg) => FilteredUpdateOneof::transaction(msg),
+            Message::Entry(msg) => FilteredUpdateOneof::entry(Arc::clone(msg)),
+            Message::BlockMeta(msg) => FilteredUpdateOneof::block_meta(Arc::clone(msg)),
+            Message::Block(msg) => FilteredUpdateOneof::block(Box::new(FilteredUpdateBlock {
+                meta: Arc::clone(&msg.meta),
+                transactions: msg.transactions.clone(),
+                updated_account_count: msg.updated_account_count,
+                accounts: msg.accounts.clone(),
+                accounts_data_slice: FilterAccountsDataSlice::default(),
+                entries: msg.entries.clone(),
+            })),
+        };
+        let message = FilteredUpdate::new_empty(message);
+
+        let mut body = Vec::with_capacity(RECORD_BODY_HEADER_SIZE + message.encoded_len());
+        body.extend_from_slice(&slot.to_le_bytes());
+        message.encode(&mut body).map_err(io::Error::other)?;
+        Ok(body)
+    }
+}
+
+/// Reader of processed records starting from `from_slot`,
+/// positions are fixed on creation so records appended later are not read.
+#[derive(Debug)]
+pub struct MessageLogReader {
+    segments: VecDeque<(PathBuf, u64, u64)>, // (path, start, end)
+    end: MessageLogPosition,
+    size: u64,
+    from_slot: Slot,
+    reader: Option<Take<BufReader<File>>>,
+}
+
+impl MessageLogReader {
+    /// Position to continue replay with `MessageLog::replay_after`
+    pub const fn end(&self) -> MessageLogPosition {
+        self.end
+    }
+
+    /// Total size of records to read
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn read_next(&mut self) -> io::Result<Option<Message>> {
+        loop {
+            // empty ranges are skipped
+            while self
+                .reader
+                .as_ref()
+                .map_or(true, |reader| reader.limit() == 0)
+            {
+                let Some((path, start, end)) = self.segments.pop_front() else {
+                    return Ok(None);
+                };
+                let mut file = File::open(path)?;
+                file.seek(SeekFrom::Start(start))?;
+                self.reader = Some(BufReader::new(file).take(end - start));
+            }
+            let reader = self.reader.as_mut().expect("reader should be opened");
+
+            let limit = reader.limit();
+            let Some(body) = read_record(reader, limit)? else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "corrupted record in replay log",
+                ));
+            };
+
+            let (slot, payload) = parse_record_body(&body);
+            if slot >= self.from_slot {
+                return decode_message(payload).map(Some);
+            }
+        }
+    }
+
+    /// Read messages in the blocking thread
+    pub fn spawn(mut self, capacity: usize) -> mpsc::Receiver<io::Result<Message>> {
+        let (tx, rx) = mpsc::channel(capacity);
+        tokio::task::spawn_blocking(move || loop {
+            let message = match self.read_next() {
+                Ok(Some(message)) => Ok(message),
+                Ok(None) => break,
+                Err(error) => {
+                    error!("replay log: failed to read: {error}");
+                    Err(error)
+                }
+            };
+            let is_err = message.is_err();
+            if tx.blocking_send(message).is_err() || is_err {
+                break;
+            }
+        });
+        rx
+    }
+}
+
+// Returns `None` on the end of data or on partially written / corrupted record
+fn read_record(reader: &mut impl Read, limit: u64) -> io::Result<Option<Vec<u8>>> {
+    if limit < RECORD_HEADER_SIZE {
+        return Ok(None);
+    }
+
+    let mut header = [0; RECORD_HEADER_SIZE as usize];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    let len = u32::from_le_bytes(header[0..4].try_into().expect("valid slice size")) as u64;
+    let crc = u32::from_le_bytes(header[4..8].try_into().expect("valid slice size"));
+    if len < RECORD_BODY_HEADER_SIZE as u64 || len > limit - RECORD_HEADER_SIZE {
+        return Ok(None);
+    }
+
+    let mut body = Vec::with_capacity(len as usize);
+    reader.take(len).read_to_end(&mut body)?;
+    if body.len() as u64 != len || crc32fast::hash(&body) != crc {
+        return Ok(None);
+    }
+    Ok(Some(body))
+}
+
+fn parse_record_body(body: &[u8]) -> (Slot, &[u8]) {
+    (
+        Slot::from_le_bytes(body[0..8].try_into().expect("valid slice size")),
+        &body[RECORD_BODY_HEADER_SIZE..],
+    )
+}
+
+fn decode_message(payload: &[u8]) -> io::Result<Message> {
+    let update = SubscribeUpdate::decode(payload)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let mut message = update
+        .update_oneof
+        .ok_or("update should be defined")
+        .and_then(Message::from_update_oneof)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    // account keys are not encoded, restore them from transaction and meta
+    let result = match &mut message {
+        Message::Transaction(msg) => Arc::make_mut(&mut msg.transaction).fill_account_keys(),
+        Message::Block(msg) => Arc::make_mut(msg)
+            .transactions
+            .iter_mut()
+            .try_for_each(|tx| Arc::make_mut(tx).fill_account_keys()),
+        _ => Ok(()),
+    };
+    result.map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{MessageLog, MessageLogReader},
+        crate::config::ConfigGrpcReplayLog,
+        solana_sdk::{clock::Slot, pubkey::Pubkey},
+        std::{
+            fs::{self, OpenOptions},
+            io::Write,
+            path::Path,
+            sync::Arc,
+        },
+        yellowstone_grpc_proto::{
+            plugin::message::{
+                CommitmentLevel, Message, MessageAccount, MessageAccountInfo, MessageEncodedCache,
+                MessageSlot, MessageSlotRollback,
+            },
+            prelude::SlotRollbackReason,
+        },
+    };
+
+    fn create_config(path: &Path, segment_size_limit: u64) -> ConfigGrpcReplayLog {
+        ConfigGrpcReplayLog {
+            path: path.to_path_buf(),
+            segment_size_limit,
+            stored_slots: u64::MAX / 2,
+            stored_size_limit: u64::MAX,
+        }
+    }
+
+    fn create_messages(slot: Slot) -> Vec<Message> {
+        vec![
+            Message::Account(MessageAccount {
+                account: Arc::new(MessageAccountInfo {
+                    pubkey: Pubkey::new_unique(),
+                    lamports: slot * 1_000,
+                    owner: Pubkey::new_unique(),
+                    executable: false,
+                    rent_epoch: 0,
+                    data: vec![slot as u8; 64],
+                    write_version: slot,
+                    txn_signature: None,
+                    encoded: MessageEncodedCache::default(),
+                }),
+                slot,
+                is_startup: false,
+            }),
+            Message::Slot(MessageSlot {
+                slot,
+                parent: slot.checked_sub(1),
+                status: CommitmentLevel::Processed,
+                dead_error: None,
+            }),
+        ]
+    }
+
+    fn read_all(mut reader: MessageLogReader) -> Vec<Message> {
+        let mut messages = vec![];
+        while let Some(message) = reader.read_next().expect("valid records") {
+            messages.push(message);
+        }
+        messages
+    }
+
+    fn list_segments(path: &Path) -> Vec<String> {
+        let mut names = fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name.ends_with(".log"))
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_replay_from_slot() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = MessageLog::open(create_config(dir.path(), 1024 * 1024)).unwrap();
+        assert_eq!(log.first_available(), None);
+        assert!(log.replay(0).unwrap().is_none());
+
+        let mut expected = vec![];
+        for slot in 10..20 {
+            let messages = create_messages(slot);
+            log.append(&messages).unwrap();
+            if slot >= 15 {
+                expected.extend(messages);
+            }
+        }
+
+        assert_eq!(log.first_available(), Some(10));
+        assert!(log.replay(9).unwrap().is_none());
+        let reader = log.replay(15).unwrap().unwrap();
+        assert_eq!(read_all(reader), expected);
+        let reader = log.replay(20).unwrap().unwrap();
+        assert!(read_all(reader).is_empty());
+    }
+
+    #[test]
+    fn test_replay_slot_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = MessageLog::open(create_config(dir.path(), 1024 * 1024)).unwrap();
+
+        let mut expected = vec![];
+        for slot in 10..13 {
+            let mut messages = create_messages(slot);
+            // rollback of abandoned slot is sent with status of later slot
+            if slot == 12 {
+                messages.insert(
+                    1,
+                    Message::SlotRollback(MessageSlotRollback {
+                        slot: 11,
+                        reason: SlotRollbackReason::AbandonedFork,
+                    }),
+                );
+            }
+            log.append(&messages).unwrap();
+            expected.extend(messages);
+        }
+
+        let reader = log.replay(10).unwrap().unwrap();
+        assert_eq!(read_all(reader), expected);
+    }
+
+    #[test]
+    fn test_segment_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = MessageLog::open(create_config(dir.path(), 512)).unwrap();
+
+        let mut expected = vec![];
+        for slot in 0..20 {
+            let messages = create_messages(slot);
+            log.append(&messages).unwrap();
+            expected.extend(messages);
+        }
+        let segments = list_segments(dir.path());
+        assert!(segments.len() > 1);
+
+        let reader = log.replay(0).unwrap().unwrap();
+        assert_eq!(read_all(reader), expected);
+
+        // sealed segments are loaded from index files
+        drop(log);
+        let mut log = MessageLog::open(create_config(dir.path(), 512)).unwrap();
+        assert_eq!(list_segments(dir.path()), segments);
+        assert_eq!(log.first_available(), Some(0));
+        let reader = log.replay(7).unwrap().unwrap();
+        assert_eq!(read_all(reader), expected[14..]);
+
+        // new records are written to the new segment
+        let messages = create_messages(20);
+        log.append(&messages).unwrap();
+        expected.extend(messages);
+        assert_eq!(list_segments(dir.path()).len(), segments.len() + 1);
+        let reader = log.replay(0).unwrap().unwrap();
+        assert_eq!(read_all(reader), expected);
+    }
+
+    #[test]
+    fn test_replay_after() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = MessageLog::open(create_config(dir.path(), 512)).unwrap();
+
+        let mut expected = vec![];
+        for slot in 0..10 {
+            let messages = create_messages(slot);
+            log.append(&messages).unwrap();
+            if slot >= 5 {
+                expected.extend(messages);
+            }
+        }
+        let reader = log.replay(5).unwrap().unwrap();
+        assert!(reader.size() > 0);
+        let position = reader.end();
+        assert_eq!(read_all(reader), expected);
+
+        // only records appended after the position, including new segments
+        let segments = list_segments(dir.path()).len();
+        let mut expected = vec![];
+        for slot in 10..20 {
+            let messages = create_messages(slot);
+            log.append(&messages).unwrap();
+            expected.extend(messages);
+        }
+        assert!(list_segments(dir.path()).len() > segments);
+        let reader = log.replay_after(position).unwrap().unwrap();
+        let position = reader.end();
+        assert_eq!(read_all(reader), expected);
+
+        let reader = log.replay_after(position).unwrap().unwrap();
+        assert_eq!(reader.size(), 0);
+        assert!(read_all(reader).is_empty());
+
+        // removed records can not be replayed
+        log.clear().unwrap();
+        log.append(&create_messages(20)).unwrap();
+        assert!(log.replay_after(position).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_restart_with_missed_slots() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = MessageLog::open(create_config(dir.path(), 1024 * 1024)).unwrap();
+        for slot in 10..13 {
+            log.append(&create_messages(slot)).unwrap();
+        }
+        drop(log);
+
+        // slots 13..20 were produced while plugin was stopped
+        let mut log = MessageLog::open(create_config(dir.path(), 1024 * 1024)).unwrap();
+        assert_eq!(log.first_available(), Some(10));
+        let messages = create_messages(20);
+        log.append(&messages).unwrap();
+
+        assert_eq!(log.first_available(), Some(20));
+        assert_eq!(list_segments(dir.path()), vec![format!("{:020}.log", 1)]);
+        assert!(log.replay(15).unwrap().is_none());
+        let reader = log.replay(20).unwrap().unwrap();
+        assert_eq!(read_all(reader), messages);
+
+        // without missed slots stored segments are kept
+        drop(log);
+        let mut log = MessageLog::open(create_config(dir.path(), 1024 * 1024)).unwrap();
+        log.append(&create_messages(21)).unwrap();
+        assert_eq!(log.first_available(), Some(20));
+        let reader = log.replay(20).unwrap().unwrap();
+        assert_eq!(read_all(reader).len(), 4);
+    }
+
+    #[test]
+    fn test_truncate_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = MessageLog::open(create_config(dir.path(), 1024 * 1024)).unwrap();
+
+        let mut expected = vec![];
+        for slot in 0..5 {
+            let messages = create_messages(slot);
+            log.append(&messages).unwrap();
+            expected.extend(messages);
+        }
+        drop(log);
+
+        // partially written record
+        let segments = list_segments(dir.path());
+        assert_eq!(segments.len(), 1);
+        let path = dir.path().join(&segments[0]);
+        let size = fs::metadata(&path).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[200, 0, 0, 0, 1, 2, 3, 4, 5, 6]).unwrap();
+        drop(file);
+
+        let mut log = MessageLog::open(create_config(dir.path(), 1024 * 1024)).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), size);
+        let reader = log.replay(0).unwrap().unwrap();
+        assert_eq!(read_all(reader), expected);
+        drop(log);
+
+        // corrupted last record
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(size - 1).unwrap();
+        drop(file);
+        expected.pop();
+
+        let mut log = MessageLog::open(create_config(dir.path(), 1024 * 1024)).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < size - 1);
+        let reader = log.replay(0).unwrap().unwrap();
+        assert_eq!(read_all(reader), expected);
+
+        // log is usable after truncation
+        let messages = create_messages(5);
+        log.append(&messages).unwrap();
+        expected.extend(messages);
+        let reader = log.replay(0).unwrap().unwrap();
+        assert_eq!(read_all(reader), expected);
+    }
+
+    #[test]
+    fn test_truncate_sealed_segment() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = MessageLog::open(create_config(dir.path(), 512)).unwrap();
+        for slot in 0..20 {
+            log.append(&create_messages(slot)).unwrap();
+        }
+        drop(log);
+
+        // corrupted segment without index, everything before next segment is removed
+        let segments = list_segments(dir.path());
+        assert!(segments.len() > 3);
+        let path = dir.path().join(&segments[1]);
+        fs::remove_file(path.with_extension("index")).unwrap();
+        let size = fs::metadata(&path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(size - 1).unwrap();
+        drop(file);
+
+        let log = MessageLog::open(create_config(dir.path(), 512)).unwrap();
+        assert_eq!(list_segments(dir.path()), segments[2..]);
+        assert!(log.first_available() > Some(0));
+    }
+
+    #[test]
+    fn test_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = create_config(dir.path(), 512);
+        config.stored_slots = 10;
+        let mut log = MessageLog::open(config.clone()).unwrap();
+        for slot in 0..100 {
+            log.append(&create_messages(slot)).unwrap();
+        }
+
+        // by slots
+        let first_available = log.first_available().unwrap();
+        assert!(first_available > 80 && first_available <= 90);
+        assert!(log.replay(first_available - 1).unwrap().is_none());
+        let reader = log.replay(first_available).unwrap().unwrap();
+        let messages = read_all(reader);
+        assert_eq!(messages.len() as u64, (100 - first_available) * 2);
+        assert_eq!(messages[0].get_slot(), first_available);
+        drop(log);
+
+        // by size, applied on open
+        config.stored_slots = u64::MAX / 2;
+        config.stored_size_limit = 1024;
+        let log = MessageLog::open(config).unwrap();
+        let size = list_segments(dir.path())
+            .iter()
+            .map(|name| fs::metadata(dir.path().join(name)).unwrap().len())
+            .sum::<u64>();
+        assert!(size <= 1024);
+        assert!(log.first_available().unwrap() > first_available);
+    }
+}
@@ -1,5 +1,8 @@
 use {
-    crate::{config::ConfigPrometheus, version::VERSION as VERSION_INFO},
+    crate::{
+        config::{ConfigGrpcRateLimitPolicy, ConfigGrpcSlowConsumerPolicy, ConfigPrometheus},
+        version::VERSION as VERSION_INFO,
+    },
     agave_geyser_plugin_interface::geyser_plugin_interface::SlotStatus,
     http_body_util::{combinators::BoxBody, BodyExt, Empty as BodyEmpty, Full as BodyFull},
     hyper::{
@@ -24,7 +27,10 @@ use {
         sync::{mpsc, oneshot, Notify},
         task::JoinHandle,
     },
-    yellowstone_grpc_proto::plugin::{filter::Filter, message::CommitmentLevel},
+    yellowstone_grpc_proto::{
+        plugin::{filter::Filter, message::CommitmentLevel},
+        prelude::SlotRollbackReason,
+    },
 };
 
 lazy_static::lazy_static! {
@@ -58,6 +64,11 @@ lazy_static::lazy_static! {
         "connections_total", "Total number of connections to gRPC service"
     ).unwrap();
 
+    static ref CONNECTIONS_REJECTED: IntCounterVec = IntCounterVec::new(
+        Opts::new("connections_rejected_total", "Number of subscriptions rejected by connections limit"),
+        &["limit"]
+    ).unwrap();
+
     static ref SUBSCRIPTIONS_TOTAL: IntGaugeVec = IntGaugeVec::new(
         Opts::new("subscriptions_total", "Total number of subscriptions to gRPC service"),
         &["endpoint", "subscription"]
@@ -67,6 +78,31 @@ lazy_static::lazy_static! {
         Opts::new("missed_status_message_total", "Number of missed messages by commitment"),
         &["status"]
     ).unwrap();
+
+    static ref SLOT_ROLLBACKS: IntCounterVec = IntCounterVec::new(
+        Opts::new("slot_rollbacks_total", "Number of slots with processed data which should be discarded"),
+        &["reason"]
+    ).unwrap();
+
+    static ref SLOW_CONSUMERS: IntCounterVec = IntCounterVec::new(
+        Opts::new("slow_consumers_total", "Number of times clients failed to keep up with updates"),
+        &["endpoint", "policy"]
+    ).unwrap();
+
+    static ref SLOW_CONSUMERS_SKIPPED: IntCounterVec = IntCounterVec::new(
+        Opts::new("slow_consumers_skipped_messages_total", "Number of updates and batches of geyser messages skipped for slow clients"),
+        &["endpoint", "policy"]
+    ).unwrap();
+
+    static ref RATE_LIMITED_MESSAGES: IntCounterVec = IntCounterVec::new(
+        Opts::new("rate_limited_messages_total", "Number of updates exceeded rate limit"),
+        &["limit", "policy"]
+    ).unwrap();
+
+    static ref RATE_LIMITED_BYTES: IntCounterVec = IntCounterVec::new(
+        Opts::new("rate_limited_bytes_total", "Encoded size of updates exceeded rate limit"),
+        &["limit", "policy"]
+    ).unwrap();
 }
 
 #[derive(Debug)]
@@ -197,8 +233,14 @@ impl PrometheusService {
             register!(INVALID_FULL_BLOCKS);
             register!(MESSAGE_QUEUE_SIZE);
             register!(CONNECTIONS_TOTAL);
+            register!(CONNECTIONS_REJECTED);
             register!(SUBSCRIPTIONS_TOTAL);
             register!(MISSED_STATUS_MESSAGE);
+            register!(SLOT_ROLLBACKS);
+            register!(SLOW_CONSUMERS);
+            register!(SLOW_CONSUMERS_SKIPPED);
+            register!(RATE_LIMITED_MESSAGES);
+            register!(RATE_LIMITED_BYTES);
 
             VERSION
                 .with_label_values(&[
@@ -349,6 +391,10 @@ pub fn connections_total_dec() {
     CONNECTIONS_TOTAL.dec()
 }
 
+pub fn connections_rejected_inc(limit: &str) {
+    CONNECTIONS_REJECTED.with_label_values(&[limit]).inc()
+}
+
 pub fn update_subscriptions(endpoint: &str, old: Option<&Filter>, new: Option<&Filter>) {
     for (multiplier, filter) in [(-1, old), (1, new)] {
         if let Some(filter) = filter {
@@ -370,3 +416,34 @@ pub fn missed_status_message_inc(status: CommitmentLevel) {
         .with_label_values(&[status.as_str()])
         .inc()
 }
+
+pub fn slot_rollback_inc(reason: SlotRollbackReason) {
+    SLOT_ROLLBACKS
+        .with_label_values(&[reason.as_str_name()])
+        .inc()
+}
+
+pub fn slow_consumer_inc(endpoint: &str, policy: ConfigGrpcSlowConsumerPolicy) {
+    SLOW_CONSUMERS
+        .with_label_values(&[endpoint, policy.as_str()])
+        .inc()
+}
+
+pub fn slow_consumer_skipped_inc(
+    endpoint: &str,
+    policy: ConfigGrpcSlowConsumerPolicy,
+    skipped: u64,
+) {
+    SLOW_CONSUMERS_SKIPPED
+        .with_label_values(&[endpoint, policy.as_str()])
+        .inc_by(skipped)
+}
+
+pub fn rate_limited_inc(limit: &str, policy: ConfigGrpcRateLimitPolicy, bytes: usize) {
+    RATE_LIMITED_MESSAGES
+        .with_label_values(&[limit, policy.as_str()])
+        .inc();
+    RATE_LIMITED_BYTES
+        .with_label_values(&[limit, policy.as_str()])
+        .inc_by(bytes as u64);
+}
@@ -0,0 +1,243 @@
+use {
+    crate::config::ConfigGrpcRateLimitBucket,
+    std::{sync::Mutex, time::Instant},
+};
+
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+}
+
+impl TokenBucket {
+    const fn new(rate: usize) -> Self {
+        Self {
+            rate: rate as f64,
+            tokens: rate as f64,
+        }
+    }
+
+    fn refill(&mut self, elapsed: f64) {
+        self.tokens = (self.tokens + self.rate * elapsed).min(self.rate);
+    }
+
+    // update larger than bucket size is allowed once bucket is full
+    fn is_available(&self, cost: f64) -> bool {
+        self.tokens >= cost.min(self.rate)
+    }
+}
+
+#[derive(Debug)]
+struct RateLimiterState {
+    messages: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+    updated_at: Instant,
+}
+
+impl RateLimiterState {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.updated_at = self.updated_at.max(now);
+
+        for bucket in [self.messages.as_mut(), self.bytes.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            bucket.refill(elapsed);
+        }
+    }
+
+    fn is_available(&self, bytes: usize) -> bool {
+        [
+            (self.messages.as_ref(), 1.0),
+            (self.bytes.as_ref(), bytes as f64),
+        ]
+        .into_iter()
+        .all(|(bucket, cost)| bucket.map_or(true, |bucket| bucket.is_available(cost)))
+    }
+
+    fn consume(&mut self, bytes: usize) {
+        for (bucket, cost) in [
+            (self.messages.as_mut(), 1.0),
+            (self.bytes.as_mut(), bytes as f64),
+        ] {
+            if let Some(bucket) = bucket {
+                bucket.tokens -= cost;
+            }
+        }
+    }
+}
+
+/// Token bucket limiter for number of updates and encoded bytes per second
+#[derive(Debug)]
+pub struct RateLimiter {
+    bytes_limited: bool,
+    state: Mutex<RateLimiterState>,
+}
+
+impl RateLimiter {
+    pub fn new(config: ConfigGrpcRateLimitBucket) -> Option<Self> {
+        if config.messages_per_second.is_none() && config.bytes_per_second.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            bytes_limited: config.bytes_per_second.is_some(),
+            state: Mutex::new(RateLimiterState {
+                messages: config.messages_per_second.map(TokenBucket::new),
+                bytes: config.bytes_per_second.map(TokenBucket::new),
+                updated_at: Instant::now(),
+            }),
+        })
+    }
+
+    /// Encoded size is required only if bytes are limited
+    pub const fn is_bytes_limited(&self) -> bool {
+        self.bytes_limited
+    }
+
+    #[cfg(test)]
+    fn try_acquire_at(&self, now: Instant, bytes: usize) -> bool {
+        Self::try_acquire_all_at([self], now, bytes).is_ok()
+    }
+
+    /// Tokens are consumed only if all limiters allow the update,
+    /// otherwise index of the first exceeded limiter is returned
+    pub fn try_acquire_all<'a>(
+        limiters: impl IntoIterator<Item = &'a Self>,
+        bytes: usize,
+    ) -> Result<(), usize> {
+        Self::try_acquire_all_at(limiters, Instant::now(), bytes)
+    }
+
+    fn try_acquire_all_at<'a>(
+        limiters: impl IntoIterator<Item = &'a Self>,
+        now: Instant,
+        bytes: usize,
+    ) -> Result<(), usize> {
+        // limiters are always locked in the same order: connection one, then shared one
+        let mut states = limiters
+            .into_iter()
+            .map(|limiter| limiter.state.lock().unwrap())
+            .collect::<Vec<_>>();
+
+        for (index, state) in states.iter_mut().enumerate() {
+            state.refill(now);
+            if !state.is_available(bytes) {
+                return Err(index);
+            }
+        }
+
+        for state in states.iter_mut() {
+            state.consume(bytes);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::RateLimiter,
+        crate::config::ConfigGrpcRateLimitBucket,
+        std::time::{Duration, Instant},
+    };
+
+    #[test]
+    fn test_rate_limit_disabled() {
+        assert!(RateLimiter::new(ConfigGrpcRateLimitBucket {
+            messages_per_second: None,
+            bytes_per_second: None,
+        })
+        .is_none());
+    }
+
+    #[test]
+    fn test_rate_limit_messages() {
+        let limiter = RateLimiter::new(ConfigGrpcRateLimitBucket {
+            messages_per_second: Some(10),
+            bytes_per_second: None,
+        })
+        .unwrap();
+        assert!(!limiter.is_bytes_limited());
+
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert!(limiter.try_acquire_at(now, 0));
+        }
+        assert!(!limiter.try_acquire_at(now, 0));
+
+        let now = now + Duration::from_millis(200);
+        assert!(limiter.try_acquire_at(now, 0));
+        assert!(limiter.try_acquire_at(now, 0));
+        assert!(!limiter.try_acquire_at(now, 0));
+
+        // refill is capped by bucket size
+        let now = now + Duration::from_secs(10);
+        for _ in 0..10 {
+            assert!(limiter.try_acquire_at(now, 0));
+        }
+        assert!(!limiter.try_acquire_at(now, 0));
+    }
+
+    #[test]
+    fn test_rate_limit_bytes() {
+        let limiter = RateLimiter::new(ConfigGrpcRateLimitBucket {
+            messages_per_second: Some(100),
+            bytes_per_second: Some(1_000),
+        })
+        .unwrap();
+        assert!(limiter.is_bytes_limited());
+
+        let now = Instant::now();
+        assert!(limiter.try_acquire_at(now, 600));
+        assert!(!limiter.try_acquire_at(now, 600));
+        // rejected update does not consume message tokens
+        assert!(limiter.try_acquire_at(now, 400));
+        assert!(!limiter.try_acquire_at(now, 1));
+
+        // update larger than bucket passes on full bucket and leaves debt
+        let now = now + Duration::from_secs(1);
+        assert!(limiter.try_acquire_at(now, 3_000));
+        let now = now + Duration::from_secs(1);
+        assert!(!limiter.try_acquire_at(now, 1));
+        let now = now + Duration::from_secs(2);
+        assert!(limiter.try_acquire_at(now, 1_000));
+    }
+
+    #[test]
+    fn test_rate_limit_all() {
+        let connection = RateLimiter::new(ConfigGrpcRateLimitBucket {
+            messages_per_second: Some(10),
+            bytes_per_second: None,
+        })
+        .unwrap();
+        let token = RateLimiter::new(ConfigGrpcRateLimitBucket {
+            messages_per_second: Some(2),
+            bytes_per_second: None,
+        })
+        .unwrap();
+
+        let now = Instant::now();
+        let limiters = [&connection, &token];
+        assert_eq!(RateLimiter::try_acquire_all_at(limiters, now, 0), Ok(()));
+        assert_eq!(RateLimiter::try_acquire_all_at(limiters, now, 0), Ok(()));
+        assert_eq!(RateLimiter::try_acquire_all_at(limiters, now, 0), Err(1));
+        assert_eq!(RateLimiter::try_acquire_all_at(limiters, now, 0), Err(1));
+
+        // rejected updates do not consume tokens of other limiters
+        for _ in 0..8 {
+            assert!(connection.try_acquire_at(now, 0));
+        }
+        assert!(!connection.try_acquire_at(now, 0));
+
+        let now = now + Duration::from_secs(1);
+        assert!(token.try_acquire_at(now, 0));
+        assert!(token.try_acquire_at(now, 0));
+        assert_eq!(RateLimiter::try_acquire_all_at(limiters, now, 0), Err(1));
+        for _ in 0..10 {
+            assert!(connection.try_acquire_at(now, 0));
+        }
+        assert_eq!(RateLimiter::try_acquire_all_at(limiters, now, 0), Err(0));
+    }
+}
@@ -1,16 +1,19 @@
 use {
-    criterion::{criterion_group, criterion_main, BenchmarkId, Criterion},
+    criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion},
     prost::Message as _,
-    std::time::Duration,
+    std::{sync::Arc, time::Duration},
     yellowstone_grpc_proto::plugin::{
-        filter::message::{
-            tests::{
-                create_accounts, create_message_filters, load_predefined_blocks,
-                load_predefined_transactions,
+        filter::{
+            message::{
+                tests::{
+                    create_accounts, create_accounts_raw, create_message_filters,
+                    load_predefined_blocks, load_predefined_transactions,
+                },
+                FilteredUpdate, FilteredUpdateOneof,
             },
-            FilteredUpdate, FilteredUpdateOneof,
+            FilterAccountsDataSlice,
         },
-        message::MessageTransaction,
+        message::{MessageAccount, MessageTransaction},
     },
 };
 
@@ -41,6 +44,7 @@ fn bench_account(c: &mut Criterion) {
         .map(|(msg, data_slice)| FilteredUpdate {
             filters: filters.clone(),
             message: FilteredUpdateOneof::account(&msg, data_slice),
+            commitment: None,
         })
         .collect::<Vec<_>>();
     bench!(&updates, "accounts");
@@ -53,6 +57,7 @@ fn bench_account(c: &mut Criterion) {
                 transaction,
                 slot: 42,
             }),
+            commitment: None,
         })
         .collect::<Vec<_>>();
     bench!(&updates, "transactions");
@@ -62,16 +67,180 @@ fn bench_account(c: &mut Criterion) {
         .map(|block| FilteredUpdate {
             filters: filters.clone(),
             message: FilteredUpdateOneof::block(Box::new(block)),
+            commitment: None,
         })
         .collect::<Vec<_>>();
     bench!(&updates, "blocks");
 }
 
+// every subscriber encodes own copy of the message ("copy") or all subscribers share
+// message with encoded transactions and accounts ("shared")
+fn bench_subscribers(c: &mut Criterion) {
+    let filters = create_message_filters(&["my special filter"]);
+
+    macro_rules! bench {
+        ($create_updates:expr, $kind:expr) => {
+            for subscribers in [1, 10, 100] {
+                c.bench_with_input(
+                    BenchmarkId::new(format!("{}/copy", $kind), subscribers),
+                    &subscribers,
+                    |b, &subscribers| {
+                        b.iter_batched(
+                            || {
+                                (0..subscribers)
+                                    .map(|_| $create_updates())
+                                    .collect::<Vec<_>>()
+                            },
+                            |clients| {
+                                for updates in clients.iter() {
+                                    for update in updates.iter() {
+                                        update.encode_to_vec().len();
+                                    }
+                                }
+                            },
+                            BatchSize::LargeInput,
+                        )
+                    },
+                );
+                c.bench_with_input(
+                    BenchmarkId::new(format!("{}/shared", $kind), subscribers),
+                    &subscribers,
+                    |b, &subscribers| {
+                        b.iter_batched(
+                            $create_updates,
+                            |updates| {
+                                for _ in 0..subscribers {
+                                    for update in updates.iter() {
+                                        update.encode_to_vec().len();
+                                    }
+                                }
+                            },
+                            BatchSize::LargeInput,
+                        )
+                    },
+                );
+            }
+        };
+    }
+
+    // clone of the message info does not copy encoded cache
+    let accounts = create_accounts_raw()
+        .into_iter()
+        .filter(|account| account.data.len() <= 1024)
+        .collect::<Vec<_>>();
+    let create_updates = || {
+        accounts
+            .iter()
+            .map(|account| FilteredUpdate {
+                filters: filters.clone(),
+                message: FilteredUpdateOneof::account(
+                    &MessageAccount {
+                        account: Arc::new(account.as_ref().clone()),
+                        slot: 42,
+                        is_startup: false,
+                    },
+                    FilterAccountsDataSlice::default(),
+                ),
+                commitment: None,
+            })
+            .collect::<Vec<_>>()
+    };
+    bench!(create_updates, "subscribers/accounts");
+
+    let transactions = load_predefined_transactions();
+    let create_updates = || {
+        transactions
+            .iter()
+            .map(|transaction| FilteredUpdate {
+                filters: filters.clone(),
+                message: FilteredUpdateOneof::transaction(&MessageTransaction {
+                    transaction: Arc::new(transaction.as_ref().clone()),
+                    slot: 42,
+                }),
+                commitment: None,
+            })
+            .collect::<Vec<_>>()
+    };
+    bench!(create_updates, "subscribers/transactions");
+}
+
+// encoded cache is filled by the first subscriber and read by others ("cached"), without
+// cache every subscriber encodes the message as before the cache was added ("baseline")
+fn bench_encoded_cache(c: &mut Criterion) {
+    let filters = create_message_filters(&["my special filter"]);
+
+    macro_rules! bench {
+        ($create_updates:expr, $kind:expr) => {
+            let updates = $create_updates();
+            for update in updates.iter() {
+                update.encode_to_vec();
+            }
+            c.bench_function(&format!("{}/cached", $kind), |b| {
+                b.iter(|| {
+                    for update in updates.iter() {
+                        update.encode_to_vec().len();
+                    }
+                })
+            });
+            c.bench_function(&format!("{}/baseline", $kind), |b| {
+                b.iter_batched(
+                    $create_updates,
+                    |updates| {
+                        for update in updates.iter() {
+                            update.encode_to_vec().len();
+                        }
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        };
+    }
+
+    let accounts = create_accounts_raw()
+        .into_iter()
+        .filter(|account| account.data.len() <= 1024)
+        .collect::<Vec<_>>();
+    let create_updates = || {
+        accounts
+            .iter()
+            .map(|account| FilteredUpdate {
+                filters: filters.clone(),
+                message: FilteredUpdateOneof::account(
+                    &MessageAccount {
+                        account: Arc::new(account.as_ref().clone()),
+                        slot: 42,
+                        is_startup: false,
+                    },
+                    FilterAccountsDataSlice::default(),
+                ),
+                commitment: None,
+            })
+            .collect::<Vec<_>>()
+    };
+    bench!(create_updates, "encoded_cache/accounts");
+
+    let transactions = load_predefined_transactions();
+    let create_updates = || {
+        transactions
+            .iter()
+            .map(|transaction| FilteredUpdate {
+                filters: filters.clone(),
+                message: FilteredUpdateOneof::transaction(&MessageTransaction {
+                    transaction: Arc::new(transaction.as_ref().clone()),
+                    slot: 42,
+                }),
+                commitment: None,
+            })
+            .collect::<Vec<_>>()
+    };
+    bench!(create_updates, "encoded_cache/transactions");
+}
+
 criterion_group!(
     name = benches;
     config = Criterion::default()
         .warm_up_time(Duration::from_secs(3)) // default 3
         .measurement_time(Duration::from_secs(5)); // default 5
-    targets = bench_account
+    targets = bench_account, bench_subscribers, bench_encoded_cache
 );
 criterion_main!(benches);
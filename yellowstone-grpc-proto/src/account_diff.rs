@@ -0,0 +1,83 @@
+use {
+    crate::geyser::{
+        SubscribeUpdateAccount, SubscribeUpdateAccountDiff, SubscribeUpdateAccountDiffPatch,
+    },
+    std::collections::HashMap,
+};
+
+pub type AccountDiffResult<T> = Result<T, &'static str>;
+
+pub fn apply_patches(
+    data: &mut Vec<u8>,
+    data_len: u64,
+    patches: &[SubscribeUpdateAccountDiffPatch],
+) -> AccountDiffResult<()> {
+    let data_len = usize::try_from(data_len).map_err(|_| "invalid data length")?;
+    data.resize(data_len, 0);
+    for patch in patches {
+        let start = usize::try_from(patch.offset).map_err(|_| "invalid patch offset")?;
+        let end = start
+            .checked_add(patch.data.len())
+            .ok_or("invalid patch offset")?;
+        data.get_mut(start..end)
+            .ok_or("patch out of account data")?
+            .copy_from_slice(&patch.data);
+    }
+    Ok(())
+}
+
+/// Account data received with `accounts_diff` enabled, every `SubscribeUpdateAccount`
+/// should be passed to [`AccountDiffState::update`] and every `SubscribeUpdateAccountDiff`
+/// to [`AccountDiffState::apply`] in the order received from the stream.
+#[derive(Debug, Default, Clone)]
+pub struct AccountDiffState {
+    accounts: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl AccountDiffState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    pub fn get(&self, pubkey: &[u8]) -> Option<&[u8]> {
+        self.accounts.get(pubkey).map(|data| data.as_slice())
+    }
+
+    pub fn remove(&mut self, pubkey: &[u8]) -> Option<Vec<u8>> {
+        self.accounts.remove(pubkey)
+    }
+
+    pub fn update(&mut self, msg: &SubscribeUpdateAccount) {
+        if let Some(account) = &msg.account {
+            self.accounts
+                .insert(account.pubkey.clone(), account.data.clone());
+        }
+    }
+
+    pub fn apply(
+        &mut self,
+        msg: SubscribeUpdateAccountDiff,
+    ) -> AccountDiffResult<SubscribeUpdateAccount> {
+        let mut account = msg.account.ok_or("account should be defined")?;
+        let data = self
+            .accounts
+            .get_mut(&account.pubkey)
+            .ok_or("account data is not known")?;
+        apply_patches(data, msg.data_len, &msg.patches)?;
+        account.data = data.clone();
+
+        Ok(SubscribeUpdateAccount {
+            account: Some(account),
+            slot: msg.slot,
+            is_startup: msg.is_startup,
+        })
+    }
+}
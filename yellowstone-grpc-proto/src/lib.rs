@@ -37,6 +37,8 @@ pub use prost;
 #[cfg(feature = "tonic")]
 pub use tonic;
 
+pub mod account_diff;
+
 #[cfg(feature = "plugin")]
 pub mod plugin;
 
@@ -4,12 +4,19 @@ use {
             subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
             subscribe_request_filter_accounts_filter_lamports::Cmp as AccountsFilterLamports,
             subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
-            CommitmentLevel as CommitmentLevelProto, SubscribeRequest,
+            subscribe_request_filter_transactions_cmp::Cmp as TransactionsFilterCmp,
+            AccountEncoding, CommitmentLevel as CommitmentLevelProto, SubscribeRequest,
             SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
-            SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterLamports,
+            SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterAnyOf,
+            SubscribeRequestFilterAccountsFilterLamports,
+            SubscribeRequestFilterAccountsFilterMemcmp, SubscribeRequestFilterAccountsFilterNot,
             SubscribeRequestFilterBlocks, SubscribeRequestFilterBlocksMeta,
+            SubscribeRequestFilterDelta, SubscribeRequestFilterDeltaAccounts,
+            SubscribeRequestFilterDeltaRemove, SubscribeRequestFilterDeltaTransactions,
             SubscribeRequestFilterEntry, SubscribeRequestFilterSlots,
-            SubscribeRequestFilterTransactions,
+            SubscribeRequestFilterTransactions, SubscribeRequestFilterTransactionsCmp,
+            SubscribeRequestFilterTransactionsInstructionData,
+            SubscribeRequestFilterTransactionsTokenBalance, SubscribeUpdateAccountInfoParsed,
         },
         plugin::{
             filter::{
@@ -20,29 +27,42 @@ use {
                 },
                 message::{
                     FilteredUpdate, FilteredUpdateBlock, FilteredUpdateFilters,
-                    FilteredUpdateOneof, FilteredUpdates,
+                    FilteredUpdateOneof, FilteredUpdateTransactionMask, FilteredUpdates,
                 },
                 name::{FilterName, FilterNameError, FilterNames},
             },
             message::{
-                CommitmentLevel, Message, MessageAccount, MessageBlock, MessageBlockMeta,
-                MessageEntry, MessageSlot, MessageTransaction,
+                CommitmentLevel, Message, MessageAccount, MessageAccountInfo, MessageBlock,
+                MessageBlockMeta, MessageEntry, MessageSlot, MessageTransaction,
+                MessageTransactionInfo,
             },
         },
+        solana::storage::confirmed_block,
     },
     base64::{engine::general_purpose::STANDARD as base64_engine, Engine},
     bytes::buf::BufMut,
     prost::encoding::{encode_key, encode_varint, WireType},
+    regex::{RegexSet, RegexSetBuilder},
+    solana_account_decoder::{
+        parse_account_data::{
+            parse_account_data_v2, AccountAdditionalDataV2, SplTokenAdditionalData,
+        },
+        parse_token::is_known_spl_token_id,
+    },
     solana_sdk::{
         pubkey::{ParsePubkeyError, Pubkey},
         signature::{ParseSignatureError, Signature},
     },
-    spl_token_2022::{generic_token_account::GenericTokenAccount, state::Account as TokenAccount},
+    spl_token_2022::{
+        extension::StateWithExtensions,
+        generic_token_account::GenericTokenAccount,
+        state::{Account as TokenAccount, Mint},
+    },
     std::{
-        collections::{HashMap, HashSet},
+        collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
         ops::Range,
         str::FromStr,
-        sync::Arc,
+        sync::{Arc, Mutex},
     },
 };
 
@@ -55,21 +75,39 @@ pub enum FilterError {
 
     #[error("failed to create CommitmentLevel from {commitment}")]
     InvalidCommitment { commitment: i32 },
+    #[error("failed to create AccountEncoding from {encoding}")]
+    InvalidAccountEncoding { encoding: i32 },
     #[error(transparent)]
     InvalidPubkey(#[from] ParsePubkeyError),
     #[error(transparent)]
     InvalidSignature(#[from] ParseSignatureError),
+    #[error(transparent)]
+    InvalidRegex(#[from] regex::Error),
 
     #[error("Too many filters provided; max {max}")]
     CreateAccountStateMaxFilters { max: usize },
     #[error("{0}")]
     CreateAccountState(&'static str),
+    #[error("{0}")]
+    CreateTransactionsInstructionData(&'static str),
+    #[error("{0}")]
+    CreateTransactionsCmp(&'static str),
     #[error("`include_{0}` is not allowed")]
     CreateBlocksNotAllowed(&'static str),
+    #[error("`{0}` account encoding is not allowed")]
+    CreateAccountsEncodingNotAllowed(&'static str),
     #[error("failed to create filter: data slices out of order")]
     CreateDataSliceOutOfOrder,
     #[error("failed to create filter: data slices overlapped")]
     CreateDataSliceOverlap,
+    #[error("failed to create filter: accounts_diff can not be used with data slices")]
+    CreateAccountsDiffDataSlice,
+    #[error("failed to create filter: accounts_diff can not be used with parsed account encoding")]
+    CreateAccountsDiffEncoding,
+    #[error("failed to create filter: accounts_diff can not be used with different commitments")]
+    CreateAccountsDiffCommitment,
+    #[error("failed to update filter: {kind} filter `{name}` not found")]
+    DeltaFilterNotFound { kind: &'static str, name: String },
 }
 
 pub type FilterResult<T> = Result<T, FilterError>;
@@ -108,7 +146,10 @@ pub struct Filter {
     blocks: FilterBlocks,
     blocks_meta: FilterBlocksMeta,
     commitment: CommitmentLevel,
+    commitments: FilterCommitments,
     accounts_data_slice: FilterAccountsDataSlice,
+    accounts_diff: Option<FilterAccountsDiff>,
+    transactions_commitment_updates: bool,
     ping: Option<i32>,
 }
 
@@ -129,7 +170,10 @@ impl Default for Filter {
             blocks: FilterBlocks::default(),
             blocks_meta: FilterBlocksMeta::default(),
             commitment: CommitmentLevel::Processed,
+            commitments: FilterCommitments::default(),
             accounts_data_slice: FilterAccountsDataSlice::default(),
+            accounts_diff: None,
+            transactions_commitment_updates: false,
             ping: None,
         }
     }
@@ -140,9 +184,15 @@ impl Filter {
         config: &SubscribeRequest,
         limits: &FilterLimits,
         names: &mut FilterNames,
+        mint_decimals: &FilterMintDecimals,
     ) -> FilterResult<Self> {
         Ok(Self {
-            accounts: FilterAccounts::new(&config.accounts, &limits.accounts, names)?,
+            accounts: FilterAccounts::new(
+                &config.accounts,
+                &limits.accounts,
+                names,
+                mint_decimals,
+            )?,
             slots: FilterSlots::new(&config.slots, &limits.slots, names)?,
             transactions: FilterTransactions::new(
                 &config.transactions,
@@ -160,14 +210,149 @@ impl Filter {
             blocks: FilterBlocks::new(&config.blocks, &limits.blocks, names)?,
             blocks_meta: FilterBlocksMeta::new(&config.blocks_meta, &limits.blocks_meta, names)?,
             commitment: Self::decode_commitment(config.commitment)?,
+            commitments: FilterCommitments::new(config)?,
             accounts_data_slice: FilterAccountsDataSlice::new(
                 &config.accounts_data_slice,
                 limits.accounts.data_slice_max,
             )?,
+            accounts_diff: FilterAccountsDiff::create(config, &limits.accounts)?,
+            transactions_commitment_updates: config
+                .transactions_commitment_updates
+                .unwrap_or_default(),
             ping: config.ping.as_ref().map(|msg| msg.id),
         })
     }
 
+    /// Applies changes of named filters to the live filter, the whole delta is
+    /// validated first and on error the filter is not changed.
+    pub fn apply_delta(
+        &mut self,
+        delta: &SubscribeRequestFilterDelta,
+        limits: &FilterLimits,
+    ) -> FilterResult<()> {
+        let remove = delta.remove.clone().unwrap_or_default();
+        Self::check_delta_names("accounts", &remove.accounts, |name| {
+            self.accounts.get_name(name).is_some()
+        })?;
+        Self::check_delta_names("slots", &remove.slots, |name| {
+            self.slots.filters.contains_key(name)
+        })?;
+        Self::check_delta_names("transactions", &remove.transactions, |name| {
+            self.transactions.filters.contains_key(name)
+        })?;
+        Self::check_delta_names("transactions_status", &remove.transactions_status, |name| {
+            self.transactions_status.filters.contains_key(name)
+        })?;
+        Self::check_delta_names("blocks", &remove.blocks, |name| {
+            self.blocks.filters.contains_key(name)
+        })?;
+        Self::check_delta_names("blocks_meta", &remove.blocks_meta, |name| {
+            self.blocks_meta
+                .filters
+                .iter()
+                .any(|filter| filter.as_ref() == name)
+        })?;
+        Self::check_delta_names("entry", &remove.entry, |name| {
+            self.entries
+                .filters
+                .iter()
+                .any(|filter| filter.as_ref() == name)
+        })?;
+
+        // updated filters should not be removed by the same delta
+        Self::check_delta_names("accounts", delta.accounts.keys(), |name| {
+            !remove.accounts.iter().any(|removed| removed == name)
+        })?;
+        Self::check_delta_names("transactions", delta.transactions.keys(), |name| {
+            !remove.transactions.iter().any(|removed| removed == name)
+        })?;
+        Self::check_delta_names(
+            "transactions_status",
+            delta.transactions_status.keys(),
+            |name| {
+                !remove
+                    .transactions_status
+                    .iter()
+                    .any(|removed| removed == name)
+            },
+        )?;
+
+        let accounts = delta
+            .accounts
+            .iter()
+            .map(|(name, delta)| self.accounts.create_delta(name, delta, &limits.accounts))
+            .collect::<FilterResult<Vec<_>>>()?;
+        let transactions = delta
+            .transactions
+            .iter()
+            .map(|(name, delta)| {
+                self.transactions
+                    .create_delta(name, delta, &limits.transactions)
+            })
+            .collect::<FilterResult<Vec<_>>>()?;
+        let transactions_status = delta
+            .transactions_status
+            .iter()
+            .map(|(name, delta)| {
+                self.transactions_status
+                    .create_delta(name, delta, &limits.transactions_status)
+            })
+            .collect::<FilterResult<Vec<_>>>()?;
+
+        for name in remove.accounts.iter() {
+            self.accounts.remove(name);
+        }
+        for name in remove.slots.iter() {
+            self.slots.filters.remove(name.as_str());
+        }
+        for name in remove.transactions.iter() {
+            self.transactions.filters.remove(name.as_str());
+        }
+        for name in remove.transactions_status.iter() {
+            self.transactions_status.filters.remove(name.as_str());
+        }
+        for name in remove.blocks.iter() {
+            self.blocks.filters.remove(name.as_str());
+        }
+        self.blocks_meta.filters.retain(|filter| {
+            !remove
+                .blocks_meta
+                .iter()
+                .any(|name| name == filter.as_ref())
+        });
+        self.entries
+            .filters
+            .retain(|filter| !remove.entry.iter().any(|name| name == filter.as_ref()));
+        self.commitments.remove(&remove);
+
+        for delta in accounts {
+            self.accounts.apply_delta(delta);
+        }
+        for delta in transactions {
+            self.transactions.apply_delta(delta);
+        }
+        for delta in transactions_status {
+            self.transactions_status.apply_delta(delta);
+        }
+        Ok(())
+    }
+
+    fn check_delta_names<'a>(
+        kind: &'static str,
+        names: impl IntoIterator<Item = &'a String>,
+        is_valid: impl Fn(&str) -> bool,
+    ) -> FilterResult<()> {
+        for name in names {
+            if !is_valid(name) {
+                return Err(FilterError::DeltaFilterNotFound {
+                    kind,
+                    name: name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn decode_commitment(commitment: Option<i32>) -> FilterResult<CommitmentLevel> {
         let commitment = commitment.unwrap_or(CommitmentLevelProto::Processed as i32);
         CommitmentLevelProto::try_from(commitment)
@@ -186,6 +371,37 @@ impl Filter {
         })
     }
 
+    fn decode_memcmp(
+        memcmp: &SubscribeRequestFilterAccountsFilterMemcmp,
+    ) -> Result<(usize, Vec<u8>), &'static str> {
+        const MAX_DATA_SIZE: usize = 128;
+        const MAX_DATA_BASE58_SIZE: usize = 175;
+        const MAX_DATA_BASE64_SIZE: usize = 172;
+
+        let data = match &memcmp.data {
+            Some(AccountsFilterMemcmpOneof::Bytes(data)) => data.clone(),
+            Some(AccountsFilterMemcmpOneof::Base58(data)) => {
+                if data.len() > MAX_DATA_BASE58_SIZE {
+                    return Err("data too large");
+                }
+                bs58::decode(data)
+                    .into_vec()
+                    .map_err(|_| "invalid base58")?
+            }
+            Some(AccountsFilterMemcmpOneof::Base64(data)) => {
+                if data.len() > MAX_DATA_BASE64_SIZE {
+                    return Err("data too large");
+                }
+                base64_engine.decode(data).map_err(|_| "invalid base64")?
+            }
+            None => return Err("data for memcmp should be defined"),
+        };
+        if data.len() > MAX_DATA_SIZE {
+            return Err("data too large");
+        }
+        Ok((memcmp.offset as usize, data))
+    }
+
     fn decode_pubkeys_into_set(
         pubkeys: &[String],
         limit: &HashSet<Pubkey>,
@@ -218,29 +434,98 @@ impl Filter {
         ]
     }
 
+    /// Default commitment of the request, used for named filters without own commitment
     pub const fn get_commitment_level(&self) -> CommitmentLevel {
         self.commitment
     }
 
+    /// Returns `true` if commitment updates are requested for processed transactions
+    pub const fn has_transactions_commitment_updates(&self) -> bool {
+        self.transactions_commitment_updates
+    }
+
+    /// Next updates of the accounts are sent in full, dropped updates were not delivered
+    /// to the client and previous data of the accounts can't be used for diff
+    pub fn reset_accounts_diff(&self, pubkeys: impl IntoIterator<Item = Pubkey>) {
+        if let Some(accounts_diff) = &self.accounts_diff {
+            accounts_diff.reset(pubkeys);
+        }
+    }
+
+    /// Returns `true` if any named filter receives updates at the commitment
+    pub fn has_commitment_level(&self, commitment: CommitmentLevel) -> bool {
+        self.commitment == commitment || self.commitments.contains(commitment)
+    }
+
+    const fn commitment_match<'a>(
+        &self,
+        commitments: &'a HashMap<String, CommitmentLevel>,
+        commitment: Option<CommitmentLevel>,
+    ) -> FilterCommitmentMatch<'a> {
+        FilterCommitmentMatch {
+            commitments,
+            default: self.commitment,
+            commitment,
+        }
+    }
+
+    /// Updates for named filters with commitment of the broadcast, every update is tagged
+    /// with the commitment. Without commitment (accounts snapshot) all filters are used.
     pub fn get_updates(
         &self,
         message: &Message,
         commitment: Option<CommitmentLevel>,
     ) -> FilteredUpdates {
-        match message {
-            Message::Account(message) => self
-                .accounts
-                .get_updates(message, &self.accounts_data_slice),
-            Message::Slot(message) => self.slots.get_updates(message, commitment),
+        let mut updates = match message {
+            Message::Account(message) => self.accounts.get_updates(
+                message,
+                &self.accounts_data_slice,
+                self.accounts_diff.as_ref(),
+                self.commitment_match(&self.commitments.accounts, commitment),
+            ),
+            Message::Slot(message) => {
+                let mut updates = self.slots.get_updates(message, commitment);
+                self.commitment_match(&self.commitments.slots, commitment)
+                    .retain(&mut updates);
+                updates
+            }
             Message::Transaction(message) => {
                 let mut updates = self.transactions.get_updates(message);
-                updates.append(&mut self.transactions_status.get_updates(message));
+                self.commitment_match(&self.commitments.transactions, commitment)
+                    .retain(&mut updates);
+                let mut updates_status = self.transactions_status.get_updates(message);
+                self.commitment_match(&self.commitments.transactions_status, commitment)
+                    .retain(&mut updates_status);
+                updates.append(&mut updates_status);
+                updates
+            }
+            Message::Entry(message) => {
+                let mut updates = self.entries.get_updates(message);
+                self.commitment_match(&self.commitments.entries, commitment)
+                    .retain(&mut updates);
+                updates
+            }
+            Message::Block(message) => {
+                let mut updates = self.blocks.get_updates(message, &self.accounts_data_slice);
+                self.commitment_match(&self.commitments.blocks, commitment)
+                    .retain(&mut updates);
+                updates
+            }
+            // sent by client loop to clients received processed data of the slot
+            Message::SlotRollback(_) => FilteredUpdates::new(),
+            Message::BlockMeta(message) => {
+                let mut updates = self.blocks_meta.get_updates(message);
+                self.commitment_match(&self.commitments.blocks_meta, commitment)
+                    .retain(&mut updates);
                 updates
             }
-            Message::Entry(message) => self.entries.get_updates(message),
-            Message::Block(message) => self.blocks.get_updates(message, &self.accounts_data_slice),
-            Message::BlockMeta(message) => self.blocks_meta.get_updates(message),
+        };
+        if commitment.is_some() {
+            for update in updates.iter_mut() {
+                update.commitment = commitment;
+            }
         }
+        updates
     }
 
     pub fn get_pong_msg(&self) -> Option<FilteredUpdate> {
@@ -249,6 +534,115 @@ impl Filter {
     }
 }
 
+// commitments of named filters which override commitment of the request
+#[derive(Debug, Default, Clone)]
+struct FilterCommitments {
+    accounts: HashMap<String, CommitmentLevel>,
+    slots: HashMap<String, CommitmentLevel>,
+    transactions: HashMap<String, CommitmentLevel>,
+    transactions_status: HashMap<String, CommitmentLevel>,
+    entries: HashMap<String, CommitmentLevel>,
+    blocks: HashMap<String, CommitmentLevel>,
+    blocks_meta: HashMap<String, CommitmentLevel>,
+}
+
+impl FilterCommitments {
+    fn new(config: &SubscribeRequest) -> FilterResult<Self> {
+        Ok(Self {
+            accounts: Self::decode(config.accounts.iter().map(|(k, f)| (k, f.commitment)))?,
+            slots: Self::decode(config.slots.iter().map(|(k, f)| (k, f.commitment)))?,
+            transactions: Self::decode(config.transactions.iter().map(|(k, f)| (k, f.commitment)))?,
+            transactions_status: Self::decode(
+                config
+                    .transactions_status
+                    .iter()
+                    .map(|(k, f)| (k, f.commitment)),
+            )?,
+            entries: Self::decode(config.entry.iter().map(|(k, f)| (k, f.commitment)))?,
+            blocks: Self::decode(config.blocks.iter().map(|(k, f)| (k, f.commitment)))?,
+            blocks_meta: Self::decode(config.blocks_meta.iter().map(|(k, f)| (k, f.commitment)))?,
+        })
+    }
+
+    fn decode<'a>(
+        configs: impl Iterator<Item = (&'a String, Option<i32>)>,
+    ) -> FilterResult<HashMap<String, CommitmentLevel>> {
+        configs
+            .filter_map(|(name, commitment)| {
+                commitment.map(|commitment| {
+                    Filter::decode_commitment(Some(commitment))
+                        .map(|commitment| (name.clone(), commitment))
+                })
+            })
+            .collect()
+    }
+
+    fn remove(&mut self, remove: &SubscribeRequestFilterDeltaRemove) {
+        for (map, names) in [
+            (&mut self.accounts, &remove.accounts),
+            (&mut self.slots, &remove.slots),
+            (&mut self.transactions, &remove.transactions),
+            (&mut self.transactions_status, &remove.transactions_status),
+            (&mut self.entries, &remove.entry),
+            (&mut self.blocks, &remove.blocks),
+            (&mut self.blocks_meta, &remove.blocks_meta),
+        ] {
+            for name in names {
+                map.remove(name);
+            }
+        }
+    }
+
+    fn contains(&self, commitment: CommitmentLevel) -> bool {
+        [
+            &self.accounts,
+            &self.slots,
+            &self.transactions,
+            &self.transactions_status,
+            &self.entries,
+            &self.blocks,
+            &self.blocks_meta,
+        ]
+        .into_iter()
+        .any(|map| map.values().any(|value| *value == commitment))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FilterCommitmentMatch<'a> {
+    commitments: &'a HashMap<String, CommitmentLevel>,
+    default: CommitmentLevel,
+    commitment: Option<CommitmentLevel>,
+}
+
+impl<'a> FilterCommitmentMatch<'a> {
+    fn is_match(&self, name: &str) -> bool {
+        match self.commitment {
+            Some(commitment) => {
+                self.commitments.get(name).copied().unwrap_or(self.default) == commitment
+            }
+            None => true,
+        }
+    }
+
+    // drop names of filters with other commitment and updates without names
+    fn retain(&self, updates: &mut FilteredUpdates) {
+        let Some(commitment) = self.commitment else {
+            return;
+        };
+        if self.commitments.is_empty() {
+            if commitment != self.default {
+                updates.clear();
+            }
+            return;
+        }
+        updates.retain(|update| {
+            update.filters.retain(|name| self.is_match(name.as_ref()));
+            !update.filters.is_empty()
+        });
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 struct FilterAccounts {
     nonempty_txn_signature: Vec<(FilterName, Option<bool>)>,
@@ -258,6 +652,8 @@ struct FilterAccounts {
     owner: HashMap<Pubkey, HashSet<FilterName>>,
     owner_required: HashSet<FilterName>,
     filters: Vec<(FilterName, FilterAccountsState)>,
+    json_parsed: Option<FilterAccountsJsonParsed>,
+    data_slice: HashMap<FilterName, FilterAccountsDataSlice>,
 }
 
 impl FilterAccounts {
@@ -265,6 +661,7 @@ impl FilterAccounts {
         configs: &HashMap<String, SubscribeRequestFilterAccounts>,
         limits: &FilterLimitsAccounts,
         names: &mut FilterNames,
+        mint_decimals: &FilterMintDecimals,
     ) -> FilterResult<Self> {
         FilterLimits::check_max(configs.len(), limits.max)?;
 
@@ -300,8 +697,41 @@ impl FilterAccounts {
                 Filter::decode_pubkeys(&filter.owner, &limits.owner_reject),
             )?;
 
-            this.filters
-                .push((names.get(name)?, FilterAccountsState::new(&filter.filters)?));
+            let state = FilterAccountsState::new(&filter.filters, limits)?;
+            if state.lamports_delta.is_some()
+                && filter.account.is_empty()
+                && filter.owner.is_empty()
+            {
+                return Err(FilterError::CreateAccountState(
+                    "lamports_delta requires account or owner filter",
+                ));
+            }
+            this.filters.push((names.get(name)?, state));
+
+            if let Some(data_slice) = &filter.data_slice {
+                this.data_slice.insert(
+                    names.get(name)?,
+                    FilterAccountsDataSlice::new(&data_slice.slices, limits.data_slice_max)?,
+                );
+            }
+
+            let encoding = filter
+                .account_encoding
+                .unwrap_or(AccountEncoding::Raw as i32);
+            let raw = match AccountEncoding::try_from(encoding)
+                .map_err(|_error| FilterError::InvalidAccountEncoding { encoding })?
+            {
+                AccountEncoding::Raw => continue,
+                AccountEncoding::JsonParsed => false,
+                AccountEncoding::RawAndJsonParsed => true,
+            };
+            if !limits.json_parsed {
+                return Err(FilterError::CreateAccountsEncodingNotAllowed("json_parsed"));
+            }
+            this.json_parsed
+                .get_or_insert_with(|| FilterAccountsJsonParsed::new(mint_decimals.clone()))
+                .filters
+                .insert(names.get(name)?, raw);
         }
         Ok(this)
     }
@@ -326,140 +756,691 @@ impl FilterAccounts {
         Ok(required)
     }
 
+    fn get_name(&self, name: &str) -> Option<&FilterName> {
+        self.filters
+            .iter()
+            .map(|(filter_name, _state)| filter_name)
+            .find(|filter_name| filter_name.as_ref() == name)
+    }
+
+    fn create_delta(
+        &self,
+        name: &str,
+        delta: &SubscribeRequestFilterDeltaAccounts,
+        limits: &FilterLimitsAccounts,
+    ) -> FilterResult<FilterAccountsDelta> {
+        let Some((name, state)) = self
+            .filters
+            .iter()
+            .find(|(filter_name, _state)| filter_name.as_ref() == name)
+        else {
+            return Err(FilterError::DeltaFilterNotFound {
+                kind: "accounts",
+                name: name.to_owned(),
+            });
+        };
+
+        let account = FilterDeltaPubkeys::new(
+            &delta.account_add,
+            &delta.account_remove,
+            &limits.account_reject,
+        )?;
+        let owner =
+            FilterDeltaPubkeys::new(&delta.owner_add, &delta.owner_remove, &limits.owner_reject)?;
+        let account_len = account.len_after(&self.account, name);
+        let owner_len = owner.len_after(&self.owner, name);
+
+        FilterLimits::check_any(account_len == 0 && owner_len == 0, limits.any)?;
+        FilterLimits::check_pubkey_max(account_len, limits.account_max)?;
+        FilterLimits::check_pubkey_max(owner_len, limits.owner_max)?;
+        if state.lamports_delta.is_some() && account_len == 0 && owner_len == 0 {
+            return Err(FilterError::CreateAccountState(
+                "lamports_delta requires account or owner filter",
+            ));
+        }
+
+        Ok(FilterAccountsDelta {
+            name: name.clone(),
+            account,
+            account_required: account_len > 0,
+            owner,
+            owner_required: owner_len > 0,
+        })
+    }
+
+    fn apply_delta(&mut self, delta: FilterAccountsDelta) {
+        delta.account.apply_map(&mut self.account, &delta.name);
+        if delta.account_required {
+            self.account_required.insert(delta.name.clone());
+        } else {
+            self.account_required.remove(&delta.name);
+        }
+
+        delta.owner.apply_map(&mut self.owner, &delta.name);
+        if delta.owner_required {
+            self.owner_required.insert(delta.name);
+        } else {
+            self.owner_required.remove(&delta.name);
+        }
+    }
+
+    fn remove(&mut self, name: &str) {
+        self.nonempty_txn_signature
+            .retain(|(filter_name, _value)| filter_name.as_ref() != name);
+        self.nonempty_txn_signature_required.remove(name);
+        for map in [&mut self.account, &mut self.owner] {
+            map.retain(|_pubkey, names| {
+                names.remove(name);
+                !names.is_empty()
+            });
+        }
+        self.account_required.remove(name);
+        self.owner_required.remove(name);
+        self.filters
+            .retain(|(filter_name, _state)| filter_name.as_ref() != name);
+        if let Some(json_parsed) = &mut self.json_parsed {
+            json_parsed.filters.remove(name);
+            if json_parsed.filters.is_empty() {
+                self.json_parsed = None;
+            }
+        }
+        self.data_slice.remove(name);
+    }
+
     fn get_updates(
         &self,
         message: &MessageAccount,
         accounts_data_slice: &FilterAccountsDataSlice,
+        accounts_diff: Option<&FilterAccountsDiff>,
+        commitment: FilterCommitmentMatch<'_>,
     ) -> FilteredUpdates {
-        let mut filter = FilterAccountsMatch::new(self);
+        let mut filter = FilterAccountsMatch::new(self, commitment);
         filter.match_txn_signature(&message.account.txn_signature);
         filter.match_account(&message.account.pubkey);
         filter.match_owner(&message.account.owner);
-        filter.match_data_lamports(&message.account.data, message.account.lamports);
+        filter.match_data_lamports(
+            &message.account.pubkey,
+            &message.account.data,
+            message.account.lamports,
+        );
         let filters = filter.get_filters();
-        filtered_updates_once_owned!(
-            filters,
-            FilteredUpdateOneof::account(message, accounts_data_slice.clone())
-        )
-    }
-}
-
-#[derive(Debug, Default, Clone)]
-struct FilterAccountsState {
-    memcmp: Vec<(usize, Vec<u8>)>,
-    datasize: Option<usize>,
-    token_account_state: bool,
-    lamports: Vec<FilterAccountsLamports>,
-}
+        if filters.is_empty() {
+            return FilteredUpdates::new();
+        }
+        if let Some(accounts_diff) = accounts_diff {
+            return filtered_updates_once_owned!(filters, accounts_diff.get_update(message));
+        }
 
-impl FilterAccountsState {
-    fn new(filters: &[SubscribeRequestFilterAccountsFilter]) -> FilterResult<Self> {
-        const MAX_FILTERS: usize = 4;
-        const MAX_DATA_SIZE: usize = 128;
-        const MAX_DATA_BASE58_SIZE: usize = 175;
-        const MAX_DATA_BASE64_SIZE: usize = 172;
+        // one update per distinct data slice, with names of filters requested it
+        self.group_by_data_slice(filters, accounts_data_slice)
+            .into_iter()
+            .map(|(data_slice, filters)| {
+                let update = match &self.json_parsed {
+                    Some(json_parsed) => json_parsed.get_update(message, &filters, &data_slice),
+                    None => FilteredUpdateOneof::account(message, data_slice),
+                };
+                FilteredUpdate::new(filters, update)
+            })
+            .collect()
+    }
 
-        if filters.len() > MAX_FILTERS {
-            return Err(FilterError::CreateAccountStateMaxFilters { max: MAX_FILTERS });
+    fn group_by_data_slice(
+        &self,
+        filters: FilteredUpdateFilters,
+        accounts_data_slice: &FilterAccountsDataSlice,
+    ) -> Vec<(FilterAccountsDataSlice, FilteredUpdateFilters)> {
+        if self.data_slice.is_empty() {
+            return vec![(accounts_data_slice.clone(), filters)];
         }
 
-        let mut this = Self::default();
-        for filter in filters {
-            match &filter.filter {
-                Some(AccountsFilterDataOneof::Memcmp(memcmp)) => {
-                    let data = match &memcmp.data {
-                        Some(AccountsFilterMemcmpOneof::Bytes(data)) => data.clone(),
-                        Some(AccountsFilterMemcmpOneof::Base58(data)) => {
-                            if data.len() > MAX_DATA_BASE58_SIZE {
-                                return Err(FilterError::CreateAccountState("data too large"));
-                            }
-                            bs58::decode(data)
-                                .into_vec()
-                                .map_err(|_| FilterError::CreateAccountState("invalid base58"))?
-                        }
-                        Some(AccountsFilterMemcmpOneof::Base64(data)) => {
-                            if data.len() > MAX_DATA_BASE64_SIZE {
-                                return Err(FilterError::CreateAccountState("data too large"));
-                            }
-                            base64_engine
-                                .decode(data)
-                                .map_err(|_| FilterError::CreateAccountState("invalid base64"))?
-                        }
-                        None => {
-                            return Err(FilterError::CreateAccountState(
-                                "data for memcmp should be defined",
-                            ))
-                        }
-                    };
-                    if data.len() > MAX_DATA_SIZE {
-                        return Err(FilterError::CreateAccountState("data too large"));
-                    }
-                    this.memcmp.push((memcmp.offset as usize, data));
-                }
-                Some(AccountsFilterDataOneof::Datasize(datasize)) => {
-                    if this.datasize.replace(*datasize as usize).is_some() {
-                        return Err(FilterError::CreateAccountState(
-                            "datasize used more than once",
-                        ));
-                    }
-                }
-                Some(AccountsFilterDataOneof::TokenAccountState(value)) => {
-                    if !value {
-                        return Err(FilterError::CreateAccountState(
-                            "token_account_state only allowed to be true",
-                        ));
-                    }
-                    this.token_account_state = true;
-                }
-                Some(AccountsFilterDataOneof::Lamports(
-                    SubscribeRequestFilterAccountsFilterLamports { cmp },
-                )) => {
-                    let Some(cmp) = cmp else {
-                        return Err(FilterError::CreateAccountState(
-                            "cmp for lamports should be defined",
-                        ));
-                    };
-                    this.lamports.push(cmp.into());
-                }
+        let mut groups: Vec<(FilterAccountsDataSlice, FilteredUpdateFilters)> = vec![];
+        for name in filters {
+            let data_slice = self.data_slice.get(&name).unwrap_or(accounts_data_slice);
+            match groups.iter_mut().find(|(value, _)| value == data_slice) {
+                Some((_, filters)) => filters.push(name),
                 None => {
-                    return Err(FilterError::CreateAccountState("filter should be defined"));
+                    let mut filters = FilteredUpdateFilters::new();
+                    filters.push(name);
+                    groups.push((data_slice.clone(), filters));
                 }
             }
         }
-        Ok(this)
+        groups
     }
+}
 
-    fn is_empty(&self) -> bool {
-        self.memcmp.is_empty()
-            && self.datasize.is_none()
-            && !self.token_account_state
-            && self.lamports.is_empty()
+#[derive(Debug)]
+struct FilterAccountsDelta {
+    name: FilterName,
+    account: FilterDeltaPubkeys,
+    account_required: bool,
+    owner: FilterDeltaPubkeys,
+    owner_required: bool,
+}
+
+// Pubkeys in both lists are kept in the filter
+#[derive(Debug)]
+struct FilterDeltaPubkeys {
+    add: HashSet<Pubkey>,
+    remove: HashSet<Pubkey>,
+}
+
+impl FilterDeltaPubkeys {
+    fn new(add: &[String], remove: &[String], limit: &HashSet<Pubkey>) -> FilterResult<Self> {
+        let add = Filter::decode_pubkeys_into_set(add, limit)?;
+        let mut remove = Filter::decode_pubkeys_into_set(remove, &HashSet::new())?;
+        remove.retain(|pubkey| !add.contains(pubkey));
+        Ok(Self { add, remove })
     }
 
-    fn is_match(&self, data: &[u8], lamports: u64) -> bool {
-        if matches!(self.datasize, Some(datasize) if data.len() != datasize) {
-            return false;
+    fn len_after_set(&self, set: &HashSet<Pubkey>) -> usize {
+        set.len() - self.remove.intersection(set).count() + self.add.difference(set).count()
+    }
+
+    fn len_after(&self, map: &HashMap<Pubkey, HashSet<FilterName>>, name: &FilterName) -> usize {
+        let contains = |pubkey: &Pubkey| map.get(pubkey).is_some_and(|names| names.contains(name));
+        map.values().filter(|names| names.contains(name)).count()
+            - self.remove.iter().filter(|pubkey| contains(pubkey)).count()
+            + self.add.iter().filter(|pubkey| !contains(pubkey)).count()
+    }
+
+    fn apply_set(self, set: &mut HashSet<Pubkey>) {
+        for pubkey in self.remove.iter() {
+            set.remove(pubkey);
         }
-        if self.token_account_state && !TokenAccount::valid_account_data(data) {
-            return false;
+        set.extend(self.add);
+    }
+
+    fn apply_map(self, map: &mut HashMap<Pubkey, HashSet<FilterName>>, name: &FilterName) {
+        for pubkey in self.remove {
+            if let Entry::Occupied(mut entry) = map.entry(pubkey) {
+                entry.get_mut().remove(name);
+                if entry.get().is_empty() {
+                    entry.remove();
+                }
+            }
         }
-        if self.lamports.iter().any(|f| !f.is_match(lamports)) {
-            return false;
+        for pubkey in self.add {
+            map.entry(pubkey).or_default().insert(name.clone());
         }
-        for (offset, bytes) in self.memcmp.iter() {
-            if data.len() < *offset + bytes.len() {
-                return false;
-            }
-            let data = &data[*offset..*offset + bytes.len()];
-            if data != bytes {
-                return false;
+    }
+}
+
+/// Decimals of spl-token mints, token accounts can not be parsed without them.
+/// Shared by filters of all clients and filled from every account update,
+/// so the decimals survive filter replacement and do not depend on filters.
+#[derive(Debug, Clone)]
+pub struct FilterMintDecimals(Arc<Mutex<FilterAccountsCache<u8>>>);
+
+impl FilterMintDecimals {
+    pub fn new(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(FilterAccountsCache::new(capacity))))
+    }
+
+    pub fn update(&self, account: &MessageAccountInfo) {
+        if is_known_spl_token_id(&account.owner) {
+            if let Ok(mint) = StateWithExtensions::<Mint>::unpack(&account.data) {
+                let mut cache = self.0.lock().expect("poisoned mint cache");
+                cache.update(account.pubkey, mint.base.decimals);
             }
         }
-        true
+    }
+
+    fn get(&self, mint: &Pubkey) -> Option<u8> {
+        let cache = self.0.lock().expect("poisoned mint cache");
+        cache.get(mint).copied()
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone)]
+struct FilterAccountsJsonParsed {
+    // filters with parsed encoding, value is true if raw data should be sent too
+    filters: HashMap<FilterName, bool>,
+    mint_decimals: FilterMintDecimals,
+}
+
+impl FilterAccountsJsonParsed {
+    fn new(mint_decimals: FilterMintDecimals) -> Self {
+        Self {
+            filters: HashMap::new(),
+            mint_decimals,
+        }
+    }
+
+    fn get_update(
+        &self,
+        message: &MessageAccount,
+        filters: &FilteredUpdateFilters,
+        accounts_data_slice: &FilterAccountsDataSlice,
+    ) -> FilteredUpdateOneof {
+        let mut parsed_required = false;
+        let mut raw_required = false;
+        for name in filters.iter() {
+            match self.filters.get(name) {
+                Some(raw) => {
+                    parsed_required = true;
+                    raw_required |= *raw;
+                }
+                None => raw_required = true,
+            }
+        }
+
+        let parsed = if parsed_required {
+            self.parse(&message.account)
+        } else {
+            None
+        };
+        let data_slice = if raw_required || parsed.is_none() {
+            Some(accounts_data_slice.clone())
+        } else {
+            None
+        };
+        FilteredUpdateOneof::account_encoded(message, data_slice, parsed.map(Arc::new))
+    }
+
+    fn parse(&self, account: &MessageAccountInfo) -> Option<SubscribeUpdateAccountInfoParsed> {
+        let mut additional_data = AccountAdditionalDataV2::default();
+        if is_known_spl_token_id(&account.owner) {
+            if let Some(mint) = TokenAccount::unpack_account_mint(&account.data) {
+                additional_data.spl_token_additional_data = self
+                    .mint_decimals
+                    .get(mint)
+                    .map(SplTokenAdditionalData::with_decimals);
+            }
+        }
+
+        parse_account_data_v2(
+            &account.pubkey,
+            &account.owner,
+            &account.data,
+            Some(additional_data),
+        )
+        .ok()
+        .map(|parsed| SubscribeUpdateAccountInfoParsed {
+            program: parsed.program,
+            json: parsed.parsed.to_string(),
+            space: parsed.space,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FilterAccountsDiff {
+    keyframe_interval: usize,
+    cache: Arc<Mutex<FilterAccountsDiffCache>>,
+}
+
+impl FilterAccountsDiff {
+    fn create(
+        config: &SubscribeRequest,
+        limits: &FilterLimitsAccounts,
+    ) -> FilterResult<Option<Self>> {
+        if !config.accounts_diff.unwrap_or(false) {
+            return Ok(None);
+        }
+        if !config.accounts_data_slice.is_empty()
+            || config.accounts.values().any(|filter| {
+                filter
+                    .data_slice
+                    .as_ref()
+                    .is_some_and(|data_slice| !data_slice.slices.is_empty())
+            })
+        {
+            return Err(FilterError::CreateAccountsDiffDataSlice);
+        }
+        if config.accounts.values().any(|filter| {
+            filter
+                .account_encoding
+                .is_some_and(|encoding| encoding != AccountEncoding::Raw as i32)
+        }) {
+            return Err(FilterError::CreateAccountsDiffEncoding);
+        }
+        // cache of sent accounts is shared by all account filters
+        let commitment = Filter::decode_commitment(config.commitment)?;
+        for filter in config.accounts.values() {
+            if filter.commitment.is_some()
+                && Filter::decode_commitment(filter.commitment)? != commitment
+            {
+                return Err(FilterError::CreateAccountsDiffCommitment);
+            }
+        }
+
+        Ok(Some(Self {
+            keyframe_interval: limits.diff_keyframe_interval,
+            cache: Arc::new(Mutex::new(FilterAccountsDiffCache::new(
+                limits.diff_cache_size_max,
+            ))),
+        }))
+    }
+
+    // returns full account as keyframe if previous data is unknown, interval is reached or
+    // patches are not smaller than data
+    fn get_update(&self, message: &MessageAccount) -> FilteredUpdateOneof {
+        let mut cache = self.cache.lock().expect("poisoned accounts diff cache");
+
+        let mut update = None;
+        let mut updates_since_keyframe = 0;
+        if let Some((prev, updates)) = cache.get(&message.account.pubkey) {
+            if updates.saturating_add(1) < self.keyframe_interval {
+                let patches = Self::create_patches(&prev.data, &message.account.data);
+                if Self::patches_size(&patches) < message.account.data.len() {
+                    update = Some(FilteredUpdateOneof::account_diff(message, patches));
+                    updates_since_keyframe = updates + 1;
+                }
+            }
+        }
+        cache.insert(Arc::clone(&message.account), updates_since_keyframe);
+
+        update.unwrap_or_else(|| {
+            FilteredUpdateOneof::account(message, FilterAccountsDataSlice::default())
+        })
+    }
+
+    fn reset(&self, pubkeys: impl IntoIterator<Item = Pubkey>) {
+        let mut cache = self.cache.lock().expect("poisoned accounts diff cache");
+        for pubkey in pubkeys {
+            cache.set_updates(&pubkey, self.keyframe_interval);
+        }
+    }
+
+    fn create_patches(prev: &[u8], data: &[u8]) -> Vec<Range<usize>> {
+        // compare by chunks first, most of data usually is not changed
+        const CHUNK_SIZE: usize = 64;
+        // equal bytes between changes are cheaper to resend than a new patch
+        const PATCH_GAP_MAX: usize = 16;
+
+        let common = prev.len().min(data.len());
+        let mut patches: Vec<Range<usize>> = vec![];
+        let mut offset = 0;
+        while offset < common {
+            let chunk_end = (offset + CHUNK_SIZE).min(common);
+            if prev[offset..chunk_end] != data[offset..chunk_end] {
+                for index in offset..chunk_end {
+                    if prev[index] != data[index] {
+                        match patches.last_mut() {
+                            Some(patch) if index - patch.end < PATCH_GAP_MAX => {
+                                patch.end = index + 1
+                            }
+                            _ => patches.push(index..index + 1),
+                        }
+                    }
+                }
+            }
+            offset = chunk_end;
+        }
+        if data.len() > common {
+            match patches.last_mut() {
+                Some(patch) if common - patch.end < PATCH_GAP_MAX => patch.end = data.len(),
+                _ => patches.push(common..data.len()),
+            }
+        }
+        patches
+    }
+
+    fn patches_size(patches: &[Range<usize>]) -> usize {
+        // approximate size of tags, offset and length
+        const PATCH_OVERHEAD: usize = 8;
+
+        patches
+            .iter()
+            .map(|patch| patch.len() + PATCH_OVERHEAD)
+            .sum()
+    }
+}
+
+// last sent account per pubkey with number of diffs since keyframe,
+// oldest inserted pubkey evicted when total size of data is over the limit
+#[derive(Debug)]
+struct FilterAccountsDiffCache {
+    size_max: usize,
+    size: usize,
+    accounts: HashMap<Pubkey, (Arc<MessageAccountInfo>, usize)>,
+    order: VecDeque<Pubkey>,
+}
+
+impl FilterAccountsDiffCache {
+    fn new(size_max: usize) -> Self {
+        Self {
+            size_max,
+            size: 0,
+            accounts: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&self, pubkey: &Pubkey) -> Option<(&MessageAccountInfo, usize)> {
+        self.accounts
+            .get(pubkey)
+            .map(|(account, updates)| (account.as_ref(), *updates))
+    }
+
+    fn set_updates(&mut self, pubkey: &Pubkey, updates: usize) {
+        if let Some((_account, value)) = self.accounts.get_mut(pubkey) {
+            *value = updates;
+        }
+    }
+
+    fn insert(&mut self, account: Arc<MessageAccountInfo>, updates: usize) {
+        let pubkey = account.pubkey;
+        self.size += account.data.len();
+        match self.accounts.insert(pubkey, (account, updates)) {
+            Some((prev, _updates)) => self.size -= prev.data.len(),
+            None => self.order.push_back(pubkey),
+        }
+
+        while self.size > self.size_max {
+            let Some(pubkey) = self.order.pop_front() else {
+                break;
+            };
+            if let Some((account, _updates)) = self.accounts.remove(&pubkey) {
+                self.size -= account.data.len();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct FilterAccountsState {
+    memcmp: Vec<(usize, Vec<u8>)>,
+    datasize: Option<usize>,
+    datasize_min: Option<usize>,
+    datasize_max: Option<usize>,
+    token_account_state: bool,
+    lamports: Vec<FilterAccountsLamports>,
+    lamports_delta: Option<FilterAccountsLamportsDelta>,
+    any_of: Vec<Vec<FilterAccountsState>>,
+    not: Vec<FilterAccountsState>,
+}
+
+impl FilterAccountsState {
+    fn new(
+        filters: &[SubscribeRequestFilterAccountsFilter],
+        limits: &FilterLimitsAccounts,
+    ) -> FilterResult<Self> {
+        const MAX_FILTERS: usize = 4;
+
+        if filters.len() > MAX_FILTERS {
+            return Err(FilterError::CreateAccountStateMaxFilters { max: MAX_FILTERS });
+        }
+
+        let mut total = 0;
+        Self::new_nested(filters, limits, 0, &mut total)
+    }
+
+    fn new_nested(
+        filters: &[SubscribeRequestFilterAccountsFilter],
+        limits: &FilterLimitsAccounts,
+        depth: usize,
+        total: &mut usize,
+    ) -> FilterResult<Self> {
+        FilterLimits::check_depth_max(depth, limits.filters_depth_max)?;
+        *total += filters.len();
+        FilterLimits::check_max(*total, limits.filters_total_max)?;
+
+        let mut this = Self::default();
+        for filter in filters {
+            match &filter.filter {
+                Some(AccountsFilterDataOneof::Memcmp(memcmp)) => {
+                    this.memcmp.push(
+                        Filter::decode_memcmp(memcmp).map_err(FilterError::CreateAccountState)?,
+                    );
+                }
+                Some(AccountsFilterDataOneof::Datasize(datasize)) => {
+                    if this.datasize.replace(*datasize as usize).is_some() {
+                        return Err(FilterError::CreateAccountState(
+                            "datasize used more than once",
+                        ));
+                    }
+                }
+                Some(AccountsFilterDataOneof::TokenAccountState(value)) => {
+                    if !value {
+                        return Err(FilterError::CreateAccountState(
+                            "token_account_state only allowed to be true",
+                        ));
+                    }
+                    this.token_account_state = true;
+                }
+                Some(AccountsFilterDataOneof::Lamports(
+                    SubscribeRequestFilterAccountsFilterLamports { cmp },
+                )) => {
+                    let Some(cmp) = cmp else {
+                        return Err(FilterError::CreateAccountState(
+                            "cmp for lamports should be defined",
+                        ));
+                    };
+                    this.lamports.push(cmp.into());
+                }
+                Some(AccountsFilterDataOneof::AnyOf(
+                    SubscribeRequestFilterAccountsFilterAnyOf { groups },
+                )) => {
+                    if groups.is_empty() {
+                        return Err(FilterError::CreateAccountState(
+                            "any_of should contain at least one group",
+                        ));
+                    }
+                    let mut any_of = Vec::with_capacity(groups.len());
+                    for group in groups {
+                        if group.filters.is_empty() {
+                            return Err(FilterError::CreateAccountState(
+                                "group in any_of should not be empty",
+                            ));
+                        }
+                        any_of.push(Self::new_nested(&group.filters, limits, depth + 1, total)?);
+                    }
+                    this.any_of.push(any_of);
+                }
+                Some(AccountsFilterDataOneof::Not(SubscribeRequestFilterAccountsFilterNot {
+                    filters,
+                })) => {
+                    if filters.is_empty() {
+                        return Err(FilterError::CreateAccountState(
+                            "not should contain at least one filter",
+                        ));
+                    }
+                    this.not
+                        .push(Self::new_nested(filters, limits, depth + 1, total)?);
+                }
+                Some(AccountsFilterDataOneof::DatasizeMin(datasize)) => {
+                    if this.datasize_min.replace(*datasize as usize).is_some() {
+                        return Err(FilterError::CreateAccountState(
+                            "datasize_min used more than once",
+                        ));
+                    }
+                }
+                Some(AccountsFilterDataOneof::DatasizeMax(datasize)) => {
+                    if this.datasize_max.replace(*datasize as usize).is_some() {
+                        return Err(FilterError::CreateAccountState(
+                            "datasize_max used more than once",
+                        ));
+                    }
+                }
+                Some(AccountsFilterDataOneof::LamportsDelta(value)) => {
+                    // stateful, evaluation of nested filters can be short-circuited
+                    if depth > 0 {
+                        return Err(FilterError::CreateAccountState(
+                            "lamports_delta is not allowed in nested filters",
+                        ));
+                    }
+                    if this.lamports_delta.is_some() {
+                        return Err(FilterError::CreateAccountState(
+                            "lamports_delta used more than once",
+                        ));
+                    }
+                    this.lamports_delta = Some(FilterAccountsLamportsDelta::new(
+                        *value,
+                        limits.lamports_delta_cache_max,
+                    ));
+                }
+                None => {
+                    return Err(FilterError::CreateAccountState("filter should be defined"));
+                }
+            }
+        }
+        Ok(this)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.memcmp.is_empty()
+            && self.datasize.is_none()
+            && self.datasize_min.is_none()
+            && self.datasize_max.is_none()
+            && !self.token_account_state
+            && self.lamports.is_empty()
+            && self.lamports_delta.is_none()
+            && self.any_of.is_empty()
+            && self.not.is_empty()
+    }
+
+    fn is_match(&self, pubkey: &Pubkey, data: &[u8], lamports: u64) -> bool {
+        // checked first, cache should be updated on every call
+        if let Some(lamports_delta) = &self.lamports_delta {
+            if !lamports_delta.is_match(pubkey, lamports) {
+                return false;
+            }
+        }
+        if matches!(self.datasize, Some(datasize) if data.len() != datasize) {
+            return false;
+        }
+        if matches!(self.datasize_min, Some(datasize) if data.len() < datasize) {
+            return false;
+        }
+        if matches!(self.datasize_max, Some(datasize) if data.len() > datasize) {
+            return false;
+        }
+        if self.token_account_state && !TokenAccount::valid_account_data(data) {
+            return false;
+        }
+        if self.lamports.iter().any(|f| !f.is_match(lamports)) {
+            return false;
+        }
+        for (offset, bytes) in self.memcmp.iter() {
+            if data.len() < *offset + bytes.len() {
+                return false;
+            }
+            let data = &data[*offset..*offset + bytes.len()];
+            if data != bytes {
+                return false;
+            }
+        }
+        if !self.any_of.iter().all(|groups| {
+            groups
+                .iter()
+                .any(|group| group.is_match(pubkey, data, lamports))
+        }) {
+            return false;
+        }
+        if self
+            .not
+            .iter()
+            .any(|filter| filter.is_match(pubkey, data, lamports))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum FilterAccountsLamports {
     Eq(u64),
     Ne(u64),
@@ -489,9 +1470,72 @@ impl FilterAccountsLamports {
     }
 }
 
+#[derive(Debug, Clone)]
+struct FilterAccountsLamportsDelta {
+    min: u64,
+    cache: Arc<Mutex<FilterAccountsCache<u64>>>,
+}
+
+impl FilterAccountsLamportsDelta {
+    fn new(min: u64, capacity: usize) -> Self {
+        Self {
+            min,
+            cache: Arc::new(Mutex::new(FilterAccountsCache::new(capacity))),
+        }
+    }
+
+    fn is_match(&self, pubkey: &Pubkey, lamports: u64) -> bool {
+        let mut cache = self.cache.lock().expect("poisoned lamports cache");
+        cache
+            .update(*pubkey, lamports)
+            .is_some_and(|prev| prev.abs_diff(lamports) > self.min)
+    }
+}
+
+// last seen value per pubkey, oldest inserted pubkey evicted on overflow
+#[derive(Debug)]
+struct FilterAccountsCache<T> {
+    capacity: usize,
+    values: HashMap<Pubkey, T>,
+    order: VecDeque<Pubkey>,
+}
+
+impl<T> FilterAccountsCache<T> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&self, pubkey: &Pubkey) -> Option<&T> {
+        self.values.get(pubkey)
+    }
+
+    fn update(&mut self, pubkey: Pubkey, value: T) -> Option<T> {
+        if let Some(prev) = self.values.get_mut(&pubkey) {
+            return Some(std::mem::replace(prev, value));
+        }
+
+        if self.capacity == 0 {
+            return None;
+        }
+        while self.order.len() >= self.capacity {
+            if let Some(pubkey) = self.order.pop_front() {
+                self.values.remove(&pubkey);
+            }
+        }
+        self.values.insert(pubkey, value);
+        self.order.push_back(pubkey);
+        None
+    }
+}
+
 #[derive(Debug)]
 struct FilterAccountsMatch<'a> {
     filter: &'a FilterAccounts,
+    commitment: FilterCommitmentMatch<'a>,
     nonempty_txn_signature: HashSet<&'a str>,
     account: HashSet<&'a str>,
     owner: HashSet<&'a str>,
@@ -499,9 +1543,10 @@ struct FilterAccountsMatch<'a> {
 }
 
 impl<'a> FilterAccountsMatch<'a> {
-    fn new(filter: &'a FilterAccounts) -> Self {
+    fn new(filter: &'a FilterAccounts, commitment: FilterCommitmentMatch<'a>) -> Self {
         Self {
             filter,
+            commitment,
             nonempty_txn_signature: Default::default(),
             account: Default::default(),
             owner: Default::default(),
@@ -539,9 +1584,22 @@ impl<'a> FilterAccountsMatch<'a> {
         Self::extend(&mut self.owner, &self.filter.owner, pubkey)
     }
 
-    fn match_data_lamports(&mut self, data: &[u8], lamports: u64) {
+    fn match_data_lamports(&mut self, pubkey: &Pubkey, data: &[u8], lamports: u64) {
         for (name, filter) in self.filter.filters.iter() {
-            if filter.is_match(data, lamports) {
+            // lamports are remembered per commitment of the filter
+            if !self.commitment.is_match(name.as_ref()) {
+                continue;
+            }
+            // remember lamports only for accounts matched by account / owner
+            if filter.lamports_delta.is_some()
+                && ((self.filter.account_required.contains(name)
+                    && !self.account.contains(name.as_ref()))
+                    || (self.filter.owner_required.contains(name)
+                        && !self.owner.contains(name.as_ref())))
+            {
+                continue;
+            }
+            if filter.is_match(pubkey, data, lamports) {
                 self.data.insert(name.as_ref());
             }
         }
@@ -555,6 +1613,10 @@ impl<'a> FilterAccountsMatch<'a> {
                 let name = filter_name.as_ref();
                 let af = &self.filter;
 
+                if !self.commitment.is_match(name) {
+                    return None;
+                }
+
                 // If filter name in required but not in matched => return `false`
                 if af.nonempty_txn_signature_required.contains(name)
                     && !self.nonempty_txn_signature.contains(name)
@@ -649,12 +1711,164 @@ struct FilterTransactionsInner {
     account_include: HashSet<Pubkey>,
     account_exclude: HashSet<Pubkey>,
     account_required: HashSet<Pubkey>,
+    signer_include: HashSet<Pubkey>,
+    fee_payer: HashSet<Pubkey>,
+    program_include: HashSet<Pubkey>,
+    program_exclude: HashSet<Pubkey>,
+    inner_instructions: bool,
+    instruction_data: Vec<FilterTransactionsInstructionData>,
+    log_contains: Vec<String>,
+    log_regex: Option<RegexSet>,
+    token_balance: Vec<FilterTransactionsTokenBalance>,
+    compute_units_consumed: Vec<FilterTransactionsCmp>,
+    fee: Vec<FilterTransactionsCmp>,
+    mask: FilteredUpdateTransactionMask,
 }
 
-#[derive(Debug, Clone)]
-struct FilterTransactions {
-    filter_type: FilterTransactionsType,
-    filters: HashMap<FilterName, FilterTransactionsInner>,
+impl FilterTransactionsInner {
+    // true if only `account_*` sets can narrow the stream, used for `any` check after delta;
+    // `account_exclude` alone counts as narrowing same as on create, while `program_exclude`,
+    // `log_regex` and bounds of compute units / fee do not
+    fn is_accounts_only(&self) -> bool {
+        self.vote.is_none()
+            && self.failed.is_none()
+            && self.signer_include.is_empty()
+            && self.fee_payer.is_empty()
+            && self.program_include.is_empty()
+            && self.instruction_data.is_empty()
+            && self.log_contains.is_empty()
+            && self.token_balance.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct FilterTransactionsDelta {
+    name: FilterName,
+    account_include: FilterDeltaPubkeys,
+    account_exclude: FilterDeltaPubkeys,
+    account_required: FilterDeltaPubkeys,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FilterTransactionsCmp {
+    Eq(u64),
+    Ne(u64),
+    Lt(u64),
+    Gt(u64),
+}
+
+impl FilterTransactionsCmp {
+    fn decode(filters: &[SubscribeRequestFilterTransactionsCmp]) -> FilterResult<Vec<Self>> {
+        filters
+            .iter()
+            .map(|filter| match filter.cmp {
+                Some(TransactionsFilterCmp::Eq(value)) => Ok(Self::Eq(value)),
+                Some(TransactionsFilterCmp::Ne(value)) => Ok(Self::Ne(value)),
+                Some(TransactionsFilterCmp::Lt(value)) => Ok(Self::Lt(value)),
+                Some(TransactionsFilterCmp::Gt(value)) => Ok(Self::Gt(value)),
+                None => Err(FilterError::CreateTransactionsCmp(
+                    "cmp for transaction value should be defined",
+                )),
+            })
+            .collect()
+    }
+
+    // all predicates of one field should match, so `gt` + `lt` define a range
+    const fn is_match(self, value: u64) -> bool {
+        match self {
+            Self::Eq(expected) => value == expected,
+            Self::Ne(expected) => value != expected,
+            Self::Lt(max) => value < max,
+            Self::Gt(min) => value > min,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FilterTransactionsInstructionData {
+    program_id: Pubkey,
+    offset: usize,
+    data: Vec<u8>,
+}
+
+impl FilterTransactionsInstructionData {
+    fn new(filter: &SubscribeRequestFilterTransactionsInstructionData) -> FilterResult<Self> {
+        let Some(memcmp) = &filter.memcmp else {
+            return Err(FilterError::CreateTransactionsInstructionData(
+                "memcmp for instruction data should be defined",
+            ));
+        };
+        let (offset, data) = Filter::decode_memcmp(memcmp)
+            .map_err(FilterError::CreateTransactionsInstructionData)?;
+
+        Ok(Self {
+            program_id: filter.program_id.parse()?,
+            offset,
+            data,
+        })
+    }
+
+    fn is_match(&self, program_id: &Pubkey, data: &[u8]) -> bool {
+        self.program_id == *program_id
+            && data
+                .get(self.offset..)
+                .is_some_and(|data| data.starts_with(&self.data))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FilterTransactionsTokenBalance {
+    mint: String,
+    owner: Option<String>,
+    min_delta: u64,
+}
+
+impl FilterTransactionsTokenBalance {
+    fn new(filter: &SubscribeRequestFilterTransactionsTokenBalance) -> FilterResult<Self> {
+        // keep base58 strings, token balances in meta are already encoded
+        Ok(Self {
+            mint: Pubkey::from_str(&filter.mint)?.to_string(),
+            owner: filter
+                .owner
+                .as_ref()
+                .map(|owner| Pubkey::from_str(owner).map(|owner| owner.to_string()))
+                .transpose()?,
+            min_delta: filter.min_delta.unwrap_or(0).max(1),
+        })
+    }
+
+    fn is_match(&self, meta: &confirmed_block::TransactionStatusMeta) -> bool {
+        meta.pre_token_balances
+            .iter()
+            .chain(meta.post_token_balances.iter())
+            .filter(|balance| {
+                balance.mint == self.mint
+                    && self
+                        .owner
+                        .as_ref()
+                        .map_or(true, |owner| balance.owner == *owner)
+            })
+            .any(|balance| {
+                let pre = Self::get_amount(&meta.pre_token_balances, balance.account_index);
+                let post = Self::get_amount(&meta.post_token_balances, balance.account_index);
+                pre.abs_diff(post) >= self.min_delta
+            })
+    }
+
+    fn get_amount(balances: &[confirmed_block::TokenBalance], account_index: u32) -> u64 {
+        balances
+            .iter()
+            .find(|balance| balance.account_index == account_index)
+            .and_then(|balance| balance.ui_token_amount.as_ref())
+            .and_then(|amount| amount.amount.parse().ok())
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FilterTransactions {
+    filter_type: FilterTransactionsType,
+    filters: HashMap<FilterName, FilterTransactionsInner>,
 }
 
 impl FilterTransactions {
@@ -664,16 +1878,27 @@ impl FilterTransactions {
         filter_type: FilterTransactionsType,
         names: &mut FilterNames,
     ) -> FilterResult<Self> {
+        // limit for compiled regex and lazy DFA cache, protects from expensive patterns
+        const LOG_REGEX_SIZE_LIMIT: usize = 256 * 1024;
+
         FilterLimits::check_max(configs.len(), limits.max)?;
 
         let mut filters = HashMap::new();
         for (name, filter) in configs {
+            // `program_exclude`, `log_regex` (e.g. `.*`) and bounds of compute units / fee
+            // (e.g. `fee > 0`) alone do not narrow the stream, `account_exclude` does
             FilterLimits::check_any(
                 filter.vote.is_none()
                     && filter.failed.is_none()
                     && filter.account_include.is_empty()
                     && filter.account_exclude.is_empty()
-                    && filter.account_required.is_empty(),
+                    && filter.account_required.is_empty()
+                    && filter.signer_include.is_empty()
+                    && filter.fee_payer.is_empty()
+                    && filter.program_include.is_empty()
+                    && filter.instruction_data.is_empty()
+                    && filter.log_contains.is_empty()
+                    && filter.token_balance.is_empty(),
                 limits.any,
             )?;
             FilterLimits::check_pubkey_max(
@@ -688,6 +1913,31 @@ impl FilterTransactions {
                 filter.account_required.len(),
                 limits.account_required_max,
             )?;
+            FilterLimits::check_pubkey_max(filter.signer_include.len(), limits.signer_include_max)?;
+            FilterLimits::check_pubkey_max(filter.fee_payer.len(), limits.fee_payer_max)?;
+            FilterLimits::check_pubkey_max(
+                filter.program_include.len(),
+                limits.program_include_max,
+            )?;
+            FilterLimits::check_pubkey_max(
+                filter.program_exclude.len(),
+                limits.program_exclude_max,
+            )?;
+            FilterLimits::check_max(filter.instruction_data.len(), limits.instruction_data_max)?;
+            FilterLimits::check_max(filter.log_contains.len(), limits.log_contains_max)?;
+            for pattern in filter.log_contains.iter() {
+                FilterLimits::check_length_max(pattern.len(), limits.log_contains_length_max)?;
+            }
+            FilterLimits::check_max(filter.log_regex.len(), limits.log_regex_max)?;
+            for pattern in filter.log_regex.iter() {
+                FilterLimits::check_length_max(pattern.len(), limits.log_regex_length_max)?;
+            }
+            FilterLimits::check_max(filter.token_balance.len(), limits.token_balance_max)?;
+            FilterLimits::check_max(
+                filter.compute_units_consumed.len(),
+                limits.compute_units_consumed_cmp_max,
+            )?;
+            FilterLimits::check_max(filter.fee.len(), limits.fee_cmp_max)?;
 
             filters.insert(
                 names.get(name)?,
@@ -713,6 +1963,56 @@ impl FilterTransactions {
                         &filter.account_required,
                         &HashSet::new(),
                     )?,
+                    signer_include: Filter::decode_pubkeys_into_set(
+                        &filter.signer_include,
+                        &HashSet::new(),
+                    )?,
+                    fee_payer: Filter::decode_pubkeys_into_set(&filter.fee_payer, &HashSet::new())?,
+                    program_include: Filter::decode_pubkeys_into_set(
+                        &filter.program_include,
+                        &limits.program_include_reject,
+                    )?,
+                    program_exclude: Filter::decode_pubkeys_into_set(
+                        &filter.program_exclude,
+                        &HashSet::new(),
+                    )?,
+                    inner_instructions: filter.inner_instructions.unwrap_or(false),
+                    instruction_data: filter
+                        .instruction_data
+                        .iter()
+                        .map(FilterTransactionsInstructionData::new)
+                        .collect::<Result<_, _>>()?,
+                    log_contains: filter.log_contains.clone(),
+                    log_regex: if filter.log_regex.is_empty() {
+                        None
+                    } else {
+                        Some(
+                            RegexSetBuilder::new(&filter.log_regex)
+                                .size_limit(LOG_REGEX_SIZE_LIMIT)
+                                .dfa_size_limit(LOG_REGEX_SIZE_LIMIT)
+                                .build()?,
+                        )
+                    },
+                    token_balance: filter
+                        .token_balance
+                        .iter()
+                        .map(FilterTransactionsTokenBalance::new)
+                        .collect::<Result<_, _>>()?,
+                    compute_units_consumed: FilterTransactionsCmp::decode(
+                        &filter.compute_units_consumed,
+                    )?,
+                    fee: FilterTransactionsCmp::decode(&filter.fee)?,
+                    mask: match filter_type {
+                        FilterTransactionsType::Transaction => FilteredUpdateTransactionMask {
+                            logs: filter.include_logs.unwrap_or(true),
+                            inner_instructions: filter.include_inner_instructions.unwrap_or(true),
+                            token_balances: filter.include_token_balances.unwrap_or(true),
+                            rewards: filter.include_rewards.unwrap_or(true),
+                        },
+                        FilterTransactionsType::TransactionStatus => {
+                            FilteredUpdateTransactionMask::default()
+                        }
+                    },
                 },
             );
         }
@@ -722,71 +2022,298 @@ impl FilterTransactions {
         })
     }
 
+    const fn kind(&self) -> &'static str {
+        match self.filter_type {
+            FilterTransactionsType::Transaction => "transactions",
+            FilterTransactionsType::TransactionStatus => "transactions_status",
+        }
+    }
+
+    fn create_delta(
+        &self,
+        name: &str,
+        delta: &SubscribeRequestFilterDeltaTransactions,
+        limits: &FilterLimitsTransactions,
+    ) -> FilterResult<FilterTransactionsDelta> {
+        let Some((name, inner)) = self.filters.get_key_value(name) else {
+            return Err(FilterError::DeltaFilterNotFound {
+                kind: self.kind(),
+                name: name.to_owned(),
+            });
+        };
+
+        let account_include = FilterDeltaPubkeys::new(
+            &delta.account_include_add,
+            &delta.account_include_remove,
+            &limits.account_include_reject,
+        )?;
+        let account_exclude = FilterDeltaPubkeys::new(
+            &delta.account_exclude_add,
+            &delta.account_exclude_remove,
+            &HashSet::new(),
+        )?;
+        let account_required = FilterDeltaPubkeys::new(
+            &delta.account_required_add,
+            &delta.account_required_remove,
+            &HashSet::new(),
+        )?;
+        let account_include_len = account_include.len_after_set(&inner.account_include);
+        let account_exclude_len = account_exclude.len_after_set(&inner.account_exclude);
+        let account_required_len = account_required.len_after_set(&inner.account_required);
+
+        FilterLimits::check_any(
+            inner.is_accounts_only()
+                && account_include_len == 0
+                && account_exclude_len == 0
+                && account_required_len == 0,
+            limits.any,
+        )?;
+        FilterLimits::check_pubkey_max(account_include_len, limits.account_include_max)?;
+        FilterLimits::check_pubkey_max(account_exclude_len, limits.account_exclude_max)?;
+        FilterLimits::check_pubkey_max(account_required_len, limits.account_required_max)?;
+
+        Ok(FilterTransactionsDelta {
+            name: name.clone(),
+            account_include,
+            account_exclude,
+            account_required,
+        })
+    }
+
+    fn apply_delta(&mut self, delta: FilterTransactionsDelta) {
+        if let Some(inner) = self.filters.get_mut(&delta.name) {
+            delta.account_include.apply_set(&mut inner.account_include);
+            delta.account_exclude.apply_set(&mut inner.account_exclude);
+            delta
+                .account_required
+                .apply_set(&mut inner.account_required);
+        }
+    }
+
     pub fn get_updates(&self, message: &MessageTransaction) -> FilteredUpdates {
-        let filters = self
-            .filters
-            .iter()
-            .filter_map(|(name, inner)| {
-                if let Some(is_vote) = inner.vote {
-                    if is_vote != message.transaction.is_vote {
-                        return None;
-                    }
+        let filters = self.filters.iter().filter_map(|(name, inner)| {
+            if let Some(is_vote) = inner.vote {
+                if is_vote != message.transaction.is_vote {
+                    return None;
                 }
+            }
 
-                if let Some(is_failed) = inner.failed {
-                    if is_failed != message.transaction.meta.err.is_some() {
-                        return None;
-                    }
+            if let Some(is_failed) = inner.failed {
+                if is_failed != message.transaction.meta.err.is_some() {
+                    return None;
                 }
+            }
 
-                if let Some(signature) = &inner.signature {
-                    let tx_sig = message.transaction.transaction.signatures.first();
-                    if Some(signature.as_ref()) != tx_sig.map(|sig| sig.as_ref()) {
-                        return None;
-                    }
+            if let Some(signature) = &inner.signature {
+                let tx_sig = message.transaction.transaction.signatures.first();
+                if Some(signature.as_ref()) != tx_sig.map(|sig| sig.as_ref()) {
+                    return None;
                 }
+            }
+
+            if !inner.account_include.is_empty()
+                && inner
+                    .account_include
+                    .intersection(&message.transaction.account_keys)
+                    .next()
+                    .is_none()
+            {
+                return None;
+            }
+
+            // checked against all account keys (signers, programs and loaded addresses),
+            // excluded transaction is not sent even if program / signer filters match it
+            if !inner.account_exclude.is_empty()
+                && inner
+                    .account_exclude
+                    .intersection(&message.transaction.account_keys)
+                    .next()
+                    .is_some()
+            {
+                return None;
+            }
+
+            if !inner.account_required.is_empty()
+                && !inner
+                    .account_required
+                    .is_subset(&message.transaction.account_keys)
+            {
+                return None;
+            }
 
-                if !inner.account_include.is_empty()
-                    && inner
-                        .account_include
-                        .intersection(&message.transaction.account_keys)
-                        .next()
-                        .is_none()
+            if !inner.signer_include.is_empty()
+                && !message
+                    .transaction
+                    .signers
+                    .iter()
+                    .any(|signer| inner.signer_include.contains(signer))
+            {
+                return None;
+            }
+
+            if !inner.fee_payer.is_empty()
+                && !message
+                    .transaction
+                    .signers
+                    .first()
+                    .is_some_and(|fee_payer| inner.fee_payer.contains(fee_payer))
+            {
+                return None;
+            }
+
+            if !inner.program_include.is_empty() || !inner.program_exclude.is_empty() {
+                let mut included = inner.program_include.is_empty();
+                for (program_id, _data) in
+                    Self::iter_instructions(&message.transaction, inner.inner_instructions)
                 {
+                    if inner.program_exclude.contains(&program_id) {
+                        return None;
+                    }
+                    included = included || inner.program_include.contains(&program_id);
+                }
+                if !included {
                     return None;
                 }
+            }
 
-                if !inner.account_exclude.is_empty()
-                    && inner
-                        .account_exclude
-                        .intersection(&message.transaction.account_keys)
-                        .next()
-                        .is_some()
-                {
+            if !inner.instruction_data.is_empty()
+                && !Self::iter_instructions(&message.transaction, inner.inner_instructions).any(
+                    |(program_id, data)| {
+                        inner
+                            .instruction_data
+                            .iter()
+                            .any(|filter| filter.is_match(&program_id, data))
+                    },
+                )
+            {
+                return None;
+            }
+
+            let log_messages = &message.transaction.meta.log_messages;
+            if !inner.log_contains.is_empty()
+                && !log_messages.iter().any(|log| {
+                    inner
+                        .log_contains
+                        .iter()
+                        .any(|pattern| log.contains(pattern.as_str()))
+                })
+            {
+                return None;
+            }
+
+            if let Some(log_regex) = &inner.log_regex {
+                if !log_messages.iter().any(|log| log_regex.is_match(log)) {
                     return None;
                 }
+            }
+
+            if !inner.token_balance.is_empty()
+                && !inner
+                    .token_balance
+                    .iter()
+                    .any(|filter| filter.is_match(&message.transaction.meta))
+            {
+                return None;
+            }
 
-                if !inner.account_required.is_empty()
-                    && !inner
-                        .account_required
-                        .is_subset(&message.transaction.account_keys)
+            if !inner.compute_units_consumed.is_empty() {
+                let compute_units_consumed = message.transaction.meta.compute_units_consumed?;
+                if !inner
+                    .compute_units_consumed
+                    .iter()
+                    .all(|cmp| cmp.is_match(compute_units_consumed))
                 {
                     return None;
                 }
+            }
 
-                Some(name.clone())
-            })
-            .collect::<FilteredUpdateFilters>();
+            if !inner
+                .fee
+                .iter()
+                .all(|cmp| cmp.is_match(message.transaction.meta.fee))
+            {
+                return None;
+            }
 
-        filtered_updates_once_owned!(
-            filters,
-            match self.filter_type {
-                FilterTransactionsType::Transaction => FilteredUpdateOneof::transaction(message),
-                FilterTransactionsType::TransactionStatus => {
-                    FilteredUpdateOneof::transaction_status(message)
+            Some((name, inner.mask))
+        });
+
+        // one update per distinct mask, with names of filters requested it
+        let mut groups: Vec<(FilteredUpdateTransactionMask, FilteredUpdateFilters)> = vec![];
+        for (name, mask) in filters {
+            match groups
+                .iter_mut()
+                .find(|(group_mask, _)| *group_mask == mask)
+            {
+                Some((_, filters)) => filters.push(name.clone()),
+                None => {
+                    let mut filters = FilteredUpdateFilters::new();
+                    filters.push(name.clone());
+                    groups.push((mask, filters));
                 }
             }
-        )
+        }
+
+        groups
+            .into_iter()
+            .map(|(mask, filters)| {
+                let update = match self.filter_type {
+                    FilterTransactionsType::Transaction => {
+                        FilteredUpdateOneof::transaction_masked(message, mask)
+                    }
+                    FilterTransactionsType::TransactionStatus => {
+                        FilteredUpdateOneof::transaction_status(message)
+                    }
+                };
+                FilteredUpdate::new(filters, update)
+            })
+            .collect()
+    }
+
+    // Program id and data of top-level and, optionally, inner instructions
+    fn iter_instructions(
+        transaction: &MessageTransactionInfo,
+        inner_instructions: bool,
+    ) -> impl Iterator<Item = (Pubkey, &[u8])> {
+        let instructions = transaction
+            .transaction
+            .message
+            .iter()
+            .flat_map(|message| message.instructions.iter())
+            .map(|ix| (ix.program_id_index, ix.data.as_slice()));
+        let inner_instructions = transaction
+            .meta
+            .inner_instructions
+            .iter()
+            .filter(move |_| inner_instructions)
+            .flat_map(|ixs| ixs.instructions.iter())
+            .map(|ix| (ix.program_id_index, ix.data.as_slice()));
+
+        instructions
+            .chain(inner_instructions)
+            .filter_map(|(program_id_index, data)| {
+                Self::get_account_key(transaction, program_id_index as usize)
+                    .map(|program_id| (program_id, data))
+            })
+    }
+
+    // Index in static account keys followed by loaded writable and readonly addresses
+    fn get_account_key(transaction: &MessageTransactionInfo, index: usize) -> Option<Pubkey> {
+        let static_keys = transaction
+            .transaction
+            .message
+            .as_ref()
+            .map(|message| message.account_keys.as_slice())
+            .unwrap_or_default();
+        let writable_keys = transaction.meta.loaded_writable_addresses.as_slice();
+        let readonly_keys = transaction.meta.loaded_readonly_addresses.as_slice();
+
+        static_keys
+            .iter()
+            .chain(writable_keys)
+            .chain(readonly_keys)
+            .nth(index)
+            .and_then(|key| Pubkey::try_from(key.as_slice()).ok())
     }
 }
 
@@ -1072,30 +2599,57 @@ impl FilterAccountsDataSlice {
 #[cfg(test)]
 mod tests {
     use {
-        super::Filter,
+        super::{Filter, FilterAccountsCache, FilterAccountsState, FilterMintDecimals},
         crate::{
+            account_diff::AccountDiffState,
             convert_to,
             geyser::{
-                SubscribeRequest, SubscribeRequestFilterAccounts,
-                SubscribeRequestFilterTransactions,
+                subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
+                subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
+                subscribe_request_filter_transactions_cmp::Cmp as TransactionsFilterCmp,
+                subscribe_update::UpdateOneof, AccountEncoding,
+                CommitmentLevel as CommitmentLevelProto, SubscribeRequest,
+                SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
+                SubscribeRequestFilterAccountsDataSlice, SubscribeRequestFilterAccountsFilter,
+                SubscribeRequestFilterAccountsFilterAnyOf,
+                SubscribeRequestFilterAccountsFilterGroup,
+                SubscribeRequestFilterAccountsFilterMemcmp,
+                SubscribeRequestFilterAccountsFilterNot, SubscribeRequestFilterDelta,
+                SubscribeRequestFilterDeltaAccounts, SubscribeRequestFilterDeltaRemove,
+                SubscribeRequestFilterDeltaTransactions, SubscribeRequestFilterSlots,
+                SubscribeRequestFilterTransactions, SubscribeRequestFilterTransactionsCmp,
+                SubscribeRequestFilterTransactionsInstructionData,
+                SubscribeRequestFilterTransactionsTokenBalance, SubscribeUpdate,
+                SubscribeUpdateAccount, SubscribeUpdateAccountDiffPatch,
             },
             plugin::{
                 filter::{
-                    limits::FilterLimits,
-                    message::{FilteredUpdateFilters, FilteredUpdateOneof},
+                    limits::{FilterLimits, FilterLimitsAccounts, FilterLimitsTransactions},
+                    message::{
+                        tests::create_accounts_raw, FilteredUpdate, FilteredUpdateFilters,
+                        FilteredUpdateOneof, FilteredUpdateTransactionMask,
+                    },
                     name::{FilterName, FilterNames},
+                    FilterAccountsDataSlice,
+                },
+                message::{
+                    CommitmentLevel, Message, MessageAccount, MessageAccountInfo,
+                    MessageEncodedCache, MessageTransaction, MessageTransactionInfo,
                 },
-                message::{Message, MessageTransaction, MessageTransactionInfo},
             },
+            solana::storage::confirmed_block,
         },
+        prost::Message as _,
         solana_sdk::{
             hash::Hash,
             message::{v0::LoadedAddresses, Message as SolMessage, MessageHeader},
+            program_pack::Pack,
             pubkey::Pubkey,
             signer::{keypair::Keypair, Signer},
             transaction::{SanitizedTransaction, Transaction},
         },
         solana_transaction_status::TransactionStatusMeta,
+        spl_token_2022::state::{Account as TokenAccount, AccountState, Mint},
         std::{collections::HashMap, sync::Arc, time::Duration},
     };
 
@@ -1103,6 +2657,10 @@ mod tests {
         FilterNames::new(64, 1024, Duration::from_secs(1))
     }
 
+    fn create_mint_decimals() -> FilterMintDecimals {
+        FilterMintDecimals::new(1024)
+    }
+
     fn create_message_transaction(
         keypair: &Keypair,
         account_keys: Vec<Pubkey>,
@@ -1119,91 +2677,1095 @@ mod tests {
         let sanitized_transaction = SanitizedTransaction::from_transaction_for_tests(
             Transaction::new(&[keypair], message, recent_blockhash),
         );
-        let meta = convert_to::create_transaction_meta(&TransactionStatusMeta {
-            status: Ok(()),
-            fee: 0,
-            pre_balances: vec![],
-            post_balances: vec![],
-            inner_instructions: None,
-            log_messages: None,
-            pre_token_balances: None,
-            post_token_balances: None,
-            rewards: None,
-            loaded_addresses: LoadedAddresses::default(),
-            return_data: None,
-            compute_units_consumed: None,
+        let meta = convert_to::create_transaction_meta(&TransactionStatusMeta {
+            status: Ok(()),
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            inner_instructions: None,
+            log_messages: None,
+            pre_token_balances: None,
+            post_token_balances: None,
+            rewards: None,
+            loaded_addresses: LoadedAddresses::default(),
+            return_data: None,
+            compute_units_consumed: None,
+        });
+        let sig = sanitized_transaction.signature();
+        let account_keys = sanitized_transaction
+            .message()
+            .account_keys()
+            .iter()
+            .copied()
+            .collect();
+        let signers = sanitized_transaction
+            .message()
+            .account_keys()
+            .iter()
+            .take(1)
+            .copied()
+            .collect();
+        MessageTransaction {
+            transaction: Arc::new(MessageTransactionInfo {
+                signature: *sig,
+                is_vote: true,
+                transaction: convert_to::create_transaction(&sanitized_transaction),
+                meta,
+                index: 1,
+                account_keys,
+                signers,
+                encoded: MessageEncodedCache::default(),
+            }),
+            slot: 100,
+        }
+    }
+
+    #[test]
+    fn test_filters_all_empty() {
+        // ensure Filter can be created with empty values
+        let config = SubscribeRequest {
+            accounts: HashMap::new(),
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
+        };
+        let limit = FilterLimits::default();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        );
+        assert!(filter.is_ok());
+    }
+
+    #[test]
+    fn test_filters_account_empty() {
+        let mut accounts = HashMap::new();
+
+        accounts.insert(
+            "solend".to_owned(),
+            SubscribeRequestFilterAccounts {
+                nonempty_txn_signature: None,
+                account_encoding: None,
+                data_slice: None,
+                account: vec![],
+                owner: vec![],
+                filters: vec![],
+                commitment: None,
+            },
+        );
+
+        let config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
+        };
+        let mut limit = FilterLimits::default();
+        limit.accounts.any = false;
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        );
+        // filter should fail
+        assert!(filter.is_err());
+    }
+
+    #[test]
+    fn test_filters_account_any_of_not() {
+        let datasize = |size| SubscribeRequestFilterAccountsFilter {
+            filter: Some(AccountsFilterDataOneof::Datasize(size)),
+        };
+        let memcmp = |offset, bytes: &[u8]| SubscribeRequestFilterAccountsFilter {
+            filter: Some(AccountsFilterDataOneof::Memcmp(
+                SubscribeRequestFilterAccountsFilterMemcmp {
+                    offset,
+                    data: Some(AccountsFilterMemcmpOneof::Bytes(bytes.to_vec())),
+                },
+            )),
+        };
+        let group = |filters| SubscribeRequestFilterAccountsFilterGroup { filters };
+
+        // (datasize = 4 AND data[0] = 1) OR datasize = 2, but NOT data[1] = 9
+        let filters = vec![
+            SubscribeRequestFilterAccountsFilter {
+                filter: Some(AccountsFilterDataOneof::AnyOf(
+                    SubscribeRequestFilterAccountsFilterAnyOf {
+                        groups: vec![
+                            group(vec![datasize(4), memcmp(0, &[1])]),
+                            group(vec![datasize(2)]),
+                        ],
+                    },
+                )),
+            },
+            SubscribeRequestFilterAccountsFilter {
+                filter: Some(AccountsFilterDataOneof::Not(
+                    SubscribeRequestFilterAccountsFilterNot {
+                        filters: vec![memcmp(1, &[9])],
+                    },
+                )),
+            },
+        ];
+        let filter = FilterAccountsState::new(&filters, &FilterLimitsAccounts::default()).unwrap();
+        assert!(!filter.is_empty());
+        assert!(filter.is_match(&Pubkey::default(), &[1, 0, 0, 0], 0));
+        assert!(!filter.is_match(&Pubkey::default(), &[2, 0, 0, 0], 0));
+        assert!(!filter.is_match(&Pubkey::default(), &[1, 9, 0, 0], 0));
+        assert!(filter.is_match(&Pubkey::default(), &[5, 5], 0));
+        assert!(!filter.is_match(&Pubkey::default(), &[5, 9], 0));
+        assert!(!filter.is_match(&Pubkey::default(), &[1, 0, 0], 0));
+
+        let limits = FilterLimitsAccounts {
+            filters_depth_max: 0,
+            ..Default::default()
+        };
+        assert!(FilterAccountsState::new(&filters, &limits).is_err());
+        let limits = FilterLimitsAccounts {
+            filters_depth_max: 1,
+            filters_total_max: 6,
+            ..Default::default()
+        };
+        assert!(FilterAccountsState::new(&filters, &limits).is_ok());
+        let limits = FilterLimitsAccounts {
+            filters_total_max: 5,
+            ..Default::default()
+        };
+        assert!(FilterAccountsState::new(&filters, &limits).is_err());
+
+        let filters = vec![SubscribeRequestFilterAccountsFilter {
+            filter: Some(AccountsFilterDataOneof::AnyOf(
+                SubscribeRequestFilterAccountsFilterAnyOf {
+                    groups: vec![group(vec![])],
+                },
+            )),
+        }];
+        assert!(FilterAccountsState::new(&filters, &FilterLimitsAccounts::default()).is_err());
+    }
+
+    #[test]
+    fn test_filters_account_datasize_range_lamports_delta() {
+        let owner = Pubkey::new_unique();
+        let account_a = Pubkey::new_unique();
+        let account_b = Pubkey::new_unique();
+        let create_message = |pubkey, owner, data_len, lamports| {
+            Message::Account(MessageAccount {
+                account: Arc::new(MessageAccountInfo {
+                    pubkey,
+                    lamports,
+                    owner,
+                    executable: false,
+                    rent_epoch: 0,
+                    data: vec![0; data_len],
+                    write_version: 0,
+                    txn_signature: None,
+                    encoded: MessageEncodedCache::default(),
+                }),
+                slot: 100,
+                is_startup: false,
+            })
+        };
+
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            "solend".to_owned(),
+            SubscribeRequestFilterAccounts {
+                account: vec![],
+                owner: vec![owner.to_string()],
+                filters: vec![
+                    SubscribeRequestFilterAccountsFilter {
+                        filter: Some(AccountsFilterDataOneof::DatasizeMin(2)),
+                    },
+                    SubscribeRequestFilterAccountsFilter {
+                        filter: Some(AccountsFilterDataOneof::DatasizeMax(4)),
+                    },
+                    SubscribeRequestFilterAccountsFilter {
+                        filter: Some(AccountsFilterDataOneof::LamportsDelta(10)),
+                    },
+                ],
+                nonempty_txn_signature: None,
+                account_encoding: None,
+                data_slice: None,
+                commitment: None,
+            },
+        );
+        let mut config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
+        };
+        let limit = FilterLimits::default();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
+
+        let cases = [
+            // first update only fills cache
+            (account_a, owner, 3, 100, false),
+            (account_a, owner, 3, 105, false),
+            (account_a, owner, 3, 120, true),
+            // cache updated even if data size is out of range
+            (account_a, owner, 5, 200, false),
+            (account_a, owner, 3, 205, false),
+            (account_a, owner, 1, 300, false),
+            (account_a, owner, 4, 311, true),
+            // not cached, owner not matched
+            (account_b, Pubkey::new_unique(), 3, 100, false),
+            (account_b, owner, 3, 1000, false),
+        ];
+        for (pubkey, owner, data_len, lamports, is_match) in cases {
+            let updates =
+                filter.get_updates(&create_message(pubkey, owner, data_len, lamports), None);
+            assert_eq!(updates.len(), usize::from(is_match));
+        }
+
+        // lamports_delta requires account or owner filter
+        let filter = config.accounts.get_mut("solend").unwrap();
+        filter.owner = vec![];
+        assert!(Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_err());
+
+        // and not allowed in nested filters
+        let filters = vec![SubscribeRequestFilterAccountsFilter {
+            filter: Some(AccountsFilterDataOneof::Not(
+                SubscribeRequestFilterAccountsFilterNot {
+                    filters: vec![SubscribeRequestFilterAccountsFilter {
+                        filter: Some(AccountsFilterDataOneof::LamportsDelta(10)),
+                    }],
+                },
+            )),
+        }];
+        assert!(FilterAccountsState::new(&filters, &FilterLimitsAccounts::default()).is_err());
+    }
+
+    #[test]
+    fn test_filters_account_cache() {
+        let account_a = Pubkey::new_unique();
+        let account_b = Pubkey::new_unique();
+        let account_c = Pubkey::new_unique();
+
+        let mut cache = FilterAccountsCache::new(2);
+        assert_eq!(cache.update(account_a, 1), None);
+        assert_eq!(cache.update(account_b, 2), None);
+        assert_eq!(cache.update(account_a, 3), Some(1));
+        // account_a is oldest inserted and evicted
+        assert_eq!(cache.update(account_c, 4), None);
+        assert_eq!(cache.update(account_b, 5), Some(2));
+        assert_eq!(cache.update(account_a, 6), None);
+        assert_eq!(cache.get(&account_a), Some(&6));
+        assert_eq!(cache.values.len(), 2);
+
+        let mut cache = FilterAccountsCache::new(0);
+        assert_eq!(cache.update(account_a, 1), None);
+        assert_eq!(cache.update(account_a, 2), None);
+    }
+
+    #[test]
+    fn test_filters_account_json_parsed() {
+        let mint = Pubkey::new_unique();
+        let mut mint_data = vec![0; Mint::LEN];
+        Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut mint_data);
+        let mut token_data = vec![0; TokenAccount::LEN];
+        TokenAccount {
+            mint,
+            owner: Pubkey::new_unique(),
+            amount: 1_500_000,
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut token_data);
+        let create_message = |pubkey, data| {
+            Message::Account(MessageAccount {
+                account: Arc::new(MessageAccountInfo {
+                    pubkey,
+                    lamports: 0,
+                    owner: spl_token_2022::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                    data,
+                    write_version: 0,
+                    txn_signature: None,
+                    encoded: MessageEncodedCache::default(),
+                }),
+                slot: 0,
+                is_startup: false,
+            })
+        };
+        let get_account = |filter: &Filter, message: &Message| {
+            let updates = filter.get_updates(message, None);
+            assert_eq!(updates.len(), 1);
+            match &updates[0].message {
+                FilteredUpdateOneof::Account(msg) => msg.clone(),
+                _ => panic!("expected account update"),
+            }
+        };
+
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            "parsed".to_owned(),
+            SubscribeRequestFilterAccounts {
+                account: vec![],
+                owner: vec![spl_token_2022::id().to_string()],
+                filters: vec![],
+                nonempty_txn_signature: None,
+                account_encoding: Some(AccountEncoding::JsonParsed as i32),
+                data_slice: None,
+                commitment: None,
+            },
+        );
+        let mut config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
+        };
+        let limit = FilterLimits::default();
+        let mint_decimals = create_mint_decimals();
+        let filter =
+            Filter::new(&config, &limit, &mut create_filter_names(), &mint_decimals).unwrap();
+
+        // decimals of the mint are unknown, raw data only
+        let token_message = create_message(Pubkey::new_unique(), token_data);
+        let account = get_account(&filter, &token_message);
+        assert!(account.data_slice.is_some());
+        assert!(account.parsed.is_none());
+
+        let mint_message = create_message(mint, mint_data);
+        let account = get_account(&filter, &mint_message);
+        assert!(account.data_slice.is_none());
+        assert_eq!(account.parsed.as_ref().unwrap().program, "spl-token-2022");
+
+        // decimals are filled from all account updates, not by filters
+        let account = get_account(&filter, &token_message);
+        assert!(account.parsed.is_none());
+        if let Message::Account(msg) = &mint_message {
+            mint_decimals.update(&msg.account);
+        }
+
+        let account = get_account(&filter, &token_message);
+        assert!(account.data_slice.is_none());
+        let parsed = account.parsed.as_ref().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&parsed.json).unwrap();
+        assert_eq!(json["type"], "account");
+        assert_eq!(json["info"]["mint"], mint.to_string());
+        assert_eq!(json["info"]["tokenAmount"]["decimals"], 6);
+        assert_eq!(json["info"]["tokenAmount"]["uiAmountString"], "1.5");
+        assert_eq!(parsed.space, TokenAccount::LEN as u64);
+
+        // not parsable accounts are sent with raw data
+        let account = get_account(
+            &filter,
+            &create_message(Pubkey::new_unique(), vec![1, 2, 3]),
+        );
+        assert!(account.data_slice.is_some());
+        assert!(account.parsed.is_none());
+
+        // raw data is sent if any matched filter requires it
+        config.accounts.insert(
+            "raw".to_owned(),
+            SubscribeRequestFilterAccounts {
+                account: vec![],
+                owner: vec![spl_token_2022::id().to_string()],
+                filters: vec![],
+                nonempty_txn_signature: None,
+                account_encoding: None,
+                data_slice: None,
+                commitment: None,
+            },
+        );
+        // mint was not seen by the new filter, decimals are shared
+        let filter =
+            Filter::new(&config, &limit, &mut create_filter_names(), &mint_decimals).unwrap();
+        let account = get_account(&filter, &token_message);
+        assert!(account.data_slice.is_some());
+        assert!(account.parsed.is_some());
+
+        // parsed encoding is not allowed with accounts_diff
+        config.accounts_diff = Some(true);
+        assert!(Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_err());
+        config.accounts_diff = None;
+
+        let limit_disabled = FilterLimits {
+            accounts: FilterLimitsAccounts {
+                json_parsed: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(Filter::new(
+            &config,
+            &limit_disabled,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_err());
+
+        config.accounts.get_mut("parsed").unwrap().account_encoding = Some(42);
+        assert!(Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_filters_account_data_slice() {
+        let data_slice = |slices: &[(u64, u64)]| {
+            Some(SubscribeRequestFilterAccountsDataSlice {
+                slices: slices
+                    .iter()
+                    .map(|(offset, length)| SubscribeRequestAccountsDataSlice {
+                        offset: *offset,
+                        length: *length,
+                    })
+                    .collect(),
+            })
+        };
+        let mut accounts = HashMap::new();
+        for (name, data_slice) in [
+            ("default", None),
+            ("full", data_slice(&[])),
+            ("head_a", data_slice(&[(0, 2)])),
+            ("head_b", data_slice(&[(0, 2)])),
+        ] {
+            accounts.insert(
+                name.to_owned(),
+                SubscribeRequestFilterAccounts {
+                    account: vec![],
+                    owner: vec![],
+                    filters: vec![],
+                    nonempty_txn_signature: None,
+                    account_encoding: None,
+                    data_slice,
+                    commitment: None,
+                },
+            );
+        }
+        let mut config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: vec![SubscribeRequestAccountsDataSlice {
+                offset: 1,
+                length: 3,
+            }],
+            ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
+        };
+        let limit = FilterLimits::default();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
+
+        let message = Message::Account(MessageAccount {
+            account: Arc::new(MessageAccountInfo {
+                pubkey: Pubkey::new_unique(),
+                lamports: 0,
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+                data: vec![1, 2, 3, 4, 5],
+                write_version: 0,
+                txn_signature: None,
+                encoded: MessageEncodedCache::default(),
+            }),
+            slot: 0,
+            is_startup: false,
+        });
+        let mut updates = filter
+            .get_updates(&message, None)
+            .into_iter()
+            .map(|update| {
+                let Some(UpdateOneof::Account(msg)) = update.as_subscribe_update().update_oneof
+                else {
+                    panic!("expected account update");
+                };
+                let mut filters = update
+                    .filters
+                    .iter()
+                    .map(|name| name.as_ref().to_owned())
+                    .collect::<Vec<_>>();
+                filters.sort();
+                (filters, msg.account.unwrap().data)
+            })
+            .collect::<Vec<_>>();
+        updates.sort();
+        assert_eq!(
+            updates,
+            vec![
+                (vec!["default".to_owned()], vec![2, 3, 4]),
+                (vec!["full".to_owned()], vec![1, 2, 3, 4, 5]),
+                (vec!["head_a".to_owned(), "head_b".to_owned()], vec![1, 2]),
+            ]
+        );
+
+        // data slices are not supported by accounts_diff
+        config.accounts_data_slice = vec![];
+        config.accounts_diff = Some(true);
+        assert!(Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_err());
+        config.accounts.remove("head_a");
+        config.accounts.remove("head_b");
+        assert!(Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_ok());
+        config.accounts_diff = None;
+
+        config.accounts.get_mut("full").unwrap().data_slice = data_slice(&[(0, 2), (1, 2)]);
+        assert!(Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_filters_accounts_diff() {
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            "diff".to_owned(),
+            SubscribeRequestFilterAccounts {
+                account: vec![],
+                owner: vec![],
+                filters: vec![],
+                nonempty_txn_signature: None,
+                account_encoding: None,
+                data_slice: None,
+                commitment: None,
+            },
+        );
+        let mut config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            accounts_diff: Some(true),
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
+        };
+        let limit = FilterLimits {
+            accounts: FilterLimitsAccounts {
+                diff_keyframe_interval: 4,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
+
+        let mut state = AccountDiffState::new();
+        let mut keyframes = 0;
+        let mut diffs = 0;
+        // all fixtures have the same pubkey, so every update is applied to the previous one
+        for account in create_accounts_raw() {
+            let data = account.data.clone();
+            let mut changed = data.clone();
+            if let Some(byte) = changed.first_mut() {
+                *byte = 1;
+            }
+            if let Some(byte) = changed.last_mut() {
+                *byte = 2;
+            }
+            let mut extended = changed.clone();
+            extended.extend_from_slice(&[3; 100]);
+            let truncated = data[..data.len() / 2].to_vec();
+
+            for (slot, data) in [data.clone(), changed, extended, truncated, data]
+                .into_iter()
+                .enumerate()
+            {
+                let msg = MessageAccount {
+                    account: Arc::new(MessageAccountInfo {
+                        data,
+                        ..account.as_ref().clone()
+                    }),
+                    slot: slot as u64,
+                    is_startup: false,
+                };
+                let Some(UpdateOneof::Account(expected)) = FilteredUpdate::new_empty(
+                    FilteredUpdateOneof::account(&msg, FilterAccountsDataSlice::default()),
+                )
+                .as_subscribe_update()
+                .update_oneof
+                else {
+                    panic!("expected account update");
+                };
+
+                let updates = filter.get_updates(&Message::Account(msg), None);
+                assert_eq!(updates.len(), 1);
+                let update = SubscribeUpdate::decode(updates[0].encode_to_vec().as_slice())
+                    .expect("failed to decode");
+                let received = match update.update_oneof {
+                    Some(UpdateOneof::Account(msg)) => {
+                        keyframes += 1;
+                        state.update(&msg);
+                        msg
+                    }
+                    Some(UpdateOneof::AccountDiff(msg)) => {
+                        diffs += 1;
+                        state.apply(msg).expect("failed to apply diff")
+                    }
+                    _ => panic!("expected account or account diff update"),
+                };
+                assert_eq!(received, expected);
+            }
+        }
+        assert!(keyframes > 0);
+        assert!(diffs > 0);
+
+        // next update after dropped diff is keyframe
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
+        let account = create_accounts_raw()
+            .into_iter()
+            .find(|account| !account.data.is_empty())
+            .unwrap();
+        let mut kinds = vec![];
+        for (slot, byte) in [0u8, 1, 2, 3].into_iter().enumerate() {
+            let mut data = account.data.clone();
+            data[0] = byte;
+            let msg = Message::Account(MessageAccount {
+                account: Arc::new(MessageAccountInfo {
+                    data,
+                    ..account.as_ref().clone()
+                }),
+                slot: slot as u64,
+                is_startup: false,
+            });
+            let updates = filter.get_updates(&msg, None);
+            assert_eq!(updates.len(), 1);
+            kinds.push(match updates[0].message {
+                FilteredUpdateOneof::Account(_) => "account",
+                FilteredUpdateOneof::AccountDiff(_) => "diff",
+                _ => panic!("expected account or account diff update"),
+            });
+            if slot == 1 {
+                filter.reset_accounts_diff([account.pubkey]);
+            }
+        }
+        assert_eq!(kinds, ["account", "diff", "account", "diff"]);
+
+        // diff can not be applied without keyframe
+        let mut state = AccountDiffState::new();
+        let mut update = None;
+        for account in create_accounts_raw() {
+            let msg = Message::Account(MessageAccount {
+                account,
+                slot: 0,
+                is_startup: false,
+            });
+            for filtered in filter.get_updates(&msg, None) {
+                if let Some(UpdateOneof::AccountDiff(msg)) =
+                    filtered.as_subscribe_update().update_oneof
+                {
+                    update = Some(msg);
+                }
+            }
+        }
+        let mut update = update.expect("expected account diff update");
+        assert!(state.apply(update.clone()).is_err());
+
+        // patches out of account data are rejected
+        state.update(&SubscribeUpdateAccount {
+            account: update.account.clone(),
+            slot: 0,
+            is_startup: false,
+        });
+        update.patches.push(SubscribeUpdateAccountDiffPatch {
+            offset: update.data_len,
+            data: vec![1],
+        });
+        assert!(state.apply(update).is_err());
+
+        // data slices are not supported
+        config.accounts_data_slice = vec![SubscribeRequestAccountsDataSlice {
+            offset: 0,
+            length: 1,
+        }];
+        assert!(Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_filters_delta() {
+        let keypair = Keypair::new();
+        let [account_a, account_b, account_c, owner] = [(); 4].map(|()| Pubkey::new_unique());
+
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            "account".to_owned(),
+            SubscribeRequestFilterAccounts {
+                account: vec![account_a.to_string()],
+                ..Default::default()
+            },
+        );
+        accounts.insert(
+            "owner".to_owned(),
+            SubscribeRequestFilterAccounts {
+                owner: vec![owner.to_string()],
+                ..Default::default()
+            },
+        );
+        let mut transactions = HashMap::new();
+        transactions.insert(
+            "transaction".to_owned(),
+            SubscribeRequestFilterTransactions {
+                account_include: vec![account_a.to_string()],
+                ..Default::default()
+            },
+        );
+        let mut slots = HashMap::new();
+        slots.insert("slot".to_owned(), SubscribeRequestFilterSlots::default());
+        let config = SubscribeRequest {
+            accounts,
+            slots,
+            transactions,
+            ..Default::default()
+        };
+        let limits = FilterLimits::default();
+        let mut filter = Filter::new(
+            &config,
+            &limits,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
+
+        let get_filters = |filter: &Filter, message: Message| {
+            let mut names = filter
+                .get_updates(&message, None)
+                .iter()
+                .flat_map(|update| update.filters.iter())
+                .map(|name| name.as_ref().to_owned())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        let account = |pubkey: Pubkey| {
+            Message::Account(MessageAccount {
+                account: Arc::new(MessageAccountInfo {
+                    pubkey,
+                    lamports: 0,
+                    owner: Pubkey::new_unique(),
+                    executable: false,
+                    rent_epoch: 0,
+                    data: vec![],
+                    write_version: 0,
+                    txn_signature: None,
+                    encoded: MessageEncodedCache::default(),
+                }),
+                slot: 0,
+                is_startup: false,
+            })
+        };
+        let transaction = |pubkey: Pubkey| {
+            Message::Transaction(create_message_transaction(
+                &keypair,
+                vec![keypair.pubkey(), pubkey],
+            ))
+        };
+
+        // replace pubkeys in accounts and transactions filters
+        let mut delta = SubscribeRequestFilterDelta::default();
+        delta.accounts.insert(
+            "account".to_owned(),
+            SubscribeRequestFilterDeltaAccounts {
+                account_add: vec![account_b.to_string(), account_c.to_string()],
+                account_remove: vec![account_a.to_string(), account_c.to_string()],
+                ..Default::default()
+            },
+        );
+        delta.transactions.insert(
+            "transaction".to_owned(),
+            SubscribeRequestFilterDeltaTransactions {
+                account_include_add: vec![account_b.to_string()],
+                account_include_remove: vec![account_a.to_string()],
+                ..Default::default()
+            },
+        );
+        filter.apply_delta(&delta, &limits).unwrap();
+        assert!(get_filters(&filter, account(account_a)).is_empty());
+        assert_eq!(get_filters(&filter, account(account_b)), vec!["account"]);
+        assert_eq!(get_filters(&filter, account(account_c)), vec!["account"]);
+        assert!(get_filters(&filter, transaction(account_a)).is_empty());
+        assert_eq!(
+            get_filters(&filter, transaction(account_b)),
+            vec!["transaction"]
+        );
+
+        // limits are checked for the result, invalid delta does not change filter
+        let limits_max = FilterLimits {
+            accounts: FilterLimitsAccounts {
+                account_max: 2,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut delta = SubscribeRequestFilterDelta::default();
+        delta.accounts.insert(
+            "account".to_owned(),
+            SubscribeRequestFilterDeltaAccounts {
+                account_add: vec![account_a.to_string()],
+                ..Default::default()
+            },
+        );
+        assert!(filter.apply_delta(&delta, &limits_max).is_err());
+        delta.remove = Some(SubscribeRequestFilterDeltaRemove {
+            slots: vec!["slot".to_owned()],
+            ..Default::default()
         });
-        let sig = sanitized_transaction.signature();
-        let account_keys = sanitized_transaction
-            .message()
-            .account_keys()
-            .iter()
-            .copied()
-            .collect();
-        MessageTransaction {
-            transaction: Arc::new(MessageTransactionInfo {
-                signature: *sig,
-                is_vote: true,
-                transaction: convert_to::create_transaction(&sanitized_transaction),
-                meta,
-                index: 1,
-                account_keys,
+        delta.accounts.get_mut("account").unwrap().account_remove = vec![account_b.to_string()];
+        filter.apply_delta(&delta, &limits_max).unwrap();
+        assert_eq!(get_filters(&filter, account(account_a)), vec!["account"]);
+        assert!(get_filters(&filter, account(account_b)).is_empty());
+        assert_eq!(filter.get_metrics()[1], ("slots", 0));
+
+        // unknown and removed filters can not be updated
+        let mut delta = SubscribeRequestFilterDelta::default();
+        delta.accounts.insert(
+            "unknown".to_owned(),
+            SubscribeRequestFilterDeltaAccounts::default(),
+        );
+        assert!(filter.apply_delta(&delta, &limits).is_err());
+        let mut delta = SubscribeRequestFilterDelta {
+            remove: Some(SubscribeRequestFilterDeltaRemove {
+                accounts: vec!["owner".to_owned()],
+                ..Default::default()
             }),
-            slot: 100,
-        }
-    }
-
-    #[test]
-    fn test_filters_all_empty() {
-        // ensure Filter can be created with empty values
-        let config = SubscribeRequest {
-            accounts: HashMap::new(),
-            slots: HashMap::new(),
-            transactions: HashMap::new(),
-            transactions_status: HashMap::new(),
-            blocks: HashMap::new(),
-            blocks_meta: HashMap::new(),
-            entry: HashMap::new(),
-            commitment: None,
-            accounts_data_slice: Vec::new(),
-            ping: None,
+            ..Default::default()
         };
-        let limit = FilterLimits::default();
-        let filter = Filter::new(&config, &limit, &mut create_filter_names());
-        assert!(filter.is_ok());
+        delta.accounts.insert(
+            "owner".to_owned(),
+            SubscribeRequestFilterDeltaAccounts::default(),
+        );
+        assert!(filter.apply_delta(&delta, &limits).is_err());
+        delta.accounts.clear();
+        filter.apply_delta(&delta, &limits).unwrap();
+        assert_eq!(filter.get_metrics()[0], ("accounts", 1));
+        assert!(filter.apply_delta(&delta, &limits).is_err());
     }
 
     #[test]
-    fn test_filters_account_empty() {
-        let mut accounts = HashMap::new();
+    fn test_filters_commitment() {
+        let keypair = Keypair::new();
+        let account = create_accounts_raw().remove(0);
 
+        let mut accounts = HashMap::new();
         accounts.insert(
-            "solend".to_owned(),
+            "account".to_owned(),
             SubscribeRequestFilterAccounts {
-                nonempty_txn_signature: None,
-                account: vec![],
+                account: vec![account.pubkey.to_string()],
                 owner: vec![],
                 filters: vec![],
+                nonempty_txn_signature: None,
+                account_encoding: None,
+                data_slice: None,
+                commitment: Some(CommitmentLevelProto::Confirmed as i32),
             },
         );
-
-        let config = SubscribeRequest {
+        let mut transactions = HashMap::new();
+        transactions.insert(
+            "transaction".to_owned(),
+            SubscribeRequestFilterTransactions {
+                account_include: vec![keypair.pubkey().to_string()],
+                ..Default::default()
+            },
+        );
+        let mut config = SubscribeRequest {
             accounts,
-            slots: HashMap::new(),
-            transactions: HashMap::new(),
-            transactions_status: HashMap::new(),
-            blocks: HashMap::new(),
-            blocks_meta: HashMap::new(),
-            entry: HashMap::new(),
-            commitment: None,
-            accounts_data_slice: Vec::new(),
-            ping: None,
+            transactions,
+            commitment: Some(CommitmentLevelProto::Processed as i32),
+            ..Default::default()
         };
-        let mut limit = FilterLimits::default();
-        limit.accounts.any = false;
-        let filter = Filter::new(&config, &limit, &mut create_filter_names());
-        // filter should fail
-        assert!(filter.is_err());
+        let limit = FilterLimits::default();
+        let mut filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
+        assert!(filter.has_commitment_level(CommitmentLevel::Processed));
+        assert!(filter.has_commitment_level(CommitmentLevel::Confirmed));
+        assert!(!filter.has_commitment_level(CommitmentLevel::Finalized));
+
+        let message_account = Message::Account(MessageAccount {
+            account,
+            slot: 100,
+            is_startup: false,
+        });
+        let message_transaction =
+            Message::Transaction(create_message_transaction(&keypair, vec![keypair.pubkey()]));
+        for (commitment, accounts, transactions) in [
+            (CommitmentLevel::Processed, 0, 1),
+            (CommitmentLevel::Confirmed, 1, 0),
+            (CommitmentLevel::Finalized, 0, 0),
+        ] {
+            let updates = filter.get_updates(&message_account, Some(commitment));
+            assert_eq!(updates.len(), accounts);
+            let updates = updates
+                .into_iter()
+                .chain(filter.get_updates(&message_transaction, Some(commitment)))
+                .collect::<Vec<_>>();
+            assert_eq!(updates.len(), accounts + transactions);
+            for update in updates {
+                assert_eq!(update.commitment, Some(commitment));
+                assert_eq!(
+                    update.as_subscribe_update().commitment,
+                    Some(CommitmentLevelProto::from(commitment) as i32)
+                );
+            }
+        }
+
+        // accounts snapshot is not filtered by commitment and not tagged
+        let updates = filter.get_updates(&message_account, None);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].commitment, None);
+
+        // commitment of removed filter is not used
+        let delta = SubscribeRequestFilterDelta {
+            remove: Some(SubscribeRequestFilterDeltaRemove {
+                accounts: vec!["account".to_owned()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        filter.apply_delta(&delta, &limit).unwrap();
+        assert!(!filter.has_commitment_level(CommitmentLevel::Confirmed));
+
+        // accounts diff cache is shared by all account filters
+        config.accounts_diff = Some(true);
+        assert!(Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_err());
+        config.commitment = Some(CommitmentLevelProto::Confirmed as i32);
+        assert!(Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals()
+        )
+        .is_ok());
     }
 
     #[test]
@@ -1219,6 +3781,22 @@ mod tests {
                 account_include: vec![],
                 account_exclude: vec![],
                 account_required: vec![],
+                signer_include: vec![],
+                fee_payer: vec![],
+                program_include: vec![],
+                program_exclude: vec![],
+                inner_instructions: None,
+                instruction_data: vec![],
+                log_contains: vec![],
+                log_regex: vec![],
+                token_balance: vec![],
+                compute_units_consumed: vec![],
+                fee: vec![],
+                include_logs: None,
+                include_inner_instructions: None,
+                include_token_balances: None,
+                include_rewards: None,
+                commitment: None,
             },
         );
 
@@ -1233,10 +3811,20 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
         };
         let mut limit = FilterLimits::default();
         limit.transactions.any = false;
-        let filter = Filter::new(&config, &limit, &mut create_filter_names());
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        );
         // filter should fail
         assert!(filter.is_err());
     }
@@ -1253,6 +3841,22 @@ mod tests {
                 account_include: vec![],
                 account_exclude: vec![],
                 account_required: vec![],
+                signer_include: vec![],
+                fee_payer: vec![],
+                program_include: vec![],
+                program_exclude: vec![],
+                inner_instructions: None,
+                instruction_data: vec![],
+                log_contains: vec![],
+                log_regex: vec![],
+                token_balance: vec![],
+                compute_units_consumed: vec![],
+                fee: vec![],
+                include_logs: None,
+                include_inner_instructions: None,
+                include_token_balances: None,
+                include_rewards: None,
+                commitment: None,
             },
         );
 
@@ -1267,14 +3871,239 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
         };
         let mut limit = FilterLimits::default();
         limit.transactions.any = false;
-        let filter_res = Filter::new(&config, &limit, &mut create_filter_names());
+        let filter_res = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        );
         // filter should succeed
         assert!(filter_res.is_ok());
     }
 
+    #[test]
+    fn test_filters_transaction_program_exclude_any() {
+        let program = Pubkey::new_unique().to_string();
+        let cases = [
+            (vec![], vec![program.clone()], false),
+            (vec![program.clone()], vec![], true),
+            (vec![program.clone()], vec![program], true),
+        ];
+        for (program_include, program_exclude, is_ok) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    signer_include: vec![],
+                    fee_payer: vec![],
+                    program_include,
+                    program_exclude,
+                    inner_instructions: None,
+                    instruction_data: vec![],
+                    log_contains: vec![],
+                    log_regex: vec![],
+                    token_balance: vec![],
+                    compute_units_consumed: vec![],
+                    fee: vec![],
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let config = SubscribeRequest {
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+                transactions,
+                transactions_status: HashMap::new(),
+                blocks: HashMap::new(),
+                blocks_meta: HashMap::new(),
+                entry: HashMap::new(),
+                commitment: None,
+                accounts_data_slice: Vec::new(),
+                ping: None,
+                from_slot: None,
+                accounts_diff: None,
+                filter_delta: None,
+                credits: None,
+                transactions_commitment_updates: None,
+            };
+            let mut limit = FilterLimits::default();
+            limit.transactions.any = false;
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            );
+            assert_eq!(filter.is_ok(), is_ok);
+        }
+    }
+
+    #[test]
+    fn test_filters_transaction_log_regex_any() {
+        let cases = [
+            (vec![], vec![".*".to_owned()], false),
+            (vec!["Program log".to_owned()], vec![], true),
+            (vec!["Program log".to_owned()], vec![".*".to_owned()], true),
+        ];
+        for (log_contains, log_regex, is_ok) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    signer_include: vec![],
+                    fee_payer: vec![],
+                    program_include: vec![],
+                    program_exclude: vec![],
+                    inner_instructions: None,
+                    instruction_data: vec![],
+                    log_contains,
+                    log_regex,
+                    token_balance: vec![],
+                    compute_units_consumed: vec![],
+                    fee: vec![],
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let config = SubscribeRequest {
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+                transactions,
+                transactions_status: HashMap::new(),
+                blocks: HashMap::new(),
+                blocks_meta: HashMap::new(),
+                entry: HashMap::new(),
+                commitment: None,
+                accounts_data_slice: Vec::new(),
+                ping: None,
+                from_slot: None,
+                accounts_diff: None,
+                filter_delta: None,
+                credits: None,
+                transactions_commitment_updates: None,
+            };
+            let mut limit = FilterLimits::default();
+            limit.transactions.any = false;
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            );
+            assert_eq!(filter.is_ok(), is_ok);
+        }
+    }
+
+    #[test]
+    fn test_filters_transaction_compute_units_fee_any() {
+        let cases = [
+            (vec![TransactionsFilterCmp::Gt(0)], vec![], None, false),
+            (
+                vec![],
+                vec![TransactionsFilterCmp::Lt(u64::MAX)],
+                None,
+                false,
+            ),
+            (
+                vec![TransactionsFilterCmp::Gt(0)],
+                vec![TransactionsFilterCmp::Gt(0)],
+                Some(false),
+                true,
+            ),
+        ];
+        for (compute_units_consumed, fee, vote, is_ok) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    signer_include: vec![],
+                    fee_payer: vec![],
+                    program_include: vec![],
+                    program_exclude: vec![],
+                    inner_instructions: None,
+                    instruction_data: vec![],
+                    log_contains: vec![],
+                    log_regex: vec![],
+                    token_balance: vec![],
+                    compute_units_consumed: compute_units_consumed
+                        .iter()
+                        .map(|cmp| SubscribeRequestFilterTransactionsCmp { cmp: Some(*cmp) })
+                        .collect(),
+                    fee: fee
+                        .iter()
+                        .map(|cmp| SubscribeRequestFilterTransactionsCmp { cmp: Some(*cmp) })
+                        .collect(),
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let config = SubscribeRequest {
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+                transactions,
+                transactions_status: HashMap::new(),
+                blocks: HashMap::new(),
+                blocks_meta: HashMap::new(),
+                entry: HashMap::new(),
+                commitment: None,
+                accounts_data_slice: Vec::new(),
+                ping: None,
+                from_slot: None,
+                accounts_diff: None,
+                filter_delta: None,
+                credits: None,
+                transactions_commitment_updates: None,
+            };
+            let mut limit = FilterLimits::default();
+            limit.transactions.any = false;
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            );
+            assert_eq!(filter.is_ok(), is_ok);
+        }
+    }
+
     #[test]
     fn test_transaction_include_a() {
         let mut transactions = HashMap::new();
@@ -1293,6 +4122,22 @@ mod tests {
                 account_include,
                 account_exclude: vec![],
                 account_required: vec![],
+                signer_include: vec![],
+                fee_payer: vec![],
+                program_include: vec![],
+                program_exclude: vec![],
+                inner_instructions: None,
+                instruction_data: vec![],
+                log_contains: vec![],
+                log_regex: vec![],
+                token_balance: vec![],
+                compute_units_consumed: vec![],
+                fee: vec![],
+                include_logs: None,
+                include_inner_instructions: None,
+                include_token_balances: None,
+                include_rewards: None,
+                commitment: None,
             },
         );
 
@@ -1307,9 +4152,20 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
         };
         let limit = FilterLimits::default();
-        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
 
         let message_transaction =
             create_message_transaction(&keypair_b, vec![account_key_b, account_key_a]);
@@ -1326,7 +4182,13 @@ mod tests {
         ));
 
         config.transactions_status = transactions;
-        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
         let updates = filter.get_updates(&message, None);
         assert_eq!(updates.len(), 2);
         assert_eq!(
@@ -1357,6 +4219,22 @@ mod tests {
                 account_include,
                 account_exclude: vec![],
                 account_required: vec![],
+                signer_include: vec![],
+                fee_payer: vec![],
+                program_include: vec![],
+                program_exclude: vec![],
+                inner_instructions: None,
+                instruction_data: vec![],
+                log_contains: vec![],
+                log_regex: vec![],
+                token_balance: vec![],
+                compute_units_consumed: vec![],
+                fee: vec![],
+                include_logs: None,
+                include_inner_instructions: None,
+                include_token_balances: None,
+                include_rewards: None,
+                commitment: None,
             },
         );
 
@@ -1371,9 +4249,20 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
         };
         let limit = FilterLimits::default();
-        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
 
         let message_transaction =
             create_message_transaction(&keypair_b, vec![account_key_b, account_key_a]);
@@ -1390,7 +4279,13 @@ mod tests {
         ));
 
         config.transactions_status = transactions;
-        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
         let updates = filter.get_updates(&message, None);
         assert_eq!(updates.len(), 2);
         assert_eq!(
@@ -1421,6 +4316,22 @@ mod tests {
                 account_include: vec![],
                 account_exclude,
                 account_required: vec![],
+                signer_include: vec![],
+                fee_payer: vec![],
+                program_include: vec![],
+                program_exclude: vec![],
+                inner_instructions: None,
+                instruction_data: vec![],
+                log_contains: vec![],
+                log_regex: vec![],
+                token_balance: vec![],
+                compute_units_consumed: vec![],
+                fee: vec![],
+                include_logs: None,
+                include_inner_instructions: None,
+                include_token_balances: None,
+                include_rewards: None,
+                commitment: None,
             },
         );
 
@@ -1435,9 +4346,20 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
         };
         let limit = FilterLimits::default();
-        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
 
         let message_transaction =
             create_message_transaction(&keypair_b, vec![account_key_b, account_key_a]);
@@ -1471,6 +4393,22 @@ mod tests {
                 account_include,
                 account_exclude: vec![],
                 account_required,
+                signer_include: vec![],
+                fee_payer: vec![],
+                program_include: vec![],
+                program_exclude: vec![],
+                inner_instructions: None,
+                instruction_data: vec![],
+                log_contains: vec![],
+                log_regex: vec![],
+                token_balance: vec![],
+                compute_units_consumed: vec![],
+                fee: vec![],
+                include_logs: None,
+                include_inner_instructions: None,
+                include_token_balances: None,
+                include_rewards: None,
+                commitment: None,
             },
         );
 
@@ -1485,9 +4423,20 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
         };
         let limit = FilterLimits::default();
-        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
 
         let message_transaction = create_message_transaction(
             &keypair_x,
@@ -1506,7 +4455,13 @@ mod tests {
         ));
 
         config.transactions_status = transactions;
-        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
         let updates = filter.get_updates(&message, None);
         assert_eq!(updates.len(), 2);
         assert_eq!(
@@ -1543,6 +4498,22 @@ mod tests {
                 account_include,
                 account_exclude: vec![],
                 account_required,
+                signer_include: vec![],
+                fee_payer: vec![],
+                program_include: vec![],
+                program_exclude: vec![],
+                inner_instructions: None,
+                instruction_data: vec![],
+                log_contains: vec![],
+                log_regex: vec![],
+                token_balance: vec![],
+                compute_units_consumed: vec![],
+                fee: vec![],
+                include_logs: None,
+                include_inner_instructions: None,
+                include_token_balances: None,
+                include_rewards: None,
+                commitment: None,
             },
         );
 
@@ -1557,9 +4528,20 @@ mod tests {
             commitment: None,
             accounts_data_slice: Vec::new(),
             ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
         };
         let limit = FilterLimits::default();
-        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
 
         let message_transaction =
             create_message_transaction(&keypair_x, vec![account_key_x, account_key_z]);
@@ -1568,4 +4550,814 @@ mod tests {
             assert!(message.filters.is_empty());
         }
     }
+
+    // program_a is invoked on top-level, program_b is loaded from ALT and invoked by program_a
+    fn create_message_transaction_with_instructions(
+        keypair: &Keypair,
+        program_a: Pubkey,
+        program_b: Pubkey,
+    ) -> MessageTransaction {
+        let mut message_transaction =
+            create_message_transaction(keypair, vec![keypair.pubkey(), program_a]);
+        let tx = Arc::get_mut(&mut message_transaction.transaction).unwrap();
+        tx.transaction.message.as_mut().unwrap().instructions =
+            vec![confirmed_block::CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![],
+                data: vec![1, 2, 3],
+            }];
+        tx.meta.loaded_readonly_addresses = vec![program_b.to_bytes().to_vec()];
+        tx.meta.inner_instructions = vec![confirmed_block::InnerInstructions {
+            index: 0,
+            instructions: vec![confirmed_block::InnerInstruction {
+                program_id_index: 2,
+                accounts: vec![],
+                data: vec![4, 5, 6],
+                stack_height: Some(2),
+            }],
+        }];
+        tx.fill_account_keys().unwrap();
+        message_transaction
+    }
+
+    #[test]
+    fn test_transaction_program_include_exclude() {
+        let keypair = Keypair::new();
+        let program_a = Pubkey::new_unique();
+        let program_b = Pubkey::new_unique();
+        let message = Message::Transaction(create_message_transaction_with_instructions(
+            &keypair, program_a, program_b,
+        ));
+
+        let cases = [
+            (vec![program_a], vec![], None, true),
+            (vec![program_b], vec![], None, false),
+            (vec![program_b], vec![], Some(true), true),
+            (vec![], vec![program_a], None, false),
+            (vec![], vec![program_b], None, true),
+            (vec![], vec![program_b], Some(true), false),
+            (vec![program_a], vec![program_b], Some(true), false),
+        ];
+        for (program_include, program_exclude, inner_instructions, is_match) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    signer_include: vec![],
+                    fee_payer: vec![],
+                    program_include: program_include.iter().map(|k| k.to_string()).collect(),
+                    program_exclude: program_exclude.iter().map(|k| k.to_string()).collect(),
+                    inner_instructions,
+                    instruction_data: vec![],
+                    log_contains: vec![],
+                    log_regex: vec![],
+                    token_balance: vec![],
+                    compute_units_consumed: vec![],
+                    fee: vec![],
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let config = SubscribeRequest {
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+                transactions,
+                transactions_status: HashMap::new(),
+                blocks: HashMap::new(),
+                blocks_meta: HashMap::new(),
+                entry: HashMap::new(),
+                commitment: None,
+                accounts_data_slice: Vec::new(),
+                ping: None,
+                from_slot: None,
+                accounts_diff: None,
+                filter_delta: None,
+                credits: None,
+                transactions_commitment_updates: None,
+            };
+            let limit = FilterLimits::default();
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            )
+            .unwrap();
+
+            let updates = filter.get_updates(&message, None);
+            assert_eq!(updates.len(), usize::from(is_match));
+        }
+    }
+
+    #[test]
+    fn test_transaction_instruction_data() {
+        let keypair = Keypair::new();
+        let program_a = Pubkey::new_unique();
+        let program_b = Pubkey::new_unique();
+        let message = Message::Transaction(create_message_transaction_with_instructions(
+            &keypair, program_a, program_b,
+        ));
+
+        let cases = [
+            (program_a, 0, vec![1, 2], None, true),
+            (program_a, 1, vec![2, 3], None, true),
+            (program_a, 2, vec![3, 4], None, false),
+            (program_a, 0, vec![4, 5], Some(true), false),
+            (program_b, 1, vec![5, 6], None, false),
+            (program_b, 1, vec![5, 6], Some(true), true),
+        ];
+        for (program_id, offset, data, inner_instructions, is_match) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    signer_include: vec![],
+                    fee_payer: vec![],
+                    program_include: vec![],
+                    program_exclude: vec![],
+                    inner_instructions,
+                    instruction_data: vec![SubscribeRequestFilterTransactionsInstructionData {
+                        program_id: program_id.to_string(),
+                        memcmp: Some(SubscribeRequestFilterAccountsFilterMemcmp {
+                            offset,
+                            data: Some(AccountsFilterMemcmpOneof::Base58(
+                                bs58::encode(data).into_string(),
+                            )),
+                        }),
+                    }],
+                    log_contains: vec![],
+                    log_regex: vec![],
+                    token_balance: vec![],
+                    compute_units_consumed: vec![],
+                    fee: vec![],
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let config = SubscribeRequest {
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+                transactions,
+                transactions_status: HashMap::new(),
+                blocks: HashMap::new(),
+                blocks_meta: HashMap::new(),
+                entry: HashMap::new(),
+                commitment: None,
+                accounts_data_slice: Vec::new(),
+                ping: None,
+                from_slot: None,
+                accounts_diff: None,
+                filter_delta: None,
+                credits: None,
+                transactions_commitment_updates: None,
+            };
+            let limit = FilterLimits::default();
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            )
+            .unwrap();
+
+            let updates = filter.get_updates(&message, None);
+            assert_eq!(updates.len(), usize::from(is_match));
+        }
+    }
+
+    #[test]
+    fn test_transaction_log_messages() {
+        let keypair = Keypair::new();
+        let mut message_transaction = create_message_transaction(&keypair, vec![keypair.pubkey()]);
+        Arc::get_mut(&mut message_transaction.transaction)
+            .unwrap()
+            .meta
+            .log_messages = vec![
+            "Program JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4 invoke [1]".to_owned(),
+            "Program log: Instruction: Route".to_owned(),
+            "Program JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4 success".to_owned(),
+        ];
+        let message = Message::Transaction(message_transaction);
+
+        let cases = [
+            (vec!["Instruction: Route"], vec![], true),
+            (vec!["Instruction: Swap"], vec![], false),
+            (vec!["Instruction: Swap", "invoke [1]"], vec![], true),
+            (
+                vec![],
+                vec![r"^Program log: Instruction: (Route|Swap)$"],
+                true,
+            ),
+            (vec![], vec![r"invoke \[2\]"], false),
+            (vec!["success"], vec![r"invoke \[2\]"], false),
+            (vec!["success"], vec![r"invoke \[1\]"], true),
+        ];
+        for (log_contains, log_regex, is_match) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    signer_include: vec![],
+                    fee_payer: vec![],
+                    program_include: vec![],
+                    program_exclude: vec![],
+                    inner_instructions: None,
+                    instruction_data: vec![],
+                    log_contains: log_contains.iter().map(|s| s.to_string()).collect(),
+                    log_regex: log_regex.iter().map(|s| s.to_string()).collect(),
+                    token_balance: vec![],
+                    compute_units_consumed: vec![],
+                    fee: vec![],
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let config = SubscribeRequest {
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+                transactions,
+                transactions_status: HashMap::new(),
+                blocks: HashMap::new(),
+                blocks_meta: HashMap::new(),
+                entry: HashMap::new(),
+                commitment: None,
+                accounts_data_slice: Vec::new(),
+                ping: None,
+                from_slot: None,
+                accounts_diff: None,
+                filter_delta: None,
+                credits: None,
+                transactions_commitment_updates: None,
+            };
+            let limit = FilterLimits::default();
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            )
+            .unwrap();
+
+            let updates = filter.get_updates(&message, None);
+            assert_eq!(updates.len(), usize::from(is_match));
+
+            let limit = FilterLimits {
+                transactions: FilterLimitsTransactions {
+                    log_contains_length_max: 5,
+                    log_regex_length_max: 5,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert!(Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals()
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_transaction_token_balance() {
+        let keypair = Keypair::new();
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+        let owner_a = Pubkey::new_unique();
+        let owner_b = Pubkey::new_unique();
+
+        let create_token_balance = |account_index, mint: Pubkey, owner: Pubkey, amount: u64| {
+            confirmed_block::TokenBalance {
+                account_index,
+                mint: mint.to_string(),
+                ui_token_amount: Some(confirmed_block::UiTokenAmount {
+                    ui_amount: amount as f64,
+                    decimals: 0,
+                    amount: amount.to_string(),
+                    ui_amount_string: amount.to_string(),
+                }),
+                owner: owner.to_string(),
+                program_id: String::new(),
+            }
+        };
+        let mut message_transaction = create_message_transaction(&keypair, vec![keypair.pubkey()]);
+        let meta = &mut Arc::get_mut(&mut message_transaction.transaction)
+            .unwrap()
+            .meta;
+        // mint_a: owner_a 100 -> 40, owner_b 0 -> 60 (new account); mint_b: unchanged
+        meta.pre_token_balances = vec![
+            create_token_balance(1, mint_a, owner_a, 100),
+            create_token_balance(3, mint_b, owner_a, 500),
+        ];
+        meta.post_token_balances = vec![
+            create_token_balance(1, mint_a, owner_a, 40),
+            create_token_balance(2, mint_a, owner_b, 60),
+            create_token_balance(3, mint_b, owner_a, 500),
+        ];
+        let message = Message::Transaction(message_transaction);
+
+        let cases = [
+            (mint_a, None, None, true),
+            (mint_a, Some(owner_a), None, true),
+            (mint_a, Some(owner_b), Some(60), true),
+            (mint_a, Some(owner_b), Some(61), false),
+            (mint_a, None, Some(61), false),
+            (mint_b, None, None, false),
+            (mint_b, Some(owner_b), None, false),
+            (Pubkey::new_unique(), None, None, false),
+        ];
+        for (mint, owner, min_delta, is_match) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    signer_include: vec![],
+                    fee_payer: vec![],
+                    program_include: vec![],
+                    program_exclude: vec![],
+                    inner_instructions: None,
+                    instruction_data: vec![],
+                    log_contains: vec![],
+                    log_regex: vec![],
+                    token_balance: vec![SubscribeRequestFilterTransactionsTokenBalance {
+                        mint: mint.to_string(),
+                        owner: owner.map(|owner: Pubkey| owner.to_string()),
+                        min_delta,
+                    }],
+                    compute_units_consumed: vec![],
+                    fee: vec![],
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let config = SubscribeRequest {
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+                transactions,
+                transactions_status: HashMap::new(),
+                blocks: HashMap::new(),
+                blocks_meta: HashMap::new(),
+                entry: HashMap::new(),
+                commitment: None,
+                accounts_data_slice: Vec::new(),
+                ping: None,
+                from_slot: None,
+                accounts_diff: None,
+                filter_delta: None,
+                credits: None,
+                transactions_commitment_updates: None,
+            };
+            let limit = FilterLimits::default();
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            )
+            .unwrap();
+
+            let updates = filter.get_updates(&message, None);
+            assert_eq!(updates.len(), usize::from(is_match));
+        }
+    }
+
+    #[test]
+    fn test_transaction_signer_fee_payer() {
+        let keypair = Keypair::new();
+        let account = Pubkey::new_unique();
+        let message = Message::Transaction(create_message_transaction(
+            &keypair,
+            vec![keypair.pubkey(), account],
+        ));
+
+        // second account is a signer too, decoded from header
+        let mut message_transaction =
+            create_message_transaction(&keypair, vec![keypair.pubkey(), account]);
+        let tx = Arc::get_mut(&mut message_transaction.transaction).unwrap();
+        tx.transaction
+            .message
+            .as_mut()
+            .unwrap()
+            .header
+            .as_mut()
+            .unwrap()
+            .num_required_signatures = 2;
+        tx.fill_account_keys().unwrap();
+        assert_eq!(tx.signers, vec![keypair.pubkey(), account]);
+        let message_multisig = Message::Transaction(message_transaction);
+
+        let cases = [
+            (vec![keypair.pubkey()], vec![], true, true),
+            (vec![account], vec![], false, true),
+            (vec![account, keypair.pubkey()], vec![], true, true),
+            (vec![], vec![keypair.pubkey()], true, true),
+            (vec![], vec![account], false, false),
+            (vec![account], vec![keypair.pubkey()], false, true),
+        ];
+        for (signer_include, fee_payer, is_match, is_match_multisig) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    signer_include: signer_include.iter().map(|s| s.to_string()).collect(),
+                    fee_payer: fee_payer.iter().map(|s| s.to_string()).collect(),
+                    program_include: vec![],
+                    program_exclude: vec![],
+                    inner_instructions: None,
+                    instruction_data: vec![],
+                    log_contains: vec![],
+                    log_regex: vec![],
+                    token_balance: vec![],
+                    compute_units_consumed: vec![],
+                    fee: vec![],
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let config = SubscribeRequest {
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+                transactions,
+                transactions_status: HashMap::new(),
+                blocks: HashMap::new(),
+                blocks_meta: HashMap::new(),
+                entry: HashMap::new(),
+                commitment: None,
+                accounts_data_slice: Vec::new(),
+                ping: None,
+                from_slot: None,
+                accounts_diff: None,
+                filter_delta: None,
+                credits: None,
+                transactions_commitment_updates: None,
+            };
+            let limit = FilterLimits::default();
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            )
+            .unwrap();
+
+            let updates = filter.get_updates(&message, None);
+            assert_eq!(updates.len(), usize::from(is_match));
+            let updates = filter.get_updates(&message_multisig, None);
+            assert_eq!(updates.len(), usize::from(is_match_multisig));
+        }
+    }
+
+    #[test]
+    fn test_transaction_account_exclude_program_signer() {
+        let keypair = Keypair::new();
+        let program_a = Pubkey::new_unique();
+        let program_b = Pubkey::new_unique();
+        let message = Message::Transaction(create_message_transaction_with_instructions(
+            &keypair, program_a, program_b,
+        ));
+
+        let other = Pubkey::new_unique();
+        let cases = [
+            (vec![other], vec![program_a], vec![], vec![], true),
+            (vec![program_b], vec![program_a], vec![], vec![], false),
+            (vec![program_a], vec![program_a], vec![], vec![], false),
+            (vec![other], vec![], vec![keypair.pubkey()], vec![], true),
+            (
+                vec![keypair.pubkey()],
+                vec![],
+                vec![keypair.pubkey()],
+                vec![],
+                false,
+            ),
+            (vec![other], vec![], vec![], vec![keypair.pubkey()], true),
+            (
+                vec![program_b],
+                vec![],
+                vec![],
+                vec![keypair.pubkey()],
+                false,
+            ),
+            (vec![program_a], vec![], vec![], vec![], false),
+            (vec![other], vec![], vec![], vec![], true),
+        ];
+        for (account_exclude, program_include, signer_include, fee_payer, is_match) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: account_exclude.iter().map(|k| k.to_string()).collect(),
+                    account_required: vec![],
+                    signer_include: signer_include.iter().map(|k| k.to_string()).collect(),
+                    fee_payer: fee_payer.iter().map(|k| k.to_string()).collect(),
+                    program_include: program_include.iter().map(|k| k.to_string()).collect(),
+                    program_exclude: vec![],
+                    inner_instructions: None,
+                    instruction_data: vec![],
+                    log_contains: vec![],
+                    log_regex: vec![],
+                    token_balance: vec![],
+                    compute_units_consumed: vec![],
+                    fee: vec![],
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let config = SubscribeRequest {
+                transactions,
+                ..Default::default()
+            };
+            // `account_exclude` alone narrows the stream
+            let mut limit = FilterLimits::default();
+            limit.transactions.any = false;
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            )
+            .unwrap();
+
+            let updates = filter.get_updates(&message, None);
+            assert_eq!(updates.len(), usize::from(is_match));
+        }
+    }
+
+    #[test]
+    fn test_transaction_compute_units_fee() {
+        let keypair = Keypair::new();
+        let mut message_transaction = create_message_transaction(&keypair, vec![keypair.pubkey()]);
+        Arc::get_mut(&mut message_transaction.transaction)
+            .unwrap()
+            .meta
+            .fee = 5_000;
+        let message_without_compute_units = Message::Transaction(message_transaction.clone());
+        Arc::make_mut(&mut message_transaction.transaction)
+            .meta
+            .compute_units_consumed = Some(150_000);
+        let message = Message::Transaction(message_transaction);
+
+        let cases = [
+            (vec![TransactionsFilterCmp::Eq(150_000)], vec![], true),
+            (
+                vec![
+                    TransactionsFilterCmp::Gt(100_000),
+                    TransactionsFilterCmp::Lt(200_000),
+                ],
+                vec![],
+                true,
+            ),
+            (vec![TransactionsFilterCmp::Gt(150_000)], vec![], false),
+            (vec![TransactionsFilterCmp::Lt(150_000)], vec![], false),
+            (vec![], vec![TransactionsFilterCmp::Ne(5_000)], false),
+            (vec![], vec![TransactionsFilterCmp::Lt(5_001)], true),
+            (
+                vec![TransactionsFilterCmp::Ne(0)],
+                vec![TransactionsFilterCmp::Gt(5_000)],
+                false,
+            ),
+        ];
+        for (compute_units_consumed, fee, is_match) in cases {
+            let mut transactions = HashMap::new();
+            transactions.insert(
+                "serum".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: None,
+                    failed: None,
+                    signature: None,
+                    account_include: vec![],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    signer_include: vec![],
+                    fee_payer: vec![],
+                    program_include: vec![],
+                    program_exclude: vec![],
+                    inner_instructions: None,
+                    instruction_data: vec![],
+                    log_contains: vec![],
+                    log_regex: vec![],
+                    token_balance: vec![],
+                    compute_units_consumed: compute_units_consumed
+                        .iter()
+                        .map(|cmp| SubscribeRequestFilterTransactionsCmp { cmp: Some(*cmp) })
+                        .collect(),
+                    fee: fee
+                        .iter()
+                        .map(|cmp| SubscribeRequestFilterTransactionsCmp { cmp: Some(*cmp) })
+                        .collect(),
+                    include_logs: None,
+                    include_inner_instructions: None,
+                    include_token_balances: None,
+                    include_rewards: None,
+                    commitment: None,
+                },
+            );
+
+            let mut config = SubscribeRequest {
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+                transactions,
+                transactions_status: HashMap::new(),
+                blocks: HashMap::new(),
+                blocks_meta: HashMap::new(),
+                entry: HashMap::new(),
+                commitment: None,
+                accounts_data_slice: Vec::new(),
+                ping: None,
+                from_slot: None,
+                accounts_diff: None,
+                filter_delta: None,
+                credits: None,
+                transactions_commitment_updates: None,
+            };
+            let limit = FilterLimits::default();
+            let filter = Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals(),
+            )
+            .unwrap();
+
+            let updates = filter.get_updates(&message, None);
+            assert_eq!(updates.len(), usize::from(is_match));
+
+            // compute units are not reported, so predicates can not match
+            let updates = filter.get_updates(&message_without_compute_units, None);
+            assert_eq!(
+                updates.len(),
+                usize::from(is_match && compute_units_consumed.is_empty())
+            );
+
+            // cmp should be always defined
+            let filter = config.transactions.get_mut("serum").unwrap();
+            filter
+                .fee
+                .push(SubscribeRequestFilterTransactionsCmp { cmp: None });
+            assert!(Filter::new(
+                &config,
+                &limit,
+                &mut create_filter_names(),
+                &create_mint_decimals()
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_transaction_mask() {
+        let keypair = Keypair::new();
+        let message =
+            Message::Transaction(create_message_transaction(&keypair, vec![keypair.pubkey()]));
+
+        let create_filter = |include_logs, include_rewards| SubscribeRequestFilterTransactions {
+            vote: None,
+            failed: None,
+            signature: None,
+            account_include: vec![],
+            account_exclude: vec![],
+            account_required: vec![],
+            signer_include: vec![],
+            fee_payer: vec![],
+            program_include: vec![],
+            program_exclude: vec![],
+            inner_instructions: None,
+            instruction_data: vec![],
+            log_contains: vec![],
+            log_regex: vec![],
+            token_balance: vec![],
+            compute_units_consumed: vec![],
+            fee: vec![],
+            include_logs,
+            include_inner_instructions: None,
+            include_token_balances: None,
+            include_rewards,
+            commitment: None,
+        };
+        let mut transactions = HashMap::new();
+        transactions.insert("full".to_owned(), create_filter(None, Some(true)));
+        transactions.insert("no_logs_a".to_owned(), create_filter(Some(false), None));
+        transactions.insert("no_logs_b".to_owned(), create_filter(Some(false), None));
+        let config = SubscribeRequest {
+            accounts: HashMap::new(),
+            slots: HashMap::new(),
+            transactions: transactions.clone(),
+            transactions_status: transactions,
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            accounts_diff: None,
+            filter_delta: None,
+            credits: None,
+            transactions_commitment_updates: None,
+        };
+        let limit = FilterLimits::default();
+        let filter = Filter::new(
+            &config,
+            &limit,
+            &mut create_filter_names(),
+            &create_mint_decimals(),
+        )
+        .unwrap();
+
+        let mut masks = vec![];
+        let mut statuses = 0;
+        for update in filter.get_updates(&message, None) {
+            let mut filters = update
+                .filters
+                .iter()
+                .map(|name| name.as_ref().to_owned())
+                .collect::<Vec<_>>();
+            filters.sort();
+            match update.message {
+                FilteredUpdateOneof::Transaction(tx) => masks.push((filters, tx.mask)),
+                FilteredUpdateOneof::TransactionStatus(_) => {
+                    // mask is ignored for transactions_status
+                    assert_eq!(filters, vec!["full", "no_logs_a", "no_logs_b"]);
+                    statuses += 1;
+                }
+                _ => panic!("expected transaction update"),
+            }
+        }
+        masks.sort_by_key(|(filters, _)| filters.clone());
+        assert_eq!(statuses, 1);
+        assert_eq!(
+            masks,
+            vec![
+                (
+                    vec!["full".to_owned()],
+                    FilteredUpdateTransactionMask::default()
+                ),
+                (
+                    vec!["no_logs_a".to_owned(), "no_logs_b".to_owned()],
+                    FilteredUpdateTransactionMask {
+                        logs: false,
+                        ..Default::default()
+                    }
+                ),
+            ]
+        );
+    }
 }
@@ -14,6 +14,10 @@ pub enum FilterLimitsCheckError {
     MaxPubkey { max: usize },
     #[error("Pubkey {pubkey} in filters is not allowed")]
     PubkeyReject { pubkey: Pubkey },
+    #[error("Max length of pattern reached, only {max} bytes allowed")]
+    MaxLength { max: usize },
+    #[error("Max depth of nested filters reached, only {max} allowed")]
+    MaxDepth { max: usize },
 }
 
 pub type FilterLimitsCheckResult = Result<(), FilterLimitsCheckError>;
@@ -55,6 +59,22 @@ impl FilterLimits {
         }
     }
 
+    pub const fn check_length_max(len: usize, max: usize) -> FilterLimitsCheckResult {
+        if len <= max {
+            Ok(())
+        } else {
+            Err(FilterLimitsCheckError::MaxLength { max })
+        }
+    }
+
+    pub const fn check_depth_max(depth: usize, max: usize) -> FilterLimitsCheckResult {
+        if depth <= max {
+            Ok(())
+        } else {
+            Err(FilterLimitsCheckError::MaxDepth { max })
+        }
+    }
+
     pub fn check_pubkey_reject(pubkey: &Pubkey, set: &HashSet<Pubkey>) -> FilterLimitsCheckResult {
         if !set.contains(pubkey) {
             Ok(())
@@ -76,6 +96,13 @@ pub struct FilterLimitsAccounts {
     #[serde(deserialize_with = "deserialize_pubkey_set")]
     pub owner_reject: HashSet<Pubkey>,
     pub data_slice_max: usize,
+    pub filters_depth_max: usize,
+    pub filters_total_max: usize,
+    pub lamports_delta_cache_max: usize,
+    pub diff_keyframe_interval: usize,
+    pub diff_cache_size_max: usize,
+    pub json_parsed: bool,
+    pub json_parsed_mint_cache_max: usize,
 }
 
 impl Default for FilterLimitsAccounts {
@@ -88,6 +115,13 @@ impl Default for FilterLimitsAccounts {
             owner_max: usize::MAX,
             owner_reject: HashSet::new(),
             data_slice_max: usize::MAX,
+            filters_depth_max: usize::MAX,
+            filters_total_max: usize::MAX,
+            lamports_delta_cache_max: 100_000,
+            diff_keyframe_interval: 100,
+            diff_cache_size_max: 64 * 1024 * 1024,
+            json_parsed: true,
+            json_parsed_mint_cache_max: 100_000,
         }
     }
 }
@@ -119,6 +153,32 @@ pub struct FilterLimitsTransactions {
     pub account_exclude_max: usize,
     #[serde(deserialize_with = "deserialize_usize_str")]
     pub account_required_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub program_include_max: usize,
+    #[serde(deserialize_with = "deserialize_pubkey_set")]
+    pub program_include_reject: HashSet<Pubkey>,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub program_exclude_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub instruction_data_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub log_contains_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub log_contains_length_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub log_regex_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub log_regex_length_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub token_balance_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub signer_include_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub fee_payer_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub compute_units_consumed_cmp_max: usize,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub fee_cmp_max: usize,
 }
 
 impl Default for FilterLimitsTransactions {
@@ -130,6 +190,19 @@ impl Default for FilterLimitsTransactions {
             account_include_reject: HashSet::new(),
             account_exclude_max: usize::MAX,
             account_required_max: usize::MAX,
+            program_include_max: usize::MAX,
+            program_include_reject: HashSet::new(),
+            program_exclude_max: usize::MAX,
+            instruction_data_max: usize::MAX,
+            log_contains_max: usize::MAX,
+            log_contains_length_max: usize::MAX,
+            log_regex_max: usize::MAX,
+            log_regex_length_max: usize::MAX,
+            token_balance_max: usize::MAX,
+            signer_include_max: usize::MAX,
+            fee_payer_max: usize::MAX,
+            compute_units_consumed_cmp_max: usize::MAX,
+            fee_cmp_max: usize::MAX,
         }
     }
 }
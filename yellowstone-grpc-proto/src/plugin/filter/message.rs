@@ -1,17 +1,22 @@
 use {
     crate::{
+        account_diff::apply_patches,
         geyser::{
             subscribe_update::UpdateOneof, CommitmentLevel as CommitmentLevelProto,
-            SubscribeUpdate, SubscribeUpdateAccount, SubscribeUpdateAccountInfo,
-            SubscribeUpdateBlock, SubscribeUpdateEntry, SubscribeUpdatePing, SubscribeUpdatePong,
-            SubscribeUpdateSlot, SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
-            SubscribeUpdateTransactionStatus,
+            SubscribeUpdate, SubscribeUpdateAccount, SubscribeUpdateAccountDiff,
+            SubscribeUpdateAccountDiffPatch, SubscribeUpdateAccountInfo,
+            SubscribeUpdateAccountInfoParsed, SubscribeUpdateBlock, SubscribeUpdateEntry,
+            SubscribeUpdateLagged, SubscribeUpdatePing, SubscribeUpdatePong,
+            SubscribeUpdateRateLimited, SubscribeUpdateSlot, SubscribeUpdateSlotRollback,
+            SubscribeUpdateTransaction, SubscribeUpdateTransactionCommitment,
+            SubscribeUpdateTransactionInfo, SubscribeUpdateTransactionStatus,
         },
         plugin::{
             filter::{name::FilterName, FilterAccountsDataSlice},
             message::{
-                MessageAccount, MessageAccountInfo, MessageBlock, MessageBlockMeta, MessageEntry,
-                MessageSlot, MessageTransaction, MessageTransactionInfo,
+                CommitmentLevel, MessageAccount, MessageAccountInfo, MessageBlock,
+                MessageBlockMeta, MessageEncodedCache, MessageEntry, MessageSlot,
+                MessageSlotRollback, MessageTransaction, MessageTransactionInfo,
             },
         },
         solana::storage::confirmed_block,
@@ -28,7 +33,7 @@ use {
     solana_sdk::signature::Signature,
     std::{
         collections::HashSet,
-        ops::{Deref, DerefMut},
+        ops::{Deref, DerefMut, Range},
         sync::Arc,
     },
 };
@@ -67,6 +72,7 @@ pub type FilteredUpdates = SmallVec<[FilteredUpdate; 2]>;
 pub struct FilteredUpdate {
     pub filters: FilteredUpdateFilters,
     pub message: FilteredUpdateOneof,
+    pub commitment: Option<CommitmentLevel>,
 }
 
 impl prost::Message for FilteredUpdate {
@@ -76,12 +82,20 @@ impl prost::Message for FilteredUpdate {
             encode_varint(name.len() as u64, buf);
             buf.put_slice(name.as_bytes());
         }
-        self.message.encode_raw(buf)
+        self.message.encode_raw(buf);
+        if let Some(commitment) = self.commitment {
+            let commitment = CommitmentLevelProto::from(commitment) as i32;
+            ::prost::encoding::int32::encode(14u32, &commitment, buf);
+        }
     }
 
     fn encoded_len(&self) -> usize {
         prost_repeated_encoded_len_map!(1u32, self.filters, |filter| filter.as_ref().len())
             + self.message.encoded_len()
+            + self.commitment.map_or(0, |commitment| {
+                let commitment = CommitmentLevelProto::from(commitment) as i32;
+                ::prost::encoding::int32::encoded_len(14u32, &commitment)
+            })
     }
 
     fn merge_field(
@@ -101,7 +115,16 @@ impl prost::Message for FilteredUpdate {
 
 impl FilteredUpdate {
     pub const fn new(filters: FilteredUpdateFilters, message: FilteredUpdateOneof) -> Self {
-        Self { filters, message }
+        Self {
+            filters,
+            message,
+            commitment: None,
+        }
+    }
+
+    pub const fn with_commitment(mut self, commitment: Option<CommitmentLevel>) -> Self {
+        self.commitment = commitment;
+        self
     }
 
     pub fn new_empty(message: FilteredUpdateOneof) -> Self {
@@ -110,7 +133,8 @@ impl FilteredUpdate {
 
     fn as_subscribe_update_account(
         message: &MessageAccountInfo,
-        data_slice: &FilterAccountsDataSlice,
+        data_slice: Option<&FilterAccountsDataSlice>,
+        parsed: Option<&SubscribeUpdateAccountInfoParsed>,
     ) -> SubscribeUpdateAccountInfo {
         SubscribeUpdateAccountInfo {
             pubkey: message.pubkey.as_ref().into(),
@@ -118,20 +142,27 @@ impl FilteredUpdate {
             owner: message.owner.as_ref().into(),
             executable: message.executable,
             rent_epoch: message.rent_epoch,
-            data: data_slice.get_slice(&message.data),
+            data: data_slice
+                .map(|data_slice| data_slice.get_slice(&message.data))
+                .unwrap_or_default(),
             write_version: message.write_version,
             txn_signature: message.txn_signature.map(|s| s.as_ref().into()),
+            parsed: parsed.cloned(),
         }
     }
 
     fn as_subscribe_update_transaction(
         message: &MessageTransactionInfo,
+        mask: FilteredUpdateTransactionMask,
     ) -> SubscribeUpdateTransactionInfo {
+        let mut meta = message.meta.clone();
+        mask.apply(&mut meta);
+
         SubscribeUpdateTransactionInfo {
             signature: message.signature.as_ref().into(),
             is_vote: message.is_vote,
             transaction: Some(message.transaction.clone()),
-            meta: Some(message.meta.clone()),
+            meta: Some(meta),
             index: message.index as u64,
         }
     }
@@ -152,11 +183,32 @@ impl FilteredUpdate {
             FilteredUpdateOneof::Account(msg) => UpdateOneof::Account(SubscribeUpdateAccount {
                 account: Some(Self::as_subscribe_update_account(
                     msg.account.as_ref(),
-                    &msg.data_slice,
+                    msg.data_slice.as_ref(),
+                    msg.parsed.as_deref(),
                 )),
                 slot: msg.slot,
                 is_startup: msg.is_startup,
             }),
+            FilteredUpdateOneof::AccountDiff(msg) => {
+                UpdateOneof::AccountDiff(SubscribeUpdateAccountDiff {
+                    account: Some(Self::as_subscribe_update_account(
+                        msg.account.as_ref(),
+                        None,
+                        None,
+                    )),
+                    slot: msg.slot,
+                    is_startup: msg.is_startup,
+                    data_len: msg.account.data.len() as u64,
+                    patches: msg
+                        .patches
+                        .iter()
+                        .map(|patch| SubscribeUpdateAccountDiffPatch {
+                            offset: patch.start as u64,
+                            data: msg.account.data[patch.clone()].to_vec(),
+                        })
+                        .collect(),
+                })
+            }
             FilteredUpdateOneof::Slot(msg) => UpdateOneof::Slot(SubscribeUpdateSlot {
                 slot: msg.slot,
                 parent: msg.parent,
@@ -167,6 +219,7 @@ impl FilteredUpdate {
                 UpdateOneof::Transaction(SubscribeUpdateTransaction {
                     transaction: Some(Self::as_subscribe_update_transaction(
                         msg.transaction.as_ref(),
+                        msg.mask,
                     )),
                     slot: msg.slot,
                 })
@@ -192,14 +245,23 @@ impl FilteredUpdate {
                 transactions: msg
                     .transactions
                     .iter()
-                    .map(|tx| Self::as_subscribe_update_transaction(tx.as_ref()))
+                    .map(|tx| {
+                        Self::as_subscribe_update_transaction(
+                            tx.as_ref(),
+                            FilteredUpdateTransactionMask::default(),
+                        )
+                    })
                     .collect(),
                 updated_account_count: msg.updated_account_count,
                 accounts: msg
                     .accounts
                     .iter()
                     .map(|acc| {
-                        Self::as_subscribe_update_account(acc.as_ref(), &msg.accounts_data_slice)
+                        Self::as_subscribe_update_account(
+                            acc.as_ref(),
+                            Some(&msg.accounts_data_slice),
+                            None,
+                        )
                     })
                     .collect(),
                 entries_count: msg.meta.entries_count,
@@ -211,6 +273,12 @@ impl FilteredUpdate {
             }),
             FilteredUpdateOneof::Ping => UpdateOneof::Ping(SubscribeUpdatePing {}),
             FilteredUpdateOneof::Pong(msg) => UpdateOneof::Pong(*msg),
+            FilteredUpdateOneof::RateLimited(msg) => UpdateOneof::RateLimited(*msg),
+            FilteredUpdateOneof::Lagged(msg) => UpdateOneof::Lagged(*msg),
+            FilteredUpdateOneof::TransactionCommitment(msg) => {
+                UpdateOneof::TransactionCommitment(msg.clone())
+            }
+            FilteredUpdateOneof::SlotRollback(msg) => UpdateOneof::SlotRollback(*msg),
             FilteredUpdateOneof::BlockMeta(msg) => UpdateOneof::BlockMeta(msg.0.clone()),
             FilteredUpdateOneof::Entry(msg) => {
                 UpdateOneof::Entry(Self::as_subscribe_update_entry(&msg.0))
@@ -224,20 +292,35 @@ impl FilteredUpdate {
                 .map(|name| name.as_ref().to_string())
                 .collect(),
             update_oneof: Some(message),
+            commitment: self
+                .commitment
+                .map(|commitment| CommitmentLevelProto::from(commitment) as i32),
         }
     }
 
     pub fn from_subscribe_update(update: SubscribeUpdate) -> Result<Self, &'static str> {
         let message = match update.update_oneof.ok_or("")? {
-            UpdateOneof::Account(msg) => {
+            UpdateOneof::Account(mut msg) => {
+                let parsed = msg
+                    .account
+                    .as_mut()
+                    .and_then(|account| account.parsed.take());
+                let data_slice = match &msg.account {
+                    Some(account) if parsed.is_some() && account.data.is_empty() => None,
+                    _ => Some(FilterAccountsDataSlice::default()),
+                };
                 let account = MessageAccount::from_update_oneof(msg)?;
                 FilteredUpdateOneof::Account(FilteredUpdateAccount {
                     account: account.account,
                     slot: account.slot,
                     is_startup: account.is_startup,
-                    data_slice: FilterAccountsDataSlice::default(),
+                    data_slice,
+                    parsed: parsed.map(Arc::new),
                 })
             }
+            UpdateOneof::AccountDiff(msg) => {
+                FilteredUpdateOneof::AccountDiff(FilteredUpdateAccountDiff::from_update_oneof(msg)?)
+            }
             UpdateOneof::Slot(msg) => {
                 let slot = MessageSlot::from_update_oneof(&msg)?;
                 FilteredUpdateOneof::Slot(FilteredUpdateSlot(slot))
@@ -247,6 +330,7 @@ impl FilteredUpdate {
                 FilteredUpdateOneof::Transaction(FilteredUpdateTransaction {
                     transaction: tx.transaction,
                     slot: tx.slot,
+                    mask: FilteredUpdateTransactionMask::default(),
                 })
             }
             UpdateOneof::TransactionStatus(msg) => {
@@ -262,6 +346,8 @@ impl FilteredUpdate {
                         },
                         index: msg.index as usize,
                         account_keys: HashSet::new(),
+                        signers: Vec::new(),
+                        encoded: MessageEncodedCache::default(),
                     }),
                     slot: msg.slot,
                 })
@@ -279,6 +365,12 @@ impl FilteredUpdate {
             }
             UpdateOneof::Ping(_) => FilteredUpdateOneof::Ping,
             UpdateOneof::Pong(msg) => FilteredUpdateOneof::Pong(msg),
+            UpdateOneof::RateLimited(msg) => FilteredUpdateOneof::RateLimited(msg),
+            UpdateOneof::Lagged(msg) => FilteredUpdateOneof::Lagged(msg),
+            UpdateOneof::TransactionCommitment(msg) => {
+                FilteredUpdateOneof::TransactionCommitment(msg)
+            }
+            UpdateOneof::SlotRollback(msg) => FilteredUpdateOneof::SlotRollback(msg),
             UpdateOneof::BlockMeta(msg) => {
                 let block_meta = MessageBlockMeta(msg);
                 FilteredUpdateOneof::BlockMeta(Arc::new(block_meta))
@@ -289,9 +381,19 @@ impl FilteredUpdate {
             }
         };
 
+        let commitment = update
+            .commitment
+            .map(|commitment| {
+                CommitmentLevelProto::try_from(commitment)
+                    .map(Into::into)
+                    .map_err(|_| "failed to parse commitment")
+            })
+            .transpose()?;
+
         Ok(Self {
             filters: update.filters.into_iter().map(FilterName::new).collect(),
             message,
+            commitment,
         })
     }
 }
@@ -300,24 +402,47 @@ pub type FilteredUpdateFilters = SmallVec<[FilterName; 4]>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum FilteredUpdateOneof {
-    Account(FilteredUpdateAccount),                     // 2
-    Slot(FilteredUpdateSlot),                           // 3
-    Transaction(FilteredUpdateTransaction),             // 4
-    TransactionStatus(FilteredUpdateTransactionStatus), // 10
-    Block(Box<FilteredUpdateBlock>),                    // 5
-    Ping,                                               // 6
-    Pong(SubscribeUpdatePong),                          // 9
-    BlockMeta(Arc<MessageBlockMeta>),                   // 7
-    Entry(FilteredUpdateEntry),                         // 8
+    Account(FilteredUpdateAccount),                              // 2
+    AccountDiff(FilteredUpdateAccountDiff),                      // 11
+    Slot(FilteredUpdateSlot),                                    // 3
+    Transaction(FilteredUpdateTransaction),                      // 4
+    TransactionStatus(FilteredUpdateTransactionStatus),          // 10
+    Block(Box<FilteredUpdateBlock>),                             // 5
+    Ping,                                                        // 6
+    Pong(SubscribeUpdatePong),                                   // 9
+    RateLimited(SubscribeUpdateRateLimited),                     // 12
+    Lagged(SubscribeUpdateLagged),                               // 13
+    TransactionCommitment(SubscribeUpdateTransactionCommitment), // 15
+    SlotRollback(SubscribeUpdateSlotRollback),                   // 16
+    BlockMeta(Arc<MessageBlockMeta>),                            // 7
+    Entry(FilteredUpdateEntry),                                  // 8
 }
 
 impl FilteredUpdateOneof {
     pub fn account(message: &MessageAccount, data_slice: FilterAccountsDataSlice) -> Self {
+        Self::account_encoded(message, Some(data_slice), None)
+    }
+
+    pub fn account_encoded(
+        message: &MessageAccount,
+        data_slice: Option<FilterAccountsDataSlice>,
+        parsed: Option<Arc<SubscribeUpdateAccountInfoParsed>>,
+    ) -> Self {
         Self::Account(FilteredUpdateAccount {
             slot: message.slot,
             account: Arc::clone(&message.account),
             is_startup: message.is_startup,
             data_slice,
+            parsed,
+        })
+    }
+
+    pub fn account_diff(message: &MessageAccount, patches: Vec<Range<usize>>) -> Self {
+        Self::AccountDiff(FilteredUpdateAccountDiff {
+            account: Arc::clone(&message.account),
+            slot: message.slot,
+            is_startup: message.is_startup,
+            patches,
         })
     }
 
@@ -326,9 +451,17 @@ impl FilteredUpdateOneof {
     }
 
     pub fn transaction(message: &MessageTransaction) -> Self {
+        Self::transaction_masked(message, FilteredUpdateTransactionMask::default())
+    }
+
+    pub fn transaction_masked(
+        message: &MessageTransaction,
+        mask: FilteredUpdateTransactionMask,
+    ) -> Self {
         Self::Transaction(FilteredUpdateTransaction {
             transaction: Arc::clone(&message.transaction),
             slot: message.slot,
+            mask,
         })
     }
 
@@ -351,6 +484,46 @@ impl FilteredUpdateOneof {
         Self::Pong(SubscribeUpdatePong { id })
     }
 
+    pub const fn rate_limited(dropped_messages: u64, dropped_bytes: u64) -> Self {
+        Self::RateLimited(SubscribeUpdateRateLimited {
+            dropped_messages,
+            dropped_bytes,
+        })
+    }
+
+    pub const fn lagged(
+        skipped_messages: u64,
+        skipped_batches: u64,
+        from_slot: u64,
+        to_slot: u64,
+    ) -> Self {
+        Self::Lagged(SubscribeUpdateLagged {
+            skipped_messages,
+            from_slot,
+            to_slot,
+            skipped_batches,
+        })
+    }
+
+    pub fn transaction_commitment(
+        signature: &Signature,
+        slot: u64,
+        status: CommitmentLevel,
+    ) -> Self {
+        Self::TransactionCommitment(SubscribeUpdateTransactionCommitment {
+            signature: signature.as_ref().into(),
+            slot,
+            status: CommitmentLevelProto::from(status) as i32,
+        })
+    }
+
+    pub const fn slot_rollback(message: &MessageSlotRollback) -> Self {
+        Self::SlotRollback(SubscribeUpdateSlotRollback {
+            slot: message.slot,
+            reason: message.reason as i32,
+        })
+    }
+
     pub const fn block_meta(message: Arc<MessageBlockMeta>) -> Self {
         Self::BlockMeta(message)
     }
@@ -364,6 +537,7 @@ impl prost::Message for FilteredUpdateOneof {
     fn encode_raw(&self, buf: &mut impl BufMut) {
         match self {
             Self::Account(msg) => message::encode(2u32, msg, buf),
+            Self::AccountDiff(msg) => message::encode(11u32, msg, buf),
             Self::Slot(msg) => message::encode(3u32, msg, buf),
             Self::Transaction(msg) => message::encode(4u32, msg, buf),
             Self::TransactionStatus(msg) => message::encode(10u32, msg, buf),
@@ -373,6 +547,10 @@ impl prost::Message for FilteredUpdateOneof {
                 encode_varint(0, buf);
             }
             Self::Pong(msg) => message::encode(9u32, msg, buf),
+            Self::RateLimited(msg) => message::encode(12u32, msg, buf),
+            Self::Lagged(msg) => message::encode(13u32, msg, buf),
+            Self::TransactionCommitment(msg) => message::encode(15u32, msg, buf),
+            Self::SlotRollback(msg) => message::encode(16u32, msg, buf),
             Self::BlockMeta(msg) => message::encode(7u32, &msg.0, buf),
             Self::Entry(msg) => message::encode(8u32, msg, buf),
         }
@@ -381,12 +559,17 @@ impl prost::Message for FilteredUpdateOneof {
     fn encoded_len(&self) -> usize {
         match self {
             Self::Account(msg) => message::encoded_len(2u32, msg),
+            Self::AccountDiff(msg) => message::encoded_len(11u32, msg),
             Self::Slot(msg) => message::encoded_len(3u32, msg),
             Self::Transaction(msg) => message::encoded_len(4u32, msg),
             Self::TransactionStatus(msg) => message::encoded_len(10u32, msg),
             Self::Block(msg) => message::encoded_len(5u32, msg),
             Self::Ping => key_len(6u32) + encoded_len_varint(0),
             Self::Pong(msg) => message::encoded_len(9u32, msg),
+            Self::RateLimited(msg) => message::encoded_len(12u32, msg),
+            Self::Lagged(msg) => message::encoded_len(13u32, msg),
+            Self::TransactionCommitment(msg) => message::encoded_len(15u32, msg),
+            Self::SlotRollback(msg) => message::encoded_len(16u32, msg),
             Self::BlockMeta(msg) => message::encoded_len(7u32, &msg.0),
             Self::Entry(msg) => message::encoded_len(8u32, msg),
         }
@@ -412,12 +595,20 @@ pub struct FilteredUpdateAccount {
     pub account: Arc<MessageAccountInfo>,
     pub slot: u64,
     pub is_startup: bool,
-    pub data_slice: FilterAccountsDataSlice,
+    // raw data is not sent if data slice is not set
+    pub data_slice: Option<FilterAccountsDataSlice>,
+    pub parsed: Option<Arc<SubscribeUpdateAccountInfoParsed>>,
 }
 
 impl prost::Message for FilteredUpdateAccount {
     fn encode_raw(&self, buf: &mut impl BufMut) {
-        Self::account_encode_raw(1u32, &self.account, &self.data_slice, buf);
+        Self::account_encode_raw(
+            1u32,
+            &self.account,
+            self.data_slice.as_ref(),
+            self.parsed.as_deref(),
+            buf,
+        );
         if self.slot != 0u64 {
             ::prost::encoding::uint64::encode(2u32, &self.slot, buf);
         }
@@ -429,7 +620,11 @@ impl prost::Message for FilteredUpdateAccount {
     fn encoded_len(&self) -> usize {
         prost_field_encoded_len(
             1u32,
-            Self::account_encoded_len(&self.account, &self.data_slice),
+            Self::account_encoded_len(
+                &self.account,
+                self.data_slice.as_ref(),
+                self.parsed.as_deref(),
+            ),
         ) + if self.slot != 0u64 {
             ::prost::encoding::uint64::encoded_len(2u32, &self.slot)
         } else {
@@ -457,15 +652,63 @@ impl prost::Message for FilteredUpdateAccount {
 }
 
 impl FilteredUpdateAccount {
+    // data is not encoded without data slice
     fn account_encode_raw(
         tag: u32,
         account: &MessageAccountInfo,
-        data_slice: &FilterAccountsDataSlice,
+        data_slice: Option<&FilterAccountsDataSlice>,
+        parsed: Option<&SubscribeUpdateAccountInfoParsed>,
         buf: &mut impl BufMut,
     ) {
         encode_key(tag, WireType::LengthDelimited, buf);
-        encode_varint(Self::account_encoded_len(account, data_slice) as u64, buf);
+        if let Some(encoded) = Self::account_encoded(account, data_slice, parsed) {
+            encode_varint(encoded.len() as u64, buf);
+            buf.put_slice(encoded);
+        } else {
+            encode_varint(
+                Self::account_fields_encoded_len(account, data_slice, parsed) as u64,
+                buf,
+            );
+            Self::account_fields_encode_raw(account, data_slice, parsed, buf);
+        }
+    }
+
+    fn account_encoded_len(
+        account: &MessageAccountInfo,
+        data_slice: Option<&FilterAccountsDataSlice>,
+        parsed: Option<&SubscribeUpdateAccountInfoParsed>,
+    ) -> usize {
+        match Self::account_encoded(account, data_slice, parsed) {
+            Some(encoded) => encoded.len(),
+            None => Self::account_fields_encoded_len(account, data_slice, parsed),
+        }
+    }
 
+    // only full account without parsed data is shared between clients
+    fn account_encoded<'a>(
+        account: &'a MessageAccountInfo,
+        data_slice: Option<&FilterAccountsDataSlice>,
+        parsed: Option<&SubscribeUpdateAccountInfoParsed>,
+    ) -> Option<&'a [u8]> {
+        let data_slice =
+            data_slice.filter(|data_slice| data_slice.as_ref().is_empty() && parsed.is_none())?;
+        Some(account.encoded.get_or_encode(|| {
+            let mut buf = Vec::with_capacity(Self::account_fields_encoded_len(
+                account,
+                Some(data_slice),
+                None,
+            ));
+            Self::account_fields_encode_raw(account, Some(data_slice), None, &mut buf);
+            buf
+        }))
+    }
+
+    fn account_fields_encode_raw(
+        account: &MessageAccountInfo,
+        data_slice: Option<&FilterAccountsDataSlice>,
+        parsed: Option<&SubscribeUpdateAccountInfoParsed>,
+        buf: &mut impl BufMut,
+    ) {
         prost_bytes_encode_raw(1u32, account.pubkey.as_ref(), buf);
         if account.lamports != 0u64 {
             ::prost::encoding::uint64::encode(2u32, &account.lamports, buf);
@@ -477,20 +720,26 @@ impl FilteredUpdateAccount {
         if account.rent_epoch != 0u64 {
             ::prost::encoding::uint64::encode(5u32, &account.rent_epoch, buf);
         }
-        data_slice.slice_encode_raw(6u32, &account.data, buf);
+        if let Some(data_slice) = data_slice {
+            data_slice.slice_encode_raw(6u32, &account.data, buf);
+        }
         if account.write_version != 0u64 {
             ::prost::encoding::uint64::encode(7u32, &account.write_version, buf);
         }
         if let Some(value) = &account.txn_signature {
             prost_bytes_encode_raw(8u32, value.as_ref(), buf);
         }
+        if let Some(parsed) = parsed {
+            message::encode(9u32, parsed, buf);
+        }
     }
 
-    fn account_encoded_len(
+    fn account_fields_encoded_len(
         account: &MessageAccountInfo,
-        data_slice: &FilterAccountsDataSlice,
+        data_slice: Option<&FilterAccountsDataSlice>,
+        parsed: Option<&SubscribeUpdateAccountInfoParsed>,
     ) -> usize {
-        let data_len = data_slice.get_slice_len(&account.data);
+        let data_len = data_slice.map_or(0, |data_slice| data_slice.get_slice_len(&account.data));
 
         prost_bytes_encoded_len(1u32, account.pubkey.as_ref())
             + if account.lamports != 0u64 {
@@ -522,6 +771,119 @@ impl FilteredUpdateAccount {
             + account
                 .txn_signature
                 .map_or(0, |sig| prost_bytes_encoded_len(8u32, sig.as_ref()))
+            + parsed.map_or(0, |parsed| message::encoded_len(9u32, parsed))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilteredUpdateAccountDiff {
+    pub account: Arc<MessageAccountInfo>,
+    pub slot: u64,
+    pub is_startup: bool,
+    // ranges of changed bytes in `account.data`
+    pub patches: Vec<Range<usize>>,
+}
+
+impl prost::Message for FilteredUpdateAccountDiff {
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        FilteredUpdateAccount::account_encode_raw(1u32, &self.account, None, None, buf);
+        if self.slot != 0u64 {
+            ::prost::encoding::uint64::encode(2u32, &self.slot, buf);
+        }
+        if self.is_startup {
+            ::prost::encoding::bool::encode(3u32, &self.is_startup, buf);
+        }
+        let data_len = self.account.data.len() as u64;
+        if data_len != 0u64 {
+            ::prost::encoding::uint64::encode(4u32, &data_len, buf);
+        }
+        for patch in self.patches.iter() {
+            encode_key(5u32, WireType::LengthDelimited, buf);
+            encode_varint(Self::patch_encoded_len(patch) as u64, buf);
+            let offset = patch.start as u64;
+            if offset != 0u64 {
+                ::prost::encoding::uint64::encode(1u32, &offset, buf);
+            }
+            if !patch.is_empty() {
+                prost_bytes_encode_raw(2u32, &self.account.data[patch.clone()], buf);
+            }
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        let data_len = self.account.data.len() as u64;
+
+        prost_field_encoded_len(
+            1u32,
+            FilteredUpdateAccount::account_encoded_len(&self.account, None, None),
+        ) + if self.slot != 0u64 {
+            ::prost::encoding::uint64::encoded_len(2u32, &self.slot)
+        } else {
+            0
+        } + if self.is_startup {
+            ::prost::encoding::bool::encoded_len(3u32, &self.is_startup)
+        } else {
+            0
+        } + if data_len != 0u64 {
+            ::prost::encoding::uint64::encoded_len(4u32, &data_len)
+        } else {
+            0
+        } + prost_repeated_encoded_len_map!(5u32, self.patches, Self::patch_encoded_len)
+    }
+
+    fn merge_field(
+        &mut self,
+        _tag: u32,
+        _wire_type: WireType,
+        _buf: &mut impl Buf,
+        _ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        unimplemented!()
+    }
+
+    fn clear(&mut self) {
+        unimplemented!()
+    }
+}
+
+impl FilteredUpdateAccountDiff {
+    fn patch_encoded_len(patch: &Range<usize>) -> usize {
+        let offset = patch.start as u64;
+
+        (if offset != 0u64 {
+            ::prost::encoding::uint64::encoded_len(1u32, &offset)
+        } else {
+            0
+        }) + if !patch.is_empty() {
+            prost_field_encoded_len(2u32, patch.len())
+        } else {
+            0
+        }
+    }
+
+    // data outside of patches is unknown and filled with zeros
+    fn from_update_oneof(msg: SubscribeUpdateAccountDiff) -> Result<Self, &'static str> {
+        let mut data = vec![];
+        apply_patches(&mut data, msg.data_len, &msg.patches)?;
+        let patches = msg
+            .patches
+            .iter()
+            .map(|patch| {
+                let start = patch.offset as usize;
+                start..start + patch.data.len()
+            })
+            .collect();
+
+        let mut account =
+            MessageAccountInfo::from_update_oneof(msg.account.ok_or("account should be defined")?)?;
+        account.data = data;
+
+        Ok(Self {
+            account: Arc::new(account),
+            slot: msg.slot,
+            is_startup: msg.is_startup,
+            patches,
+        })
     }
 }
 
@@ -598,18 +960,19 @@ impl prost::Message for FilteredUpdateSlot {
 pub struct FilteredUpdateTransaction {
     pub transaction: Arc<MessageTransactionInfo>,
     pub slot: u64,
+    pub mask: FilteredUpdateTransactionMask,
 }
 
 impl prost::Message for FilteredUpdateTransaction {
     fn encode_raw(&self, buf: &mut impl BufMut) {
-        Self::tx_encode_raw(1u32, &self.transaction, buf);
+        Self::tx_encode_raw(1u32, &self.transaction, self.mask, buf);
         if self.slot != 0u64 {
             ::prost::encoding::uint64::encode(2u32, &self.slot, buf);
         }
     }
 
     fn encoded_len(&self) -> usize {
-        prost_field_encoded_len(1u32, Self::tx_encoded_len(&self.transaction))
+        prost_field_encoded_len(1u32, Self::tx_encoded_len(&self.transaction, self.mask))
             + if self.slot != 0u64 {
                 ::prost::encoding::uint64::encoded_len(2u32, &self.slot)
             } else {
@@ -633,10 +996,50 @@ impl prost::Message for FilteredUpdateTransaction {
 }
 
 impl FilteredUpdateTransaction {
-    fn tx_encode_raw(tag: u32, tx: &MessageTransactionInfo, buf: &mut impl BufMut) {
+    fn tx_encode_raw(
+        tag: u32,
+        tx: &MessageTransactionInfo,
+        mask: FilteredUpdateTransactionMask,
+        buf: &mut impl BufMut,
+    ) {
         encode_key(tag, WireType::LengthDelimited, buf);
-        encode_varint(Self::tx_encoded_len(tx) as u64, buf);
+        if let Some(encoded) = Self::tx_encoded(tx, mask) {
+            encode_varint(encoded.len() as u64, buf);
+            buf.put_slice(encoded);
+        } else {
+            encode_varint(Self::tx_fields_encoded_len(tx, mask) as u64, buf);
+            Self::tx_fields_encode_raw(tx, mask, buf);
+        }
+    }
+
+    fn tx_encoded_len(tx: &MessageTransactionInfo, mask: FilteredUpdateTransactionMask) -> usize {
+        match Self::tx_encoded(tx, mask) {
+            Some(encoded) => encoded.len(),
+            None => Self::tx_fields_encoded_len(tx, mask),
+        }
+    }
 
+    // only transaction with full meta is shared between clients
+    fn tx_encoded(
+        tx: &MessageTransactionInfo,
+        mask: FilteredUpdateTransactionMask,
+    ) -> Option<&[u8]> {
+        if !mask.is_full() {
+            return None;
+        }
+        Some(tx.encoded.get_or_encode(|| {
+            let mask = FilteredUpdateTransactionMask::default();
+            let mut buf = Vec::with_capacity(Self::tx_fields_encoded_len(tx, mask));
+            Self::tx_fields_encode_raw(tx, mask, &mut buf);
+            buf
+        }))
+    }
+
+    fn tx_fields_encode_raw(
+        tx: &MessageTransactionInfo,
+        mask: FilteredUpdateTransactionMask,
+        buf: &mut impl BufMut,
+    ) {
         let index = tx.index as u64;
 
         prost_bytes_encode_raw(1u32, tx.signature.as_ref(), buf);
@@ -644,13 +1047,20 @@ impl FilteredUpdateTransaction {
             ::prost::encoding::bool::encode(2u32, &tx.is_vote, buf);
         }
         message::encode(3u32, &tx.transaction, buf);
-        message::encode(4u32, &tx.meta, buf);
+        if mask.is_full() {
+            message::encode(4u32, &tx.meta, buf);
+        } else {
+            Self::meta_encode_raw(4u32, &tx.meta, mask, buf);
+        }
         if index != 0u64 {
             ::prost::encoding::uint64::encode(5u32, &index, buf);
         }
     }
 
-    fn tx_encoded_len(tx: &MessageTransactionInfo) -> usize {
+    fn tx_fields_encoded_len(
+        tx: &MessageTransactionInfo,
+        mask: FilteredUpdateTransactionMask,
+    ) -> usize {
         let index = tx.index as u64;
 
         prost_bytes_encoded_len(1u32, tx.signature.as_ref())
@@ -660,13 +1070,173 @@ impl FilteredUpdateTransaction {
                 0
             }
             + message::encoded_len(3u32, &tx.transaction)
-            + message::encoded_len(4u32, &tx.meta)
+            + if mask.is_full() {
+                message::encoded_len(4u32, &tx.meta)
+            } else {
+                prost_field_encoded_len(4u32, Self::meta_encoded_len(&tx.meta, mask))
+            }
             + if index != 0u64 {
                 ::prost::encoding::uint64::encoded_len(5u32, &index)
             } else {
                 0
             }
     }
+
+    // same as `TransactionStatusMeta::encode_raw` but without excluded fields, `*_none` flags
+    // are set for excluded inner instructions and logs
+    fn meta_encode_raw(
+        tag: u32,
+        meta: &confirmed_block::TransactionStatusMeta,
+        mask: FilteredUpdateTransactionMask,
+        buf: &mut impl BufMut,
+    ) {
+        encode_key(tag, WireType::LengthDelimited, buf);
+        encode_varint(Self::meta_encoded_len(meta, mask) as u64, buf);
+
+        if let Some(err) = &meta.err {
+            message::encode(1u32, err, buf);
+        }
+        if meta.fee != 0u64 {
+            ::prost::encoding::uint64::encode(2u32, &meta.fee, buf);
+        }
+        ::prost::encoding::uint64::encode_packed(3u32, &meta.pre_balances, buf);
+        ::prost::encoding::uint64::encode_packed(4u32, &meta.post_balances, buf);
+        if mask.inner_instructions {
+            message::encode_repeated(5u32, &meta.inner_instructions, buf);
+        }
+        if meta.inner_instructions_none || !mask.inner_instructions {
+            ::prost::encoding::bool::encode(10u32, &true, buf);
+        }
+        if mask.logs {
+            ::prost::encoding::string::encode_repeated(6u32, &meta.log_messages, buf);
+        }
+        if meta.log_messages_none || !mask.logs {
+            ::prost::encoding::bool::encode(11u32, &true, buf);
+        }
+        if mask.token_balances {
+            message::encode_repeated(7u32, &meta.pre_token_balances, buf);
+            message::encode_repeated(8u32, &meta.post_token_balances, buf);
+        }
+        if mask.rewards {
+            message::encode_repeated(9u32, &meta.rewards, buf);
+        }
+        ::prost::encoding::bytes::encode_repeated(12u32, &meta.loaded_writable_addresses, buf);
+        ::prost::encoding::bytes::encode_repeated(13u32, &meta.loaded_readonly_addresses, buf);
+        if let Some(return_data) = &meta.return_data {
+            message::encode(14u32, return_data, buf);
+        }
+        if meta.return_data_none {
+            ::prost::encoding::bool::encode(15u32, &meta.return_data_none, buf);
+        }
+        if let Some(compute_units_consumed) = &meta.compute_units_consumed {
+            ::prost::encoding::uint64::encode(16u32, compute_units_consumed, buf);
+        }
+    }
+
+    fn meta_encoded_len(
+        meta: &confirmed_block::TransactionStatusMeta,
+        mask: FilteredUpdateTransactionMask,
+    ) -> usize {
+        meta.err
+            .as_ref()
+            .map_or(0, |err| message::encoded_len(1u32, err))
+            + if meta.fee != 0u64 {
+                ::prost::encoding::uint64::encoded_len(2u32, &meta.fee)
+            } else {
+                0
+            }
+            + ::prost::encoding::uint64::encoded_len_packed(3u32, &meta.pre_balances)
+            + ::prost::encoding::uint64::encoded_len_packed(4u32, &meta.post_balances)
+            + if mask.inner_instructions {
+                message::encoded_len_repeated(5u32, &meta.inner_instructions)
+            } else {
+                0
+            }
+            + if meta.inner_instructions_none || !mask.inner_instructions {
+                ::prost::encoding::bool::encoded_len(10u32, &true)
+            } else {
+                0
+            }
+            + if mask.logs {
+                ::prost::encoding::string::encoded_len_repeated(6u32, &meta.log_messages)
+            } else {
+                0
+            }
+            + if meta.log_messages_none || !mask.logs {
+                ::prost::encoding::bool::encoded_len(11u32, &true)
+            } else {
+                0
+            }
+            + if mask.token_balances {
+                message::encoded_len_repeated(7u32, &meta.pre_token_balances)
+                    + message::encoded_len_repeated(8u32, &meta.post_token_balances)
+            } else {
+                0
+            }
+            + if mask.rewards {
+                message::encoded_len_repeated(9u32, &meta.rewards)
+            } else {
+                0
+            }
+            + ::prost::encoding::bytes::encoded_len_repeated(12u32, &meta.loaded_writable_addresses)
+            + ::prost::encoding::bytes::encoded_len_repeated(13u32, &meta.loaded_readonly_addresses)
+            + meta
+                .return_data
+                .as_ref()
+                .map_or(0, |return_data| message::encoded_len(14u32, return_data))
+            + if meta.return_data_none {
+                ::prost::encoding::bool::encoded_len(15u32, &meta.return_data_none)
+            } else {
+                0
+            }
+            + meta.compute_units_consumed.as_ref().map_or(0, |value| {
+                ::prost::encoding::uint64::encoded_len(16u32, value)
+            })
+    }
+}
+
+// fields of transaction meta included into `FilteredUpdateTransaction`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilteredUpdateTransactionMask {
+    pub logs: bool,
+    pub inner_instructions: bool,
+    pub token_balances: bool,
+    pub rewards: bool,
+}
+
+impl Default for FilteredUpdateTransactionMask {
+    fn default() -> Self {
+        Self {
+            logs: true,
+            inner_instructions: true,
+            token_balances: true,
+            rewards: true,
+        }
+    }
+}
+
+impl FilteredUpdateTransactionMask {
+    pub const fn is_full(&self) -> bool {
+        self.logs && self.inner_instructions && self.token_balances && self.rewards
+    }
+
+    pub fn apply(&self, meta: &mut confirmed_block::TransactionStatusMeta) {
+        if !self.logs {
+            meta.log_messages = vec![];
+            meta.log_messages_none = true;
+        }
+        if !self.inner_instructions {
+            meta.inner_instructions = vec![];
+            meta.inner_instructions_none = true;
+        }
+        if !self.token_balances {
+            meta.pre_token_balances = vec![];
+            meta.post_token_balances = vec![];
+        }
+        if !self.rewards {
+            meta.rewards = vec![];
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -762,7 +1332,12 @@ impl prost::Message for FilteredUpdateBlock {
             message::encode(5u32, msg, buf);
         }
         for tx in &self.transactions {
-            FilteredUpdateTransaction::tx_encode_raw(6u32, tx.as_ref(), buf);
+            FilteredUpdateTransaction::tx_encode_raw(
+                6u32,
+                tx.as_ref(),
+                FilteredUpdateTransactionMask::default(),
+                buf,
+            );
         }
         if self.meta.parent_slot != 0u64 {
             ::prost::encoding::uint64::encode(7u32, &self.meta.parent_slot, buf);
@@ -780,7 +1355,8 @@ impl prost::Message for FilteredUpdateBlock {
             FilteredUpdateAccount::account_encode_raw(
                 11u32,
                 account.as_ref(),
-                &self.accounts_data_slice,
+                Some(&self.accounts_data_slice),
+                None,
                 buf,
             );
         }
@@ -822,7 +1398,10 @@ impl prost::Message for FilteredUpdateBlock {
                 .as_ref()
                 .map_or(0, |msg| message::encoded_len(5u32, msg))
             + prost_repeated_encoded_len_map!(6u32, self.transactions, |tx| {
-                FilteredUpdateTransaction::tx_encoded_len(tx.as_ref())
+                FilteredUpdateTransaction::tx_encoded_len(
+                    tx.as_ref(),
+                    FilteredUpdateTransactionMask::default(),
+                )
             })
             + if self.meta.parent_slot != 0u64 {
                 ::prost::encoding::uint64::encoded_len(7u32, &self.meta.parent_slot)
@@ -847,7 +1426,8 @@ impl prost::Message for FilteredUpdateBlock {
             + prost_repeated_encoded_len_map!(11u32, self.accounts, |account| {
                 FilteredUpdateAccount::account_encoded_len(
                     account.as_ref(),
-                    &self.accounts_data_slice,
+                    Some(&self.accounts_data_slice),
+                    None,
                 )
             })
             + if self.meta.entries_count != 0u64 {
@@ -958,15 +1538,22 @@ pub mod tests {
     #![cfg_attr(feature = "plugin-bench", allow(dead_code))]
     #![cfg_attr(feature = "plugin-bench", allow(unused_imports))]
     use {
-        super::{FilteredUpdate, FilteredUpdateBlock, FilteredUpdateFilters, FilteredUpdateOneof},
+        super::{
+            FilteredUpdate, FilteredUpdateBlock, FilteredUpdateFilters, FilteredUpdateOneof,
+            FilteredUpdateTransaction, FilteredUpdateTransactionMask,
+        },
         crate::{
             convert_to,
-            geyser::{SubscribeUpdate, SubscribeUpdateBlockMeta},
+            geyser::{
+                subscribe_update::UpdateOneof, SlotRollbackReason, SubscribeUpdate,
+                SubscribeUpdateAccountInfoParsed, SubscribeUpdateBlockMeta,
+            },
             plugin::{
                 filter::{name::FilterName, FilterAccountsDataSlice},
                 message::{
                     CommitmentLevel, MessageAccount, MessageAccountInfo, MessageBlockMeta,
-                    MessageEntry, MessageSlot, MessageTransaction, MessageTransactionInfo,
+                    MessageEncodedCache, MessageEntry, MessageSlot, MessageSlotRollback,
+                    MessageTransaction, MessageTransactionInfo,
                 },
             },
         },
@@ -1037,6 +1624,7 @@ pub mod tests {
                                     data: data.clone(),
                                     write_version,
                                     txn_signature,
+                                    encoded: MessageEncodedCache::default(),
                                 }));
                             }
                         }
@@ -1149,6 +1737,8 @@ pub mod tests {
                             meta: convert_to::create_transaction_meta(&tx.meta),
                             index,
                             account_keys: HashSet::new(),
+                            signers: Vec::new(),
+                            encoded: MessageEncodedCache::default(),
                         }
                     })
                     .map(Arc::new)
@@ -1206,10 +1796,13 @@ pub mod tests {
     }
 
     fn encode_decode_cmp(filters: &[&str], message: FilteredUpdateOneof) {
-        let msg = FilteredUpdate {
-            filters: create_message_filters(filters),
+        encode_decode_cmp_update(FilteredUpdate::new(
+            create_message_filters(filters),
             message,
-        };
+        ));
+    }
+
+    fn encode_decode_cmp_update(msg: FilteredUpdate) {
         let update = msg.as_subscribe_update();
         assert_eq!(msg.encoded_len(), update.encoded_len());
         assert_eq!(
@@ -1230,6 +1823,48 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_message_account_parsed() {
+        let parsed = Arc::new(SubscribeUpdateAccountInfoParsed {
+            program: "vote".to_owned(),
+            json: r#"{"type":"vote"}"#.to_owned(),
+            space: 42,
+        });
+        for (msg, data_slice) in create_accounts() {
+            for data_slice in [Some(data_slice), None] {
+                encode_decode_cmp(
+                    &["123"],
+                    FilteredUpdateOneof::account_encoded(
+                        &msg,
+                        data_slice,
+                        Some(Arc::clone(&parsed)),
+                    ),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_message_account_diff() {
+        for account in create_accounts_raw() {
+            let data_len = account.data.len();
+            for is_startup in [true, false] {
+                let msg = MessageAccount {
+                    account: Arc::clone(&account),
+                    slot: 42,
+                    is_startup,
+                };
+                for patches in [
+                    vec![],
+                    vec![0..data_len, data_len..data_len],
+                    vec![0..data_len.min(1), data_len / 2..data_len],
+                ] {
+                    encode_decode_cmp(&["123"], FilteredUpdateOneof::account_diff(&msg, patches));
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_message_slot() {
         for slot in [0, 42] {
@@ -1278,6 +1913,136 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_message_transaction_masked() {
+        for transaction in load_predefined_transactions() {
+            let full = FilteredUpdateTransactionMask::default();
+            assert_eq!(
+                FilteredUpdateTransaction::meta_encoded_len(&transaction.meta, full),
+                transaction.meta.encoded_len()
+            );
+
+            let msg = MessageTransaction {
+                transaction,
+                slot: 42,
+            };
+            for bits in 0..16 {
+                let mask = FilteredUpdateTransactionMask {
+                    logs: bits & 1 != 0,
+                    inner_instructions: bits & 2 != 0,
+                    token_balances: bits & 4 != 0,
+                    rewards: bits & 8 != 0,
+                };
+                encode_decode_cmp(
+                    &["123"],
+                    FilteredUpdateOneof::transaction_masked(&msg, mask),
+                );
+
+                let update =
+                    FilteredUpdate::new_empty(FilteredUpdateOneof::transaction_masked(&msg, mask));
+                let Some(UpdateOneof::Transaction(tx)) =
+                    SubscribeUpdate::decode(update.encode_to_vec().as_slice())
+                        .expect("failed to decode")
+                        .update_oneof
+                else {
+                    panic!("expected transaction update");
+                };
+                let meta = tx.transaction.unwrap().meta.unwrap();
+                let expected = &msg.transaction.meta;
+                assert_eq!(meta.fee, expected.fee);
+                assert_eq!(meta.err, expected.err);
+                assert_eq!(
+                    meta.log_messages_none,
+                    !mask.logs || expected.log_messages_none
+                );
+                if mask.logs {
+                    assert_eq!(meta.log_messages, expected.log_messages);
+                } else {
+                    assert!(meta.log_messages.is_empty());
+                }
+                if mask.inner_instructions {
+                    assert_eq!(meta.inner_instructions, expected.inner_instructions);
+                } else {
+                    assert!(meta.inner_instructions.is_empty());
+                    assert!(meta.inner_instructions_none);
+                }
+                if !mask.token_balances {
+                    assert!(meta.pre_token_balances.is_empty());
+                    assert!(meta.post_token_balances.is_empty());
+                }
+                if !mask.rewards {
+                    assert!(meta.rewards.is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_message_encoded_cache() {
+        for transaction in load_predefined_transactions() {
+            let msg = MessageTransaction {
+                transaction: Arc::new(transaction.as_ref().clone()),
+                slot: 42,
+            };
+            let mask = FilteredUpdateTransactionMask {
+                logs: false,
+                ..Default::default()
+            };
+            FilteredUpdate::new_empty(FilteredUpdateOneof::transaction_masked(&msg, mask))
+                .encode_to_vec();
+            assert!(msg.transaction.encoded.get().is_none());
+
+            let update_a = FilteredUpdate::new(
+                create_message_filters(&["a"]),
+                FilteredUpdateOneof::transaction(&msg),
+            );
+            let update_b = FilteredUpdate::new(
+                create_message_filters(&["b"]),
+                FilteredUpdateOneof::transaction(&msg),
+            );
+            assert_eq!(
+                update_a.encode_to_vec(),
+                update_a.as_subscribe_update().encode_to_vec()
+            );
+            let encoded = msg.transaction.encoded.get().expect("encoded transaction");
+            assert_eq!(
+                encoded,
+                FilteredUpdate::as_subscribe_update_transaction(
+                    &msg.transaction,
+                    FilteredUpdateTransactionMask::default()
+                )
+                .encode_to_vec()
+            );
+            assert_eq!(
+                update_b.encode_to_vec(),
+                update_b.as_subscribe_update().encode_to_vec()
+            );
+            assert!(msg.transaction.as_ref().clone().encoded.get().is_none());
+            assert_eq!(msg.transaction.as_ref().clone(), *msg.transaction);
+        }
+
+        for account in create_accounts_raw() {
+            let msg = MessageAccount {
+                account: Arc::new(account.as_ref().clone()),
+                slot: 42,
+                is_startup: false,
+            };
+            let data_slice = FilterAccountsDataSlice::new_unchecked(Arc::new(vec![0..1, 2..3]));
+            FilteredUpdate::new_empty(FilteredUpdateOneof::account(&msg, data_slice))
+                .encode_to_vec();
+            assert!(msg.account.encoded.get().is_none());
+
+            let update =
+                FilteredUpdate::new_empty(FilteredUpdateOneof::account(&msg, Default::default()));
+            assert_eq!(
+                update.encode_to_vec(),
+                update.as_subscribe_update().encode_to_vec()
+            );
+            assert!(msg.account.encoded.get().is_some());
+            assert_eq!(msg.account.as_ref().clone(), *msg.account);
+        }
+    }
+
     #[test]
     fn test_message_block() {
         for block in load_predefined_blocks() {
@@ -1309,4 +2074,50 @@ pub mod tests {
             encode_decode_cmp(&["123"], FilteredUpdateOneof::entry(entry));
         }
     }
+
+    #[test]
+    fn test_message_transaction_commitment() {
+        for status in [
+            CommitmentLevel::Confirmed,
+            CommitmentLevel::Finalized,
+            CommitmentLevel::Dead,
+        ] {
+            encode_decode_cmp(
+                &["123"],
+                FilteredUpdateOneof::transaction_commitment(&Signature::default(), 42, status),
+            );
+        }
+    }
+
+    #[test]
+    fn test_message_slot_rollback() {
+        for reason in [
+            SlotRollbackReason::AbandonedFork,
+            SlotRollbackReason::DeadSlot,
+        ] {
+            encode_decode_cmp(
+                &[],
+                FilteredUpdateOneof::slot_rollback(&MessageSlotRollback { slot: 42, reason }),
+            );
+        }
+    }
+
+    #[test]
+    fn test_message_commitment() {
+        for commitment in [
+            CommitmentLevel::Processed,
+            CommitmentLevel::Confirmed,
+            CommitmentLevel::Finalized,
+        ] {
+            for entry in create_entries() {
+                encode_decode_cmp_update(
+                    FilteredUpdate::new(
+                        create_message_filters(&["123"]),
+                        FilteredUpdateOneof::entry(entry),
+                    )
+                    .with_commitment(Some(commitment)),
+                );
+            }
+        }
+    }
 }
@@ -3,9 +3,10 @@ use {
         convert_to,
         geyser::{
             subscribe_update::UpdateOneof, CommitmentLevel as CommitmentLevelProto,
-            SubscribeUpdateAccount, SubscribeUpdateAccountInfo, SubscribeUpdateBlock,
-            SubscribeUpdateBlockMeta, SubscribeUpdateEntry, SubscribeUpdateSlot,
-            SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
+            SlotRollbackReason, SubscribeUpdateAccount, SubscribeUpdateAccountInfo,
+            SubscribeUpdateBlock, SubscribeUpdateBlockMeta, SubscribeUpdateEntry,
+            SubscribeUpdateSlot, SubscribeUpdateSlotRollback, SubscribeUpdateTransaction,
+            SubscribeUpdateTransactionInfo,
         },
         solana::storage::confirmed_block,
     },
@@ -21,13 +22,43 @@ use {
     },
     std::{
         collections::HashSet,
+        fmt,
         ops::{Deref, DerefMut},
-        sync::Arc,
+        sync::{Arc, OnceLock},
     },
 };
 
 type FromUpdateOneofResult<T> = Result<T, &'static str>;
 
+/// Protobuf encoded message, created on first use and shared between all clients.
+/// Not copied on clone because cloned message can be modified.
+#[derive(Default)]
+pub struct MessageEncodedCache(OnceLock<Vec<u8>>);
+
+impl Clone for MessageEncodedCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for MessageEncodedCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MessageEncodedCache")
+            .field(&self.0.get().map(|encoded| encoded.len()))
+            .finish()
+    }
+}
+
+impl MessageEncodedCache {
+    pub fn get(&self) -> Option<&[u8]> {
+        self.0.get().map(|encoded| encoded.as_slice())
+    }
+
+    pub fn get_or_encode(&self, encode: impl FnOnce() -> Vec<u8>) -> &[u8] {
+        self.0.get_or_init(encode)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum CommitmentLevel {
     Processed,
@@ -129,7 +160,23 @@ impl MessageSlot {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageSlotRollback {
+    pub slot: Slot,
+    pub reason: SlotRollbackReason,
+}
+
+impl MessageSlotRollback {
+    pub fn from_update_oneof(msg: &SubscribeUpdateSlotRollback) -> FromUpdateOneofResult<Self> {
+        Ok(Self {
+            slot: msg.slot,
+            reason: SlotRollbackReason::try_from(msg.reason)
+                .map_err(|_| "failed to parse rollback reason")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct MessageAccountInfo {
     pub pubkey: Pubkey,
     pub lamports: u64,
@@ -139,6 +186,22 @@ pub struct MessageAccountInfo {
     pub data: Vec<u8>,
     pub write_version: u64,
     pub txn_signature: Option<Signature>,
+    // full account without data slice
+    pub encoded: MessageEncodedCache,
+}
+
+impl PartialEq for MessageAccountInfo {
+    // encoded message is a cache and not compared
+    fn eq(&self, other: &Self) -> bool {
+        self.pubkey == other.pubkey
+            && self.lamports == other.lamports
+            && self.owner == other.owner
+            && self.executable == other.executable
+            && self.rent_epoch == other.rent_epoch
+            && self.data == other.data
+            && self.write_version == other.write_version
+            && self.txn_signature == other.txn_signature
+    }
 }
 
 impl MessageAccountInfo {
@@ -152,6 +215,7 @@ impl MessageAccountInfo {
             data: info.data.into(),
             write_version: info.write_version,
             txn_signature: info.txn.map(|txn| *txn.signature()),
+            encoded: MessageEncodedCache::default(),
         }
     }
 
@@ -170,6 +234,7 @@ impl MessageAccountInfo {
                     Signature::try_from(sig.as_slice()).map_err(|_| "invalid signature length")
                 })
                 .transpose()?,
+            encoded: MessageEncodedCache::default(),
         })
     }
 }
@@ -201,7 +266,7 @@ impl MessageAccount {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct MessageTransactionInfo {
     pub signature: Signature,
     pub is_vote: bool,
@@ -209,15 +274,33 @@ pub struct MessageTransactionInfo {
     pub meta: confirmed_block::TransactionStatusMeta,
     pub index: usize,
     pub account_keys: HashSet<Pubkey>,
+    // signers from message header, first one is fee payer
+    pub signers: Vec<Pubkey>,
+    // transaction with all fields of meta
+    pub encoded: MessageEncodedCache,
+}
+
+impl PartialEq for MessageTransactionInfo {
+    // encoded message is a cache and not compared
+    fn eq(&self, other: &Self) -> bool {
+        self.signature == other.signature
+            && self.is_vote == other.is_vote
+            && self.transaction == other.transaction
+            && self.meta == other.meta
+            && self.index == other.index
+            && self.account_keys == other.account_keys
+            && self.signers == other.signers
+    }
 }
 
 impl MessageTransactionInfo {
     pub fn from_geyser(info: &ReplicaTransactionInfoV2<'_>) -> Self {
-        let account_keys = info
-            .transaction
-            .message()
+        let message = info.transaction.message();
+        let account_keys = message.account_keys().iter().copied().collect();
+        let signers = message
             .account_keys()
             .iter()
+            .take(message.header().num_required_signatures as usize)
             .copied()
             .collect();
 
@@ -228,6 +311,8 @@ impl MessageTransactionInfo {
             meta: convert_to::create_transaction_meta(info.transaction_status_meta),
             index: info.index,
             account_keys,
+            signers,
+            encoded: MessageEncodedCache::default(),
         }
     }
 
@@ -242,23 +327,29 @@ impl MessageTransactionInfo {
             meta: msg.meta.ok_or("meta message should be defined")?,
             index: msg.index as usize,
             account_keys: HashSet::new(),
+            signers: Vec::new(),
+            encoded: MessageEncodedCache::default(),
         })
     }
 
     pub fn fill_account_keys(&mut self) -> FromUpdateOneofResult<()> {
         let mut account_keys = HashSet::new();
+        let mut signers = Vec::new();
 
         // static
-        if let Some(pubkeys) = self
-            .transaction
-            .message
-            .as_ref()
-            .map(|msg| msg.account_keys.as_slice())
-        {
-            for pubkey in pubkeys {
-                account_keys.insert(
-                    Pubkey::try_from(pubkey.as_slice()).map_err(|_| "invalid pubkey length")?,
-                );
+        if let Some(msg) = self.transaction.message.as_ref() {
+            let num_required_signatures = msg
+                .header
+                .as_ref()
+                .map(|header| header.num_required_signatures as usize)
+                .unwrap_or_default();
+            for (index, pubkey) in msg.account_keys.iter().enumerate() {
+                let pubkey =
+                    Pubkey::try_from(pubkey.as_slice()).map_err(|_| "invalid pubkey length")?;
+                if index < num_required_signatures {
+                    signers.push(pubkey);
+                }
+                account_keys.insert(pubkey);
             }
         }
 
@@ -273,6 +364,7 @@ impl MessageTransactionInfo {
         }
 
         self.account_keys = account_keys;
+        self.signers = signers;
         Ok(())
     }
 }
@@ -439,6 +531,7 @@ impl MessageBlock {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Message {
     Slot(MessageSlot),
+    SlotRollback(MessageSlotRollback),
     Account(MessageAccount),
     Transaction(MessageTransaction),
     Entry(Arc<MessageEntry>),
@@ -450,6 +543,7 @@ impl Message {
     pub fn get_slot(&self) -> u64 {
         match self {
             Self::Slot(msg) => msg.slot,
+            Self::SlotRollback(msg) => msg.slot,
             Self::Account(msg) => msg.slot,
             Self::Transaction(msg) => msg.slot,
             Self::Entry(msg) => msg.slot,
@@ -461,7 +555,11 @@ impl Message {
     pub fn from_update_oneof(oneof: UpdateOneof) -> FromUpdateOneofResult<Self> {
         Ok(match oneof {
             UpdateOneof::Account(msg) => Self::Account(MessageAccount::from_update_oneof(msg)?),
+            UpdateOneof::AccountDiff(_) => return Err("AccountDiff message is not supported"),
             UpdateOneof::Slot(msg) => Self::Slot(MessageSlot::from_update_oneof(&msg)?),
+            UpdateOneof::SlotRollback(msg) => {
+                Self::SlotRollback(MessageSlotRollback::from_update_oneof(&msg)?)
+            }
             UpdateOneof::Transaction(msg) => {
                 Self::Transaction(MessageTransaction::from_update_oneof(msg)?)
             }
@@ -471,6 +569,11 @@ impl Message {
             UpdateOneof::Block(msg) => Self::Block(Arc::new(MessageBlock::from_update_oneof(msg)?)),
             UpdateOneof::Ping(_) => return Err("Ping message is not supported"),
             UpdateOneof::Pong(_) => return Err("Pong message is not supported"),
+            UpdateOneof::RateLimited(_) => return Err("RateLimited message is not supported"),
+            UpdateOneof::Lagged(_) => return Err("Lagged message is not supported"),
+            UpdateOneof::TransactionCommitment(_) => {
+                return Err("TransactionCommitment message is not supported")
+            }
             UpdateOneof::BlockMeta(msg) => Self::BlockMeta(Arc::new(MessageBlockMeta(msg))),
             UpdateOneof::Entry(msg) => {
                 Self::Entry(Arc::new(MessageEntry::from_update_oneof(&msg)?))